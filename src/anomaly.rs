@@ -0,0 +1,236 @@
+//! Data-quality flags: extracted values that cannot all be true.
+//!
+//! An age of 120, or a death dated before the same person's appointment,
+//! almost always means a regex picked up the wrong person or the wrong
+//! time scope. The flags are for triage; nothing is dropped from the
+//! event output.
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::event::{Event, EventKind, TimeIndex, exact_ad_year};
+use crate::regime::ERA_NAMES;
+
+/// Plausible age at death, in sui.
+const AGE_RANGE: std::ops::RangeInclusive<u16> = 1..=100;
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "type")]
+pub enum AnomalyKind {
+    /// 時年N / 春秋N outside `AGE_RANGE`
+    ImplausibleAge { age: u16 },
+    /// Dated appointment later than the person's dated death
+    AppointmentAfterDeath { appointed_ad: u16, death_ad: u16 },
+    /// Enthronement (立X為帝) later than X's dated death
+    AccessionAfterDeath { accession_ad: u16, death_ad: u16 },
+}
+
+#[derive(Debug, Serialize)]
+pub struct Anomaly {
+    pub person: String,
+    #[serde(flatten)]
+    pub kind: AnomalyKind,
+    /// The event the flag is raised on
+    pub source_file: String,
+    pub byte_offset: usize,
+    pub context: String,
+}
+
+/// (regime, AD year) of a dated event.
+fn event_ad_year(e: &Event) -> Option<(&str, u16)> {
+    let t = e.time.as_ref()?;
+    Some((&t.regime, exact_ad_year(&t.regime, &t.era, t.year)?))
+}
+
+/// Flag implausible ages, appointments after death and accessions after
+/// death. A person's death year is their earliest dated death event, so a
+/// duplicate notice with a wrong date does not hide a real conflict.
+/// Deaths and appointments are only compared within one regime: bare
+/// names like 武帝 recur in every dynasty.
+pub fn find_anomalies(events: &[Event]) -> Vec<Anomaly> {
+    let mut death_ad: HashMap<(&str, &str), u16> = HashMap::new();
+    for e in events {
+        if let EventKind::Death { person, .. } = &e.kind
+            && let Some((regime, ad)) = event_ad_year(e)
+        {
+            death_ad
+                .entry((person.as_str(), regime))
+                .and_modify(|d| *d = (*d).min(ad))
+                .or_insert(ad);
+        }
+    }
+
+    let flag = |person: &str, kind, e: &Event| Anomaly {
+        person: person.to_string(),
+        kind,
+        source_file: e.source_file.clone(),
+        byte_offset: e.byte_offset,
+        context: e.context.clone(),
+    };
+    let mut anomalies = Vec::new();
+    for e in events {
+        match &e.kind {
+            EventKind::Death {
+                person,
+                age_at_death: Some(age),
+                ..
+            } if !AGE_RANGE.contains(age) => {
+                let kind = AnomalyKind::ImplausibleAge { age: *age };
+                anomalies.push(flag(person, kind, e));
+            }
+            EventKind::Appointment { person, .. } => {
+                if let Some((regime, appointed_ad)) = event_ad_year(e)
+                    && let Some(&death_ad) = death_ad.get(&(person.as_str(), regime))
+                    && appointed_ad > death_ad
+                {
+                    let kind = AnomalyKind::AppointmentAfterDeath {
+                        appointed_ad,
+                        death_ad,
+                    };
+                    anomalies.push(flag(person, kind, e));
+                }
+            }
+            EventKind::Investiture { person, role, .. } if role.ends_with('帝') => {
+                if let Some((regime, accession_ad)) = event_ad_year(e)
+                    && let Some(&death_ad) = death_ad.get(&(person.as_str(), regime))
+                    && accession_ad > death_ad
+                {
+                    let kind = AnomalyKind::AccessionAfterDeath {
+                        accession_ad,
+                        death_ad,
+                    };
+                    anomalies.push(flag(person, kind, e));
+                }
+            }
+            _ => {}
+        }
+    }
+    anomalies
+}
+
+/// A dated reference whose regnal year runs past the end of the era it
+/// was resolved to. When no regime's era of that name lasted long enough,
+/// `regime::resolve_era_year` keeps the Book's default, so a 北魏 era
+/// cited in 宋書 under a name 劉宋 also used ends up mis-scoped.
+#[derive(Debug, Serialize)]
+pub struct CrossRegimeRef {
+    /// As written, e.g. 元嘉四十年
+    pub raw: String,
+    pub regime: String,
+    pub era: String,
+    pub year: u8,
+    /// How many years the resolved era lasted
+    pub era_years: u16,
+    pub source_file: String,
+    pub byte_offset: usize,
+}
+
+/// Time references whose year the resolved era's span does not cover
+/// (`TimeRef::suspect`): possible cross-regime references, or copying
+/// errors in the year. Listed once per era-year as written, not again for
+/// each month/day marker dated by it.
+pub fn find_cross_regime_refs(index: &TimeIndex) -> Vec<CrossRegimeRef> {
+    index
+        .scopes
+        .iter()
+        .filter(|s| s.time.suspect && s.time.names_era())
+        .filter_map(|s| {
+            let t = &s.time;
+            let era_years = ERA_NAMES
+                .iter()
+                .filter(|e| e.name == t.era && e.regime.as_chinese() == t.regime)
+                .map(|e| e.end_ad - e.start_ad + 1)
+                .max()?;
+            Some(CrossRegimeRef {
+                raw: t.raw.clone(),
+                regime: t.regime.clone(),
+                era: t.era.clone(),
+                year: t.year,
+                era_years,
+                source_file: s.span.file.clone(),
+                byte_offset: t.byte_offset,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::EventScanner;
+    use crate::types::{Book, Section};
+
+    fn scan(text: &str) -> Vec<Event> {
+        let (events, _) =
+            EventScanner::new(&[]).scan_file(text, Book::SongShu, Section::LieZhuan, "t");
+        events
+    }
+
+    fn cross_refs(text: &str) -> Vec<CrossRegimeRef> {
+        let (_, scopes) =
+            EventScanner::new(&[]).scan_file(text, Book::SongShu, Section::LieZhuan, "t");
+        find_cross_regime_refs(&TimeIndex {
+            scopes,
+            era_refs: Vec::new(),
+        })
+    }
+
+    #[test]
+    fn test_flags_implausible_age() {
+        let anomalies = find_anomalies(&scan("元嘉三年，王進卒，時年一百二十。"));
+        assert!(
+            matches!(
+                anomalies.as_slice(),
+                [Anomaly {
+                    kind: AnomalyKind::ImplausibleAge { age: 120 },
+                    ..
+                }]
+            ),
+            "{anomalies:?}"
+        );
+        assert!(find_anomalies(&scan("元嘉三年，王進卒，時年六十三。")).is_empty());
+        // an age in the sentence before the death notice is not the deceased's
+        assert!(find_anomalies(&scan("元嘉三年，張華時年一百二十。王進卒。")).is_empty());
+    }
+
+    #[test]
+    fn test_flags_appointment_after_death() {
+        let events = scan("元嘉三年，王進卒。\n元嘉九年，以王進為侍中。");
+        let anomalies = find_anomalies(&events);
+        assert!(
+            matches!(
+                anomalies.as_slice(),
+                [Anomaly {
+                    kind: AnomalyKind::AppointmentAfterDeath {
+                        appointed_ad: 432,
+                        death_ad: 426
+                    },
+                    ..
+                }]
+            ),
+            "{anomalies:?}"
+        );
+        // the normal order raises nothing
+        let events = scan("元嘉三年，以王進為侍中。\n元嘉九年，王進卒。");
+        assert!(find_anomalies(&events).is_empty());
+    }
+
+    #[test]
+    fn test_flags_year_past_end_of_default_era() {
+        // 劉宋 景平 ran 423–424: two years
+        let refs = cross_refs("景平五年，王進卒。");
+        assert_eq!(refs.len(), 1, "{refs:?}");
+        assert_eq!(
+            (
+                refs[0].regime.as_str(),
+                refs[0].era_years,
+                refs[0].byte_offset
+            ),
+            ("劉宋", 2, 0)
+        );
+        assert!(cross_refs("景平二年，王進卒。").is_empty());
+        // the month marker dated by it is not listed a second time
+        assert_eq!(cross_refs("景平五年，王進卒。二月，張華卒。").len(), 1);
+    }
+}
@@ -3,17 +3,19 @@
 //! Extracts structured events (appointments, battles, deaths, transfers)
 //! with associated time references and place names from the corpus.
 
-use std::collections::HashMap;
-use std::fs;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::LazyLock;
 
+use rayon::prelude::*;
 use regex::Regex;
 use serde::Serialize;
 
 use crate::regime;
-use crate::scanner::BiographyFile;
+use crate::scanner::{BiographyFile, read_text};
 use crate::surname::build_name_regex;
-use crate::titles::build_title_regex;
-use crate::types::{Book, Person, PersonKind};
+use crate::surname::{is_known_surname, split_name};
+use crate::titles::{OFFICE_HEADS, STANDALONE_TITLES, TITLE_SUFFIXES, is_office, title_regex};
+use crate::types::{Book, Person, PersonKind, Section};
 
 // ── Byte span in a source file ───────────────────────────────────────
 
@@ -32,17 +34,105 @@ pub struct TextSpan {
 pub struct TimeRef {
     pub era: String,
     pub regime: String,
+    /// Year of the era; 0 for era-only references
     pub year: u8,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub month: Option<u8>,
+    /// 閏月: the intercalary month after `month`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub is_leap: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub day_ganzhi: Option<String>,
+    /// Numbered day of the month (五月十五日 → 15), when the text counts
+    /// days instead of naming them by ganzhi
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub day_num: Option<u8>,
+    /// Day of the month counted from the month's 朔 (first day) when the
+    /// text gives it earlier (正月甲子朔 … 正月丁卯 → 4)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub day: Option<u8>,
+    /// Sexagenary year the text gives (歲在甲子), whether it resolved
+    /// `year` or, with no era to resolve against, stands alone (year 0)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub year_ganzhi: Option<String>,
+    /// `year` runs past the end of the era (元嘉四十三年, when 元嘉 lasted
+    /// 30 years); kept for review, as it is most likely a copying error
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub suspect: bool,
+    /// How much of the date the text pins down. Files written before this
+    /// field existed read as `Year`.
+    #[serde(default)]
+    pub precision: TimePrecision,
+    /// How the text gives the reference. Files written before this field
+    /// existed read as `Era`.
+    #[serde(default)]
+    pub origin: TimeOrigin,
     /// Raw matched text
     pub raw: String,
     /// Byte offset where this time reference appears in the source file
     pub byte_offset: usize,
 }
 
+/// How precisely a `TimeRef` is dated, ordered from least to most precise
+/// so `precision >= TimePrecision::Year` keeps refs with at least a year.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, serde::Deserialize,
+)]
+pub enum TimePrecision {
+    /// An offset from another reference (明年, 後三年)
+    Relative,
+    /// A sexagenary year (歲在甲子) with no era to read it against: year 0,
+    /// no era name, and `year_ganzhi` set
+    Sexagenary,
+    /// Era only, or a part of it (元嘉中, 永明末)
+    Era,
+    /// Era and year: 元嘉三年
+    #[default]
+    Year,
+    /// Era, year and month, with or without a ganzhi day: 元嘉三年五月甲子
+    Exact,
+}
+
+/// Where a `TimeRef`'s era comes from: the text itself, or an era-year
+/// reference nearby.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, serde::Deserialize)]
+pub enum TimeOrigin {
+    /// The era is written out: 元嘉三年, 太和中
+    #[default]
+    Era,
+    /// A bare month/day marker (二月，/ 丁卯，) dated by the era-year before it
+    DayMarker,
+    /// A sexagenary year (歲在庚午) read against a nearby era-year
+    GanzhiYear,
+}
+
+impl TimeRef {
+    /// Whether the text writes out this reference's era-year (元嘉三年),
+    /// rather than a month/day marker or 歲在 year dated by one.
+    pub fn names_era(&self) -> bool {
+        self.origin == TimeOrigin::Era
+    }
+
+    /// Approximate ISO-8601 date: "0477", or "0477-05" when a month is known.
+    ///
+    /// The year is `exact_ad_year` read as proleptic Julian and the month is
+    /// the lunar month number copied as-is. The Chinese year starts in late
+    /// January or February, so 正月/臘月 events can fall in the neighbouring
+    /// Julian year, and a leap month (閏) shares its number with the month
+    /// before it. Good enough to place events on an external timeline, not
+    /// for day-level conversion.
+    pub fn to_iso_approx(&self) -> Option<String> {
+        let ad = exact_ad_year(&self.regime, &self.era, self.year)?;
+        Some(match self.month {
+            Some(m) => format!("{ad:04}-{m:02}"),
+            None => format!("{ad:04}"),
+        })
+    }
+}
+
+/// A time reference and the byte offset in the scanned text it starts at.
+type TimeAt = (usize, TimeRef);
+
 // ── Time scope ───────────────────────────────────────────────────────
 
 /// The region of text governed by a single time reference.
@@ -60,6 +150,11 @@ pub struct TimeScope {
 #[derive(Debug, Clone, Serialize, serde::Deserialize)]
 pub struct TimeIndex {
     pub scopes: Vec<TimeScope>,
+    /// Era-only references (太和中, 元嘉末), year 0 and `TimePrecision::Era`,
+    /// and sexagenary years no era resolved (`TimePrecision::Sexagenary`).
+    /// Kept apart from `scopes` so year-based consumers never see them.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub era_refs: Vec<TimeScope>,
 }
 
 impl TimeIndex {
@@ -86,6 +181,49 @@ impl TimeIndex {
             .filter(|s| s.time.regime == regime)
             .collect()
     }
+
+    /// For each source file, the eras its time scopes mention, with the
+    /// lowest and highest year seen, sorted chronologically.
+    pub fn file_eras(&self) -> BTreeMap<String, Vec<FileEra>> {
+        let mut map: BTreeMap<String, Vec<FileEra>> = BTreeMap::new();
+        for s in &self.scopes {
+            let eras = map.entry(s.span.file.clone()).or_default();
+            let t = &s.time;
+            match eras
+                .iter_mut()
+                .find(|e| e.regime == t.regime && e.era == t.era)
+            {
+                Some(e) => {
+                    e.year_from = e.year_from.min(t.year);
+                    e.year_to = e.year_to.max(t.year);
+                }
+                None => eras.push(FileEra {
+                    regime: t.regime.clone(),
+                    era: t.era.clone(),
+                    year_from: t.year,
+                    year_to: t.year,
+                }),
+            }
+        }
+        for eras in map.values_mut() {
+            eras.sort_by_key(|e| {
+                (
+                    exact_ad_year(&e.regime, &e.era, e.year_from).unwrap_or(u16::MAX),
+                    era_sort_key(&e.regime, &e.era),
+                )
+            });
+        }
+        map
+    }
+}
+
+/// One era observed in a file, see `TimeIndex::file_eras`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, serde::Deserialize)]
+pub struct FileEra {
+    pub regime: String,
+    pub era: String,
+    pub year_from: u8,
+    pub year_to: u8,
 }
 
 // ── Timeline: full era-year inventory ───────────────────────────────
@@ -106,6 +244,19 @@ pub struct EraTimeline {
     pub era: String,
     /// Years sorted ascending, each with occurrence counts.
     pub years: Vec<TimePoint>,
+    /// `coverage_ratio` against the era's entry in `regime::ERA_NAMES`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub coverage: Option<f32>,
+}
+
+impl EraTimeline {
+    /// Distinct observed years over the years the era lasted, clamped to
+    /// 1.0: years past the era's end (see `anomaly::find_cross_regime_refs`)
+    /// would otherwise count towards a span they are not part of.
+    pub fn coverage_ratio(&self, era_entry: &regime::EraEntry) -> f32 {
+        let span = era_entry.end_ad - era_entry.start_ad + 1;
+        (self.years.len() as f32 / f32::from(span)).min(1.0)
+    }
 }
 
 /// All eras observed for one regime.
@@ -157,7 +308,16 @@ impl Timeline {
                     .into_iter()
                     .map(|(era, mut years)| {
                         years.sort_by_key(|tp| tp.year);
-                        EraTimeline { era, years }
+                        let mut et = EraTimeline {
+                            era,
+                            years,
+                            coverage: None,
+                        };
+                        et.coverage = regime::ERA_NAMES
+                            .iter()
+                            .find(|e| e.name == et.era && e.regime.as_chinese() == regime)
+                            .map(|e| et.coverage_ratio(e));
+                        et
                     })
                     .collect();
                 // Sort eras by position in ERA_NAMES (chronological within regime)
@@ -191,6 +351,31 @@ pub struct PlaceRef {
     pub is_qiao: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub role_suffix: Option<String>, // 刺史, 太守, etc.
+    /// Several units at once (諸郡, 數郡, 三郡): a regional campaign
+    /// rather than one place
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub collective: bool,
+    /// What a collective is qualified by, e.g. 江南 in 江南諸郡
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
+    /// Which of several homonymous prefectures is meant, from the event's
+    /// regime and year (see `gazetteer::resolve_prefecture`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolved_id: Option<String>,
+}
+
+impl PlaceRef {
+    /// A single, non-qiao place with nothing known beyond its name.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            is_qiao: false,
+            role_suffix: None,
+            collective: false,
+            region: None,
+            resolved_id: None,
+        }
+    }
 }
 
 // ── Event types ──────────────────────────────────────────────────────
@@ -212,9 +397,230 @@ pub enum EventKind {
         target: String,
         #[serde(default, skip_serializing_if = "Option::is_none")]
         target_place: Option<PlaceRef>,
+        /// The target when it is an opposing commander (攻王進) rather
+        /// than a place
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        target_person: Option<String>,
+        /// Troop count stated in the same sentence (將兵三萬 → 30000)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        troops: Option<u32>,
+        /// Unit descriptors in the same sentence (部曲, 三營, 五軍)
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        units: Vec<String>,
+    },
+    /// X薨/卒/崩 — death; X遇害/為Y所殺 — violent death
+    Death {
+        person: String,
+        verb: String,
+        #[serde(default)]
+        manner: DeathManner,
+        /// Agent of a violent death, from "為(killer)所殺"
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        killer: Option<String>,
+        /// Office granted in the same sentence: 薨，贈司空
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        posthumous_title: Option<String>,
+        /// 謚 in the same sentence: 謚曰忠
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        posthumous_name: Option<String>,
+        /// Age in the same sentence: 卒，時年七十二 / 春秋六十. Counted in
+        /// 歲 (1 at birth), as the sources do
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        age_at_death: Option<u16>,
+    },
+    /// 封X為Y侯 / 改封Y王 — enfeoffment (or re-enfeoffment to a new fief)
+    Enfeoffment {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        person: Option<String>,
+        verb: String,
+        /// Fief place, e.g. 武昌
+        fief: String,
+        /// Noble rank, e.g. 王, 縣侯
+        rank: String,
+        /// 改封/徙封: moved from a prior (usually unrecorded) fief
+        #[serde(default)]
+        is_reassignment: bool,
+    },
+    /// X為Y所敗 — passive defeat. Patient-centric like `Death`: `person`
+    /// is the side that lost, `victor` the agent of 為…所, which is often
+    /// a state or a collective (魏, 賊) rather than a named person.
+    Defeat {
+        person: String,
+        /// 敗/破/擒/執/獲/虜/逐/圍/襲/困
+        verb: String,
+        victor: String,
+    },
+    /// X遣Y攻Z / 遣Y使Z — X sends a subordinate general or envoy
+    Dispatch {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        dispatcher: Option<String>,
+        /// The person sent
+        agent: String,
+        /// What they were sent to do, e.g. 攻建康, 使魏
+        purpose: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        troops: Option<u32>,
+        /// Unit descriptors in the same sentence, as for `Battle`
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        units: Vec<String>,
+    },
+    /// 罷X / 去X / X徵還 / 代X為Y — the end of a posting. With 代, `person`
+    /// is the one replaced and `successor` the one who took over, so
+    /// successive holders of an office can be chained.
+    TenureEnd {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        person: Option<String>,
+        /// 罷/去/徵還/代
+        verb: String,
+        /// The post or place left, e.g. 荊州, 南蠻校尉
+        office_or_place: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        successor: Option<String>,
+    },
+    /// 固辭 / 辭不拜 / 讓X / 不拜 — an appointment turned down. `office` is
+    /// named after the verb, or taken from the appointment earlier in the
+    /// same sentence (以X為侍中，固辭不拜).
+    Decline {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        person: Option<String>,
+        verb: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        office: Option<String>,
+    },
+    /// X立Y為帝/太子/皇后 — installing someone else in a role, as opposed
+    /// to taking the throne oneself. `person` may be a kin term (立子為帝)
+    /// read relative to `by`.
+    Investiture {
+        person: String,
+        /// 帝/皇帝/太子/皇太子/皇后/王
+        role: String,
+        /// The enthroner, named before 立 or, in 本紀, the emperor
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        by: Option<String>,
+    },
+    /// X平/定/克定Y — a campaign's outcome: territory brought under
+    /// control, as opposed to the attack recorded by `Battle`
+    Pacification {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        person: Option<String>,
+        /// 平/定/平定/克定
+        verb: String,
+        place: PlaceRef,
+    },
+    /// 贈X / 追贈X / 追封X為Y侯 — an office or fief granted after death
+    PosthumousGrant {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        person: Option<String>,
+        /// 贈/追贈/追封
+        verb: String,
+        /// The office (司空) or fief and rank (武昌縣侯)
+        title: String,
+        /// 追贈/追封: granted well after the death, often by a later
+        /// reign, rather than in the death notice itself
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        retroactive: bool,
+    },
+    /// X禪位於Y / X遜位 — a ruler giving up the throne, the hinge between
+    /// two regimes
+    Abdication {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        person: Option<String>,
+        /// 禪位/禪/遜位/遜
+        verb: String,
+        /// The successor, when named after 禪(位)於
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        to: Option<String>,
+    },
+    /// X僭號，改元Y — a usurper or rival ruler declaring their own era
+    UsurpationEra {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        person: Option<String>,
+        /// Declared era name, e.g. 龍昇
+        era: String,
+        /// False when `era` is missing from `regime::ERA_NAMES` — a
+        /// candidate for adding to the table
+        known_era: bool,
+    },
+    /// 徙X戶於Y / 遷其民於Y — a population resettled, as opposed to the
+    /// official transfer 遷 usually means
+    PopulationMove {
+        /// Who ordered the move, named before the verb or, in 本紀, the emperor
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        person: Option<String>,
+        /// 遷/徙
+        verb: String,
+        /// Who was moved: 豪族, 其民, 長安民
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        group: Option<String>,
+        /// 三千戶 → 3000
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        count: Option<u32>,
+        /// 戶/家/口, when a count is given
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        unit: Option<String>,
+        /// Destination, after 於/于/以實
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        to: Option<String>,
+    },
+    /// X遣使朝貢 / 遣使求救於Y / 朝于Y — a mission between courts
+    Diplomacy {
+        /// Sending court or ruler: 高麗國, 蕭衍, 吐谷渾、宕昌國
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        sender: Option<String>,
+        /// Court addressed, after 於/于
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        target: Option<String>,
+        /// 朝貢, 朝獻, 貢獻, 通好, 請和, 求救, or 朝 (a ruler's own visit)
+        kind: String,
+    },
+    /// 日有蝕之 / 京師地震 / 雍州大水 — an omen or natural disaster
+    Omen {
+        /// The phenomenon as written: 日有蝕之, 地震, 大水, 旱, 蝗, 星孛 ...
+        phenomenon: String,
+        /// Where it struck, when the text names a province or the capital
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        place: Option<PlaceRef>,
+    },
+    /// X反 / X叛 / X據Y反 / X舉兵反 — a rebellion or defection
+    Rebellion {
+        person: String,
+        /// 反, 叛, 舉兵反, or 據…反 as written
+        verb: String,
+        /// The base held, from 據Y反
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        place: Option<PlaceRef>,
+    },
+    /// X降 / X以城降Y / X率眾歸降於Y / X款附 — a surrender or submission
+    Surrender {
+        person: String,
+        /// 降, 歸降, 請降, 款附, with any 以城/率眾 before it
+        verb: String,
+        /// Side surrendered to, after 於/于 or a state right after 降 (降魏)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        to: Option<String>,
+    },
+    /// X尚Y公主 / X娶Y女 / X妻以Y — a marriage. With 妻以 the subject is
+    /// the one giving the bride (妻以女: gave him a daughter to wife).
+    Marriage {
+        person: String,
+        /// 尚, 娶, or 妻以 as written
+        verb: String,
+        /// The phrase after the verb, up to the next punctuation
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        spouse: Option<String>,
     },
-    /// X薨/卒/崩 — death
-    Death { person: String, verb: String },
+}
+
+/// How a person died, as far as the verb tells us.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, serde::Deserialize)]
+pub enum DeathManner {
+    /// 薨/卒/崩
+    #[default]
+    Natural,
+    /// 被害/見殺/遇害/為X所殺
+    Killed,
+    /// 伏誅/棄市/被誅/為X所誅
+    Executed,
 }
 
 /// A single extracted event with optional time context.
@@ -230,16 +636,231 @@ pub struct Event {
     /// All place references found in the event's context window.
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub locations: Vec<PlaceRef>,
+    /// Scanner regex that produced this event, e.g. "re_battle".
+    /// Only set with `--debug-provenance`.
+    #[serde(default, skip_deserializing, skip_serializing_if = "Option::is_none")]
+    pub extracted_by: Option<&'static str>,
+    /// Index into `TimeIndex::scopes` of the scope `time` came from, so the
+    /// whole governed passage can be pulled up, not just `context`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope_index: Option<usize>,
+    /// The biography record (`PersonSummary::file`) of the person the event
+    /// names, when it is certain: the subject acting in their own 列傳.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub person_id: Option<String>,
 }
 
-impl Event {
-    /// Extract the person name from this event's kind.
-    pub fn person_name(&self) -> &str {
-        match &self.kind {
+impl EventKind {
+    // `name` of each variant, shared by `NAMES`, `name` and the scanner's
+    // per-kind context radius so a misspelt kind does not compile.
+    pub const APPOINTMENT: &str = "Appointment";
+    pub const BATTLE: &str = "Battle";
+    pub const DEATH: &str = "Death";
+    pub const ENFEOFFMENT: &str = "Enfeoffment";
+    pub const DEFEAT: &str = "Defeat";
+    pub const DISPATCH: &str = "Dispatch";
+    pub const TENURE_END: &str = "TenureEnd";
+    pub const DECLINE: &str = "Decline";
+    pub const INVESTITURE: &str = "Investiture";
+    pub const PACIFICATION: &str = "Pacification";
+    pub const POSTHUMOUS_GRANT: &str = "PosthumousGrant";
+    pub const ABDICATION: &str = "Abdication";
+    pub const USURPATION_ERA: &str = "UsurpationEra";
+    pub const POPULATION_MOVE: &str = "PopulationMove";
+    pub const DIPLOMACY: &str = "Diplomacy";
+    pub const OMEN: &str = "Omen";
+    pub const REBELLION: &str = "Rebellion";
+    pub const SURRENDER: &str = "Surrender";
+    pub const MARRIAGE: &str = "Marriage";
+
+    /// Every variant's `name`, in declaration order.
+    pub const NAMES: &[&str] = &[
+        Self::APPOINTMENT,
+        Self::BATTLE,
+        Self::DEATH,
+        Self::ENFEOFFMENT,
+        Self::DEFEAT,
+        Self::DISPATCH,
+        Self::TENURE_END,
+        Self::DECLINE,
+        Self::INVESTITURE,
+        Self::PACIFICATION,
+        Self::POSTHUMOUS_GRANT,
+        Self::ABDICATION,
+        Self::USURPATION_ERA,
+        Self::POPULATION_MOVE,
+        Self::DIPLOMACY,
+        Self::OMEN,
+        Self::REBELLION,
+        Self::SURRENDER,
+        Self::MARRIAGE,
+    ];
+
+    /// Variant name, as used for the serialized `type` tag.
+    pub fn name(&self) -> &'static str {
+        match self {
+            EventKind::Appointment { .. } => Self::APPOINTMENT,
+            EventKind::Battle { .. } => Self::BATTLE,
+            EventKind::Death { .. } => Self::DEATH,
+            EventKind::Enfeoffment { .. } => Self::ENFEOFFMENT,
+            EventKind::Defeat { .. } => Self::DEFEAT,
+            EventKind::Dispatch { .. } => Self::DISPATCH,
+            EventKind::TenureEnd { .. } => Self::TENURE_END,
+            EventKind::Decline { .. } => Self::DECLINE,
+            EventKind::Investiture { .. } => Self::INVESTITURE,
+            EventKind::Pacification { .. } => Self::PACIFICATION,
+            EventKind::PosthumousGrant { .. } => Self::POSTHUMOUS_GRANT,
+            EventKind::Abdication { .. } => Self::ABDICATION,
+            EventKind::UsurpationEra { .. } => Self::USURPATION_ERA,
+            EventKind::PopulationMove { .. } => Self::POPULATION_MOVE,
+            EventKind::Diplomacy { .. } => Self::DIPLOMACY,
+            EventKind::Omen { .. } => Self::OMEN,
+            EventKind::Rebellion { .. } => Self::REBELLION,
+            EventKind::Surrender { .. } => Self::SURRENDER,
+            EventKind::Marriage { .. } => Self::MARRIAGE,
+        }
+    }
+
+    /// Short Chinese label for display.
+    pub fn kind_zh(&self) -> &'static str {
+        match self {
+            EventKind::Appointment { .. } => "任命",
+            EventKind::Battle { .. } => "戰事",
+            EventKind::Death { .. } => "死亡",
+            EventKind::Enfeoffment { .. } => "封爵",
+            EventKind::Defeat { .. } => "敗績",
+            EventKind::Dispatch { .. } => "派遣",
+            EventKind::TenureEnd { .. } => "去職",
+            EventKind::Decline { .. } => "辭讓",
+            EventKind::Investiture { .. } => "冊立",
+            EventKind::Pacification { .. } => "平定",
+            EventKind::PosthumousGrant { .. } => "贈官",
+            EventKind::Abdication { .. } => "禪位",
+            EventKind::UsurpationEra { .. } => "僭號",
+            EventKind::PopulationMove { .. } => "徙民",
+            EventKind::Diplomacy { .. } => "遣使",
+            EventKind::Omen { .. } => "災異",
+            EventKind::Rebellion { .. } => "叛亂",
+            EventKind::Surrender { .. } => "降附",
+            EventKind::Marriage { .. } => "婚姻",
+        }
+    }
+
+    /// Position of the variant in declaration order, the last tie-breaker
+    /// of `Event::order_key`.
+    pub fn discriminant(&self) -> u8 {
+        match self {
+            EventKind::Appointment { .. } => 0,
+            EventKind::Battle { .. } => 1,
+            EventKind::Death { .. } => 2,
+            EventKind::Enfeoffment { .. } => 3,
+            EventKind::Defeat { .. } => 4,
+            EventKind::Dispatch { .. } => 5,
+            EventKind::TenureEnd { .. } => 6,
+            EventKind::Decline { .. } => 7,
+            EventKind::Investiture { .. } => 8,
+            EventKind::Pacification { .. } => 9,
+            EventKind::PosthumousGrant { .. } => 10,
+            EventKind::Abdication { .. } => 11,
+            EventKind::UsurpationEra { .. } => 12,
+            EventKind::PopulationMove { .. } => 13,
+            EventKind::Diplomacy { .. } => 14,
+            EventKind::Omen { .. } => 15,
+            EventKind::Rebellion { .. } => 16,
+            EventKind::Surrender { .. } => 17,
+            EventKind::Marriage { .. } => 18,
+        }
+    }
+}
+
+/// The field `Event::person_name` reads, shared with `person_name_mut` so
+/// the two cannot drift apart. `$opt` is `as_ref` or `as_mut`, to match
+/// how `$kind` is borrowed.
+macro_rules! person_field {
+    ($kind:expr, $opt:ident) => {
+        match $kind {
             EventKind::Appointment { person, .. }
             | EventKind::Battle { person, .. }
-            | EventKind::Death { person, .. } => person,
+            | EventKind::Death { person, .. }
+            | EventKind::Defeat { person, .. }
+            | EventKind::Investiture { person, .. }
+            | EventKind::Rebellion { person, .. }
+            | EventKind::Surrender { person, .. }
+            | EventKind::Marriage { person, .. } => Some(person),
+            EventKind::Dispatch { agent, .. } => Some(agent),
+            EventKind::Enfeoffment { person, .. }
+            | EventKind::TenureEnd { person, .. }
+            | EventKind::Decline { person, .. }
+            | EventKind::Pacification { person, .. }
+            | EventKind::PosthumousGrant { person, .. }
+            | EventKind::Abdication { person, .. }
+            | EventKind::UsurpationEra { person, .. }
+            | EventKind::PopulationMove { person, .. } => person.$opt(),
+            EventKind::Diplomacy { sender, .. } => sender.$opt().filter(|s| is_plausible_name(s)),
+            EventKind::Omen { .. } => None,
+        }
+    };
+}
+
+impl Event {
+    /// An event as a match first yields it; `extracted_by`, `scope_index`
+    /// and `person_id` are filled in afterwards where known.
+    pub fn new(
+        kind: EventKind,
+        time: Option<TimeRef>,
+        source_file: &str,
+        byte_offset: usize,
+        context: String,
+        locations: Vec<PlaceRef>,
+    ) -> Self {
+        Self {
+            kind,
+            time,
+            source_file: source_file.to_string(),
+            byte_offset,
+            context,
+            locations,
+            extracted_by: None,
+            scope_index: None,
+            person_id: None,
+        }
+    }
+
+    /// Tie-breaker for events sorted by date: source position, then kind,
+    /// so events matched at the same offset by different patterns always
+    /// come out in the same order.
+    pub fn order_key(&self) -> (&str, usize, u8) {
+        (
+            &self.source_file,
+            self.byte_offset,
+            self.kind.discriminant(),
+        )
+    }
+
+    /// Extract the person name from this event's kind.
+    /// None when the text omits the subject (e.g. "改封武昌王"), and for a
+    /// diplomatic mission sent by a state (高麗國) rather than a ruler.
+    pub fn person_name(&self) -> Option<&str> {
+        person_field!(&self.kind, as_ref).map(String::as_str)
+    }
+
+    /// Mutable `person_name`, for rewriting an alias to the canonical name.
+    fn person_name_mut(&mut self) -> Option<&mut String> {
+        person_field!(&mut self.kind, as_mut)
+    }
+
+    /// Every person the event involves: the subject and, for a battle
+    /// against a named commander, the opponent.
+    pub fn participants(&self) -> Vec<&str> {
+        let mut persons: Vec<&str> = self.person_name().into_iter().collect();
+        if let EventKind::Battle {
+            target_person: Some(p),
+            ..
+        } = &self.kind
+        {
+            persons.push(p);
         }
+        persons
     }
 
     /// Collect all location names from this event (structured + context).
@@ -250,7 +871,10 @@ impl Event {
             | EventKind::Battle {
                 target_place: Some(p),
                 ..
-            } => {
+            }
+            | EventKind::Pacification { place: p, .. }
+            | EventKind::Omen { place: Some(p), .. }
+            | EventKind::Rebellion { place: Some(p), .. } => {
                 names.push(p.name.as_str());
             }
             _ => {}
@@ -267,7 +891,46 @@ pub struct EventStats {
     pub appointments: usize,
     pub battles: usize,
     pub deaths: usize,
+    #[serde(default)]
+    pub enfeoffments: usize,
+    #[serde(default)]
+    pub defeats: usize,
+    #[serde(default)]
+    pub dispatches: usize,
+    #[serde(default)]
+    pub tenure_ends: usize,
+    #[serde(default)]
+    pub declines: usize,
+    #[serde(default)]
+    pub investitures: usize,
+    #[serde(default)]
+    pub pacifications: usize,
+    #[serde(default)]
+    pub posthumous_grants: usize,
+    #[serde(default)]
+    pub abdications: usize,
+    #[serde(default)]
+    pub usurpation_eras: usize,
+    #[serde(default)]
+    pub population_moves: usize,
+    #[serde(default)]
+    pub diplomatic_missions: usize,
+    #[serde(default)]
+    pub omens: usize,
+    #[serde(default)]
+    pub rebellions: usize,
+    #[serde(default)]
+    pub surrenders: usize,
+    #[serde(default)]
+    pub marriages: usize,
     pub unique_time_refs: usize,
+    /// Time references whose year runs past the era (`TimeRef::suspect`)
+    #[serde(default)]
+    pub suspect_time_refs: usize,
+    /// Sexagenary years (歲在甲子) no era-year in their file resolved,
+    /// kept in `TimeIndex::era_refs` only
+    #[serde(default)]
+    pub unresolved_ganzhi_years: usize,
     pub unique_places: usize,
     pub era_distribution: HashMap<String, usize>,
     pub top_places: Vec<(String, usize)>,
@@ -281,52 +944,180 @@ pub struct EventScanner {
     re_month_day: Regex,
     // Event extraction
     re_appointment: Regex,
+    re_promotion: Regex,
+    re_direct_appointment: Regex,
     re_battle: Regex,
     re_death: Regex,
+    re_violent_death: Regex,
+    re_defeat: Regex,
+    re_enfeoffment: Regex,
+    re_usurpation: Regex,
+    re_population: Regex,
+    re_diplomacy: Regex,
+    re_court_visit: Regex,
+    re_omen: Regex,
+    re_rebellion: Regex,
+    re_surrender: Regex,
+    re_marriage: Regex,
+    re_dispatch: Regex,
+    re_tenure_end: Regex,
+    re_recall: Regex,
+    re_succession: Regex,
+    re_decline: Regex,
+    re_investiture: Regex,
+    re_pacification: Regex,
+    re_posthumous: Regex,
+    re_abdication: Regex,
+    re_troops: Regex,
+    re_units: Regex,
     // Place extraction from titles
     re_place_title: Regex,
+    /// Record the producing regex on each event (`Event::extracted_by`)
+    debug_provenance: bool,
+    /// Chars of source text kept on each side of a match in `Event::context`
+    context_config: ContextConfig,
+    /// 列傳 file path → the biography's subject, for bare-given-name
+    /// mentions (see `JoinedText::attribute_given_names`)
+    bio_subjects: HashMap<String, BioSubject>,
+    /// Max char distance at which same-person, same-kind events collapse
+    /// into the first (see `merge_adjacent`)
+    merge_window: usize,
+    /// Person-name pattern the appointment/promotion regexes are built
+    /// from, kept so their gaps can be changed after construction
+    name_re: String,
+}
+
+/// The subject of a 列傳 file, by the names the file may call them.
+struct BioSubject {
+    surname: String,
+    given: String,
+    /// Given names taken later (改名顯), from `Person::aliases`
+    renamed: Vec<String>,
+}
+
+impl BioSubject {
+    fn full_name(&self) -> String {
+        format!("{}{}", self.surname, self.given)
+    }
+}
+
+/// Default `Event::context` radius, in chars on each side of the match.
+pub const DEFAULT_CONTEXT_RADIUS: usize = 30;
+
+/// How much source text each event keeps in `Event::context`.
+#[derive(Debug, Clone)]
+pub struct ContextConfig {
+    /// Base radius; appointments and deaths scale from it (see `radius`)
+    pub radius: usize,
+    /// Per-kind radius, keyed by `EventKind::name()`, overriding the base
+    pub radius_by_kind: HashMap<String, usize>,
+}
+
+impl Default for ContextConfig {
+    fn default() -> Self {
+        Self {
+            radius: DEFAULT_CONTEXT_RADIUS,
+            radius_by_kind: HashMap::new(),
+        }
+    }
+}
+
+impl ContextConfig {
+    /// Appointments (promotions included) get 4/3 of the base, 40 by
+    /// default, since 都督諸軍事 title strings run past 30; deaths get 2/3,
+    /// as the clause after 卒 rarely says more about the death.
+    fn radius(&self, kind: &str) -> usize {
+        if let Some(&r) = self.radius_by_kind.get(kind) {
+            return r;
+        }
+        match kind {
+            EventKind::APPOINTMENT => self.radius * 4 / 3,
+            EventKind::DEATH => self.radius * 2 / 3,
+            _ => self.radius,
+        }
+    }
+}
+
+/// Default max chars between 以 and the appointee's name in
+/// `re_appointment`, room for the appointee's current titles
+/// (以散騎常侍王進為…). A wider gap catches longer title strings but
+/// also lets 以 in an unrelated phrase (以疾, 以功) reach a name later in
+/// the clause.
+pub const DEFAULT_APPOINTMENT_GAP: usize = 12;
+
+/// Default max chars of office taken after 遷/轉 in `re_promotion`. A
+/// wider span keeps long compound offices whole but runs on into the
+/// next phrase when the clause continues without punctuation.
+pub const DEFAULT_PROMOTION_GAP: usize = 12;
+
+/// Default merge window: off, every match is kept. A
+/// dense biography can match one appointment twice a few chars apart;
+/// a window of a clause or so (~10) collapses those, at the risk of
+/// folding two real postings written back to back.
+pub const DEFAULT_MERGE_WINDOW: usize = 0;
+
+// Appointment: 以{title?}{name}為{new_title}. Co-appointees listed
+// before the last name (以王進、張華及劉毅為) are split off in code.
+fn appointment_regex(name_re: &str, gap: usize) -> Regex {
+    Regex::new(&format!(
+        "以([^為]{{0,{gap}}})({name_re})(?:並|并|俱)?為([^，。；]{{2,20}})"
+    ))
+    .expect("appointment regex")
+}
+
+// Promotion: {name}遷{title}, or a clause-initial 遷{title} with the
+// subject omitted (尋遷/又遷/俄轉 ...)
+fn promotion_regex(name_re: &str, gap: usize) -> Regex {
+    Regex::new(&format!(
+        "(?m)(?:({name_re})|(?:^|[，。；])(?:尋|又|俄|累|仍|復)?)(遷|轉)[為爲]?([^，。；]{{2,{gap}}})"
+    ))
+    .expect("promotion regex")
 }
 
-/// Chinese number word → digit
+/// Chinese number word → number: 元 (the first year), or a count up to
+/// 九十九 (三, 十二, 四十三). None for anything larger.
 fn parse_cn_number(s: &str) -> Option<u8> {
-    match s {
-        "元" => Some(1),
-        "一" => Some(1),
-        "二" => Some(2),
-        "三" => Some(3),
-        "四" => Some(4),
-        "五" => Some(5),
-        "六" => Some(6),
-        "七" => Some(7),
-        "八" => Some(8),
-        "九" => Some(9),
-        "十" => Some(10),
-        "十一" => Some(11),
-        "十二" => Some(12),
-        "十三" => Some(13),
-        "十四" => Some(14),
-        "十五" => Some(15),
-        "十六" => Some(16),
-        "十七" => Some(17),
-        "十八" => Some(18),
-        "十九" => Some(19),
-        "二十" => Some(20),
-        "二十一" => Some(21),
-        "二十二" => Some(22),
-        "二十三" => Some(23),
-        "二十四" => Some(24),
-        "二十五" => Some(25),
-        "二十六" => Some(26),
-        "二十七" => Some(27),
-        "二十八" => Some(28),
-        "二十九" => Some(29),
-        "三十" => Some(30),
-        _ => None,
+    if s == "元" {
+        return Some(1);
+    }
+    if s.contains(['百', '千', '萬']) {
+        return None;
+    }
+    parse_cn_count(s).and_then(|n| u8::try_from(n).ok())
+}
+
+/// Chinese count with 十/百/千/萬 place values → number.
+/// "三萬" → 30000, "十萬" → 100000, "二萬五千" → 25000. None for zero or
+/// any other character (數萬 is not a count).
+pub fn parse_cn_count(s: &str) -> Option<u32> {
+    let (mut total, mut section, mut digit) = (0u32, 0u32, 0u32);
+    for c in s.chars() {
+        if let Some(d) = "一二三四五六七八九".chars().position(|x| x == c) {
+            digit = d as u32 + 1;
+            continue;
+        }
+        let place = match c {
+            '十' => 10,
+            '百' => 100,
+            '千' => 1000,
+            '萬' => {
+                total += (section + digit) * 10_000;
+                section = 0;
+                digit = 0;
+                continue;
+            }
+            _ => return None,
+        };
+        section += digit.max(1) * place;
+        digit = 0;
     }
+    let n = total + section + digit;
+    (n > 0).then_some(n)
 }
 
+/// Month number; a leap month (閏五) has the number of the month it follows.
 fn parse_cn_month(s: &str) -> Option<u8> {
-    match s {
+    match s.strip_prefix('閏').unwrap_or(s) {
         "正" => Some(1),
         "一" => Some(1),
         "二" => Some(2),
@@ -340,34 +1131,117 @@ fn parse_cn_month(s: &str) -> Option<u8> {
         "十" => Some(10),
         "十一" => Some(11),
         "十二" | "臘" => Some(12),
-        "閏正" | "閏一" => Some(1),
-        "閏二" => Some(2),
-        "閏三" => Some(3),
         _ => None,
     }
 }
 
+/// Position of a 干支 pair in the sexagenary cycle, 1 (甲子) to 60 (癸亥).
+/// None for pairs that never occur (甲丑: stem and branch of different
+/// parity) or anything else.
+pub fn parse_ganzhi_cycle(s: &str) -> Option<u8> {
+    const STEMS: &str = "甲乙丙丁戊己庚辛壬癸";
+    const BRANCHES: &str = "子丑寅卯辰巳午未申酉戌亥";
+    let mut chars = s.chars();
+    let (stem, branch) = (chars.next()?, chars.next()?);
+    if chars.next().is_some() {
+        return None;
+    }
+    let i = STEMS.chars().position(|c| c == stem)? as i32;
+    let j = BRANCHES.chars().position(|c| c == branch)? as i32;
+    // n ≡ i (mod 10) and n ≡ j (mod 12) has a solution only for equal parity
+    (i % 2 == j % 2).then(|| ((6 * i - 5 * j).rem_euclid(60) + 1) as u8)
+}
+
+/// Day of the month of ganzhi day `day` in a month whose 朔 (first day)
+/// was `shuo`. A day earlier in the cycle than the 朔 wraps into the next
+/// run of sixty (癸亥朔 … 丙寅 → 4); past the 30th it is not that month.
+pub fn day_of_month(shuo: &str, day: &str) -> Option<u8> {
+    let first = i32::from(parse_ganzhi_cycle(shuo)?);
+    let this = i32::from(parse_ganzhi_cycle(day)?);
+    let ordinal = (this - first).rem_euclid(60) + 1;
+    (ordinal <= 30).then_some(ordinal as u8)
+}
+
+/// Sexagenary cycle position of an AD year; AD 4 was a 甲子 year.
+fn ad_year_cycle(ad: u16) -> u8 {
+    ((i32::from(ad) - 4).rem_euclid(60) + 1) as u8
+}
+
+/// Regnal year of an era that fell on cycle position `cycle`. Eras are
+/// shorter than 60 years, so there is at most one.
+fn era_year_for_cycle(regime_chinese: &str, era_name: &str, cycle: u8) -> Option<u8> {
+    let entry = regime::ERA_NAMES
+        .iter()
+        .find(|e| e.regime.as_chinese() == regime_chinese && e.name == era_name)?;
+    (entry.start_ad..=entry.end_ad)
+        .find(|&ad| ad_year_cycle(ad) == cycle)
+        .and_then(|ad| u8::try_from(ad - entry.start_ad + 1).ok())
+}
+
+/// Sexagenary year: 歲在甲子, 歲次甲子, 太歲在乙卯, 太歲乙卯. Captures: 干支.
+static RE_GANZHI_YEAR: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new("(?:太歲(?:在|次)?|歲(?:在|次))([甲乙丙丁戊己庚辛壬癸][子丑寅卯辰巳午未申酉戌亥])")
+        .expect("ganzhi year regex")
+});
+
+/// A bare month/day marker opening a clause, as annals write them after
+/// giving the era-year once: 二月， / 丁卯， / 冬十月己巳朔，. Captures: the
+/// marker, month, 干支 day, 朔. Whether a 「，」 follows is checked in code,
+/// so that it is left for the next marker's leading punctuation.
+static RE_DAY_MARKER: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        "(?m)(?:^|[，。；：])([春夏秋冬]?(?:(正|閏?[一二三四五六七八九十]{1,2}|臘)月)?(?:([甲乙丙丁戊己庚辛壬癸][子丑寅卯辰巳午未申酉戌亥])(朔)?)?)",
+    )
+    .expect("day marker regex")
+});
+
+/// Time: {era}{number}年. Captures: era name, year number (Chinese).
+/// Depends only on the static era table, so it is compiled once and shared
+/// by every scanner; the name-based patterns are rebuilt per scanner.
+static RE_TIME: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(&format!(
+        "({})(元|[一二三四五六七八九十]{{1,3}})年",
+        regime::era_regex()
+    ))
+    .expect("time regex")
+});
+
+/// Era-only time: {era}{中|初|末...} with no year. Many era names are also
+/// common words (太和, 永安, 建武), so the era must start a clause or follow
+/// a temporal preposition and be followed by a part-of-era suffix.
+/// Captures: era name, suffix.
+static RE_ERA_ONLY: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(&format!(
+        "(?m)(?:^|[，。；：「自至及於于逮迄暨當])({})(之初|之末|之際|年中|年間|初|中|末|間)",
+        regime::era_regex()
+    ))
+    .expect("era-only regex")
+});
+
 impl EventScanner {
     pub fn new(known_persons: &[Person]) -> Self {
-        let era_re = regime::build_era_regex();
+        let era_re = regime::era_regex();
         let extra_surnames = collect_extra_surnames(known_persons);
         let name_re = build_name_regex(&extra_surnames);
-        let title_re = build_title_regex();
+        let title_re = title_regex();
 
-        // Time: {era}{number}年
-        // Captures: era name, year number (Chinese)
-        let re_time = Regex::new(&format!("({era_re})(元|[一二三四五六七八九十]{{1,3}})年"))
-            .expect("time regex");
+        // Regex clones share the compiled program
+        let re_time = RE_TIME.clone();
 
-        // Month + day: (正|二|...|十二)月(干支)
+        // Month + day: (正|二|...|十二)月(干支|N日). A ganzhi right after
+        // the month wins over a numbered day.
         let re_month_day = Regex::new(
-            r"(正|閏?[一二三四五六七八九十]{1,2}|臘)月([甲乙丙丁戊己庚辛壬癸][子丑寅卯辰巳午未申酉戌亥])?"
+            r"(正|閏?[一二三四五六七八九十]{1,2}|臘)月(?:([甲乙丙丁戊己庚辛壬癸][子丑寅卯辰巳午未申酉戌亥])|([一二三四五六七八九十]{1,3})日)?"
         )
         .expect("month_day regex");
 
-        // Appointment: 以{title?}{name}為{new_title}
-        let re_appointment = Regex::new(&format!("以[^為]{{0,12}}({name_re})為([^，。]{{2,20}})"))
-            .expect("appointment regex");
+        let re_appointment = appointment_regex(&name_re, DEFAULT_APPOINTMENT_GAP);
+        let re_promotion = promotion_regex(&name_re, DEFAULT_PROMOTION_GAP);
+
+        // Direct appointment without 為: (拜|除|授){name?}{office}. The name,
+        // if any, is split off in code (see `split_appointee`).
+        let re_direct_appointment =
+            Regex::new(r"(拜|除|授)([^\s，。；、為爲]{2,14})").expect("direct appointment regex");
 
         // Battle: {name}{verb}{target}
         // Stop target at function words (於/于 = "at", 以 = "with") to avoid
@@ -381,6 +1255,151 @@ impl EventScanner {
         let re_death =
             Regex::new(&format!("(?:{title_re})?({name_re})(薨|卒|崩)")).expect("death regex");
 
+        // Violent death: {name}為{killer}所(殺|害|誅) or {name}(被害|見殺|遇害|...)
+        let re_violent_death = Regex::new(&format!(
+            "({name_re})(?:[為爲]([^，。所]{{1,6}})所(殺|害|誅)|(被害|見殺|遇害|被誅|伏誅|棄市))"
+        ))
+        .expect("violent death regex");
+
+        // Passive defeat: {name}{軍?}為{agent}所(敗|破|擒...)
+        let re_defeat = Regex::new(&format!(
+            "({name_re})(?:軍|眾|衆)?[為爲]([^，。所]{{1,6}})所(敗|破|擒|執|獲|虜|逐|圍|襲|困)"
+        ))
+        .expect("defeat regex");
+
+        // Enfeoffment: {name?}(改封|徙封|進封|封){name為?}{fief}{rank}
+        let re_enfeoffment = Regex::new(&format!(
+            "({name_re})?(改封|徙封|進封|封)(?:({name_re})[為爲])?([^\\s，。、為爲]{{1,4}}?(?:{FIEF_RANKS}))"
+        ))
+        .expect("enfeoffment regex");
+
+        // Usurpation: {name?}(僭號|僭位|稱帝)…改元{era}
+        // Known eras are tried first so 4-char names (太平真君) stay whole;
+        // otherwise take the two chars after 改元, the usual era length.
+        let re_usurpation = Regex::new(&format!(
+            "({name_re})?(?:僭號|僭位|僭稱[^，。]{{1,4}}|稱帝)[^。]{{0,12}}?改元(?:[為爲曰])?(?:({era_re})|([^\\s，。、]{{2}}))"
+        ))
+        .expect("usurpation regex");
+
+        // Dispatch: {name?}遣{其將?}{name}(攻|使|將兵|將部曲...){rest}
+        let re_dispatch = Regex::new(&format!(
+            "({name_re})?(?:乃|又|復|仍)?遣(?:其?將|其子|其弟|偏師)?({name_re})(攻|擊|討|伐|襲|使|將兵|將(?:{RETAINER_UNITS})|率|帥|鎮|戍|屯|守)([^，。]{{0,8}})"
+        ))
+        .expect("dispatch regex");
+
+        // Tenure end: {name?}(罷|去){place}{office?} or {name?}罷{office}.
+        // A place needs a 州/郡 ending, so 去城十里 and 罷兵 stay out.
+        let re_tenure_end = Regex::new(&format!(
+            "({name_re})?(罷|去)([^\\s，。；、為爲]{{1,4}}?(?:州|郡)(?:{title_re})?|[^\\s，。；、為爲]{{0,6}}?(?:{title_re}))"
+        ))
+        .expect("tenure end regex");
+
+        // Recall to court: {name}徵還
+        let re_recall = Regex::new(&format!("({name_re})徵還")).expect("recall regex");
+
+        // Replacement: {successor?}代{predecessor}為{office}
+        let re_succession = Regex::new(&format!(
+            "({name_re})?代({name_re})[為爲]([^，。；]{{2,12}})"
+        ))
+        .expect("succession regex");
+
+        // Decline: {name?}(固辭(不拜)?|辭不拜|不拜|讓){office?}. 辭 alone is
+        // too common (辭曰, 言辭) to count without 固/苦/... or 不拜.
+        let re_decline = Regex::new(&format!(
+            "({name_re})?((?:固|苦|頻|累|懇)辭(?:不拜|不受|不就)?|辭不(?:拜|受|就)|不拜|讓)([^\\s，。；、]{{0,12}})"
+        ))
+        .expect("decline regex");
+
+        // Investiture: {name?}立{其/皇子/妃?}{installed?}為(帝|太子|皇后|王).
+        // The installed person is whatever stands between 立 and 為: a
+        // full name, a given name (立皇子詡), a kin term (立子), a prince
+        // (立會稽王昌明) or 妃X氏; with nothing there it is the subject.
+        let re_investiture = Regex::new(&format!(
+            "({name_re})?(?:乃|遂|共|更|復|迎)?立(?:其|皇子|皇弟|皇孫|妃|妻)?([^\\s，。、為爲立]{{1,6}}?)?[為爲](皇帝|帝|皇太子|太子|皇后|王)"
+        ))
+        .expect("investiture regex");
+
+        // Pacification: {name?}(克定|平定|平|定){place}. The place is
+        // validated in code (see `pacified_place`).
+        let re_pacification = Regex::new(&format!(
+            "({name_re})?(克定|平定|平|定)([^\\s，。、；]{{1,4}})"
+        ))
+        .expect("pacification regex");
+
+        // Posthumous grant: (追贈|追封|贈){name為?}{title}. The title is
+        // validated in code (see `posthumous_title`).
+        let re_posthumous = Regex::new(&format!(
+            "(追贈|追封|贈)(?:({name_re})[為爲])?([^\\s，。；、]{{1,12}})"
+        ))
+        .expect("posthumous grant regex");
+
+        // Abdication: {ruler?}(禪位|遜位|禪|遜){於/于 successor?}. The ruler
+        // is a name or an "X帝/X主" reference (順帝, 晉主); bare 禪/遜 must
+        // be followed by 於/于.
+        let re_abdication = Regex::new(&format!(
+            "([^\\s，。、；與及而乃遂日諷]?[^\\s，。、；與及而乃遂日諷告](?:帝|主)|帝|主|{name_re})?(禪位|遜位|禪|遜)([於于][^\\s，。；、]{{1,6}})?"
+        ))
+        .expect("abdication regex");
+
+        // Population move: {name?}(遷|徙){clause}. Whether the clause names
+        // a population rather than an office is decided in code (see
+        // `population_move`).
+        let re_population = Regex::new(&format!("({name_re})?(遷|徙)([^\\s，。；]{{2,16}})"))
+            .expect("population move regex");
+
+        // Diplomatic mission: {sender?}(並|各|又|頻)?遣使{kind}(於|于{target})?.
+        // The sender runs back to the clause start and may list several
+        // courts (吐谷渾、宕昌國並遣使朝獻); it is cleaned in code.
+        let re_diplomacy = Regex::new(
+            "([^\\s，。；：「」]{1,16}?)?(?:並|各|又|頻|乃|復)?遣使(朝貢|朝獻|貢獻|通好|請和|求救)(?:[於于]([^\\s，。；、]{1,4}))?",
+        )
+        .expect("diplomacy regex");
+
+        // Court visit by a ruler: {name}朝(於|于){court}
+        let re_court_visit = Regex::new(&format!("({name_re})朝[於于]([^\\s，。；、]{{1,4}})"))
+            .expect("court visit regex");
+
+        // Omen or disaster: 日有蝕之, 地震, 大水, 旱, 蝗, 星孛 ... The place
+        // before it (京師地震, 雍州大水) is read in code (see `omen_place`).
+        // Bare 日食 is a daily ration (日食一升), so only 日蝕 is taken.
+        let re_omen = Regex::new("日有[蝕食]之|日蝕|地震|地動|大水|大旱|旱災|旱蝗|旱|蝗|星孛|彗星")
+            .expect("omen regex");
+
+        // Rebellion: {name}(反|叛|據{base}反|舉兵反). Compounds of 反 that
+        // are not revolts (反覆, 反正) and 反 for 返 (反命, 反國) are
+        // dropped in code.
+        let re_rebellion = Regex::new(&format!("({name_re})(舉兵反|據([^，。]{{2,6}})反|反|叛)"))
+            .expect("rebellion regex");
+
+        // Surrender: {name}(以城|率眾...)?(歸降|請降|款附|降)(於{side}|{state})?.
+        // 降為/降號 (demotion) is dropped in code.
+        let re_surrender = Regex::new(&format!(
+            "({name_re})((?:以城|以眾|以衆|率眾|率衆|舉城|舉眾|舉衆)?(?:歸降|請降|款附|降))(?:[於于]([^\\s，。；、]{{1,4}})|([{STATES}]))?"
+        ))
+        .expect("surrender regex");
+
+        // Marriage: {name}(尚|娶|妻以){spouse}. A 尚 not followed by a
+        // princess (尚書) is dropped in code.
+        let re_marriage = Regex::new(&format!(
+            "({name_re})(尚|娶|妻以)([^\\s，。；：、「」]{{0,12}})"
+        ))
+        .expect("marriage regex");
+
+        // Troop count: (將|率|步騎|甲士...){兵/部曲?}{number}(萬|千|人)
+        let re_troops = Regex::new(&format!(
+            "(?:將|率|帥|領|統|步騎|甲士|精兵|兵|眾|衆)(?:兵|眾|衆|步騎|甲士|騎|卒|{RETAINER_UNITS})?([一二三四五六七八九十百千萬]{{1,8}})餘?(人|騎)?"
+        ))
+        .expect("troops regex");
+
+        // Unit descriptor: a retainer body (部曲) or a numbered 營/軍 (三營,
+        // 五軍). The numeral keeps 將軍/軍事 out; 一軍 (the whole army) and
+        // 三軍/六軍 (stock terms for the army at large) name no unit, but
+        // 三營/六營 do.
+        let re_units = Regex::new(&format!(
+            "({RETAINER_UNITS})|([二四五七八九十]|十[一二三四五六七八九]|二十)(營|軍)|[三六]營"
+        ))
+        .expect("units regex");
+
         // Place in title: {place}(刺史|太守|...)
         // Exclude enumeration comma (、) and common punctuation to avoid matching
         // across title boundaries like "振威將軍、刺史"
@@ -391,15 +1410,105 @@ impl EventScanner {
             re_time,
             re_month_day,
             re_appointment,
+            re_promotion,
+            re_direct_appointment,
             re_battle,
             re_death,
+            re_violent_death,
+            re_defeat,
+            re_enfeoffment,
+            re_usurpation,
+            re_population,
+            re_diplomacy,
+            re_court_visit,
+            re_omen,
+            re_rebellion,
+            re_surrender,
+            re_marriage,
+            re_dispatch,
+            re_tenure_end,
+            re_recall,
+            re_succession,
+            re_decline,
+            re_investiture,
+            re_pacification,
+            re_posthumous,
+            re_abdication,
+            re_troops,
+            re_units,
             re_place_title,
+            debug_provenance: false,
+            context_config: ContextConfig::default(),
+            bio_subjects: collect_bio_subjects(known_persons),
+            merge_window: DEFAULT_MERGE_WINDOW,
+            name_re,
         }
     }
 
-    /// Extract all time references from a text.
-    fn extract_times(&self, content: &str, book: Book) -> Vec<(usize, TimeRef)> {
+    /// Tag each event with the regex that produced it, for triaging
+    /// false positives.
+    pub fn with_provenance(mut self, on: bool) -> Self {
+        self.debug_provenance = on;
+        self
+    }
+
+    fn provenance(&self, rule: &'static str) -> Option<&'static str> {
+        self.debug_provenance.then_some(rule)
+    }
+
+    /// Context radius for every kind without an override.
+    pub fn with_context_radius(mut self, radius: usize) -> Self {
+        self.context_config.radius = radius;
+        self
+    }
+
+    /// Context radius for one kind (by `EventKind::name()`), e.g. a wider
+    /// window for battles, whose outcome often follows a clause later.
+    pub fn with_kind_context_radius(mut self, kind: impl Into<String>, radius: usize) -> Self {
+        self.context_config
+            .radius_by_kind
+            .insert(kind.into(), radius);
+        self
+    }
+
+    /// Max chars between 以 and the appointee's name (see
+    /// `DEFAULT_APPOINTMENT_GAP`).
+    pub fn with_appointment_gap(mut self, gap: usize) -> Self {
+        self.re_appointment = appointment_regex(&self.name_re, gap);
+        self
+    }
+
+    /// Max chars of office after 遷/轉 (see `DEFAULT_PROMOTION_GAP`),
+    /// raised to 2, the shortest office the pattern takes.
+    pub fn with_promotion_gap(mut self, gap: usize) -> Self {
+        self.re_promotion = promotion_regex(&self.name_re, gap.max(2));
+        self
+    }
+
+    /// Collapse same-person, same-kind events at most `window` chars
+    /// apart into the first of them.
+    pub fn with_merge_window(mut self, window: usize) -> Self {
+        self.merge_window = window;
+        self
+    }
+
+    fn context(&self, kind: &'static str, content: &str, byte_offset: usize) -> String {
+        extract_context(content, byte_offset, self.context_config.radius(kind))
+    }
+
+    /// Dated time references in `content`, and the sexagenary years
+    /// (歲在甲子) no era-year there resolves, as `TimePrecision::Sexagenary`.
+    fn extract_times(
+        &self,
+        content: &str,
+        book: Book,
+        section: Section,
+    ) -> (Vec<TimeAt>, Vec<TimeAt>) {
         let mut times = Vec::new();
+        // (regime, era, year, month, leap) → that month's 朔 ganzhi
+        let mut shuo: HashMap<(String, String, u8, u8, bool), String> = HashMap::new();
+        // Byte ranges of each era-year and the month/day read along with it
+        let mut dated_spans = Vec::new();
 
         for caps in self.re_time.captures_iter(content) {
             let full_match = caps.get(0).unwrap();
@@ -411,22 +1520,52 @@ impl EventScanner {
                 None => continue,
             };
 
-            let regime =
-                regime::resolve_era(era, book).unwrap_or_else(|| regime::default_regime(book));
+            let regime = regime::resolve_era_year(era, year, book)
+                .unwrap_or_else(|| regime::default_regime(book));
 
             // Look for month/day after this time reference
             let after = &content[full_match.end()..];
-            let (month, day_ganzhi) = if let Some(md) = self.re_month_day.captures(after) {
-                // Only match if it's close (within ~10 chars)
-                if md.get(0).unwrap().start() < 15 {
-                    let m = md.get(1).map(|m| m.as_str()).and_then(parse_cn_month);
-                    let d = md.get(2).map(|m| m.as_str().to_string());
-                    (m, d)
+            let mut span_end = full_match.end();
+            let (month, is_leap, day_ganzhi, day_num, is_shuo) =
+                if let Some(md) = self.re_month_day.captures(after) {
+                    // Only match if it's close (within ~10 chars)
+                    if md.get(0).unwrap().start() < 15 {
+                        span_end += md.get(0).unwrap().end();
+                        let raw_month = md.get(1).map_or("", |m| m.as_str());
+                        let m = parse_cn_month(raw_month);
+                        let d = md.get(2).map(|m| m.as_str().to_string());
+                        // A lunar month has 29 or 30 days
+                        let n = md
+                            .get(3)
+                            .and_then(|m| parse_cn_number(m.as_str()))
+                            .filter(|n| (1..=30).contains(n));
+                        let is_shuo =
+                            d.is_some() && after[md.get(0).unwrap().end()..].starts_with('朔');
+                        (m, m.is_some() && raw_month.starts_with('閏'), d, n, is_shuo)
+                    } else {
+                        (None, false, None, None, false)
+                    }
                 } else {
-                    (None, None)
+                    (None, false, None, None, false)
+                };
+
+            let day = match (month, &day_ganzhi) {
+                (Some(m), Some(d)) => {
+                    let key = (
+                        regime.as_chinese().to_string(),
+                        era.to_string(),
+                        year,
+                        m,
+                        is_leap,
+                    );
+                    if is_shuo {
+                        shuo.insert(key, d.clone());
+                        Some(1)
+                    } else {
+                        shuo.get(&key).and_then(|first| day_of_month(first, d))
+                    }
                 }
-            } else {
-                (None, None)
+                _ => None,
             };
 
             times.push((
@@ -436,14 +1575,163 @@ impl EventScanner {
                     regime: regime.as_chinese().to_string(),
                     year,
                     month,
+                    is_leap,
                     day_ganzhi,
+                    day_num,
+                    day,
+                    year_ganzhi: None,
+                    suspect: regime::exceeds_era_span(regime, era, year),
+                    precision: if month.is_some() {
+                        TimePrecision::Exact
+                    } else {
+                        TimePrecision::Year
+                    },
+                    origin: TimeOrigin::Era,
                     raw: full_match.as_str().to_string(),
                     byte_offset: full_match.start(),
                 },
             ));
+            dated_spans.push(full_match.start()..span_end);
         }
 
-        times
+        // Sexagenary years, read against the nearest era-year reference
+        // (the last before, else the first after). One that names the
+        // same year confirms it; one with no era-year in the file, or
+        // that the era never reached, is left unresolved.
+        let era_years = times.len();
+        let mut unresolved = Vec::new();
+        for caps in RE_GANZHI_YEAR.captures_iter(content) {
+            let full_match = caps.get(0).unwrap();
+            let ganzhi = caps.get(1).unwrap().as_str();
+            let Some(cycle) = parse_ganzhi_cycle(ganzhi) else {
+                continue;
+            };
+            let at = full_match.start();
+            let before = times[..era_years].partition_point(|(off, _)| *off < at);
+            let resolved = before
+                .checked_sub(1)
+                .or((before < era_years).then_some(before))
+                .and_then(|i| {
+                    let context = &times[i].1;
+                    era_year_for_cycle(&context.regime, &context.era, cycle).map(|y| (i, y))
+                });
+            let Some((i, year)) = resolved else {
+                unresolved.push((
+                    at,
+                    TimeRef {
+                        era: String::new(),
+                        regime: regime::default_regime(book).as_chinese().to_string(),
+                        year: 0,
+                        month: None,
+                        is_leap: false,
+                        day_ganzhi: None,
+                        day_num: None,
+                        day: None,
+                        year_ganzhi: Some(ganzhi.to_string()),
+                        suspect: false,
+                        precision: TimePrecision::Sexagenary,
+                        origin: TimeOrigin::GanzhiYear,
+                        raw: full_match.as_str().to_string(),
+                        byte_offset: at,
+                    },
+                ));
+                continue;
+            };
+            let context = &times[i].1;
+            if year == context.year {
+                times[i].1.year_ganzhi = Some(ganzhi.to_string());
+                continue;
+            }
+            let time = TimeRef {
+                year,
+                month: None,
+                is_leap: false,
+                day_ganzhi: None,
+                day_num: None,
+                day: None,
+                year_ganzhi: Some(ganzhi.to_string()),
+                suspect: false,
+                precision: TimePrecision::Year,
+                origin: TimeOrigin::GanzhiYear,
+                raw: full_match.as_str().to_string(),
+                byte_offset: at,
+                ..context.clone()
+            };
+            times.push((at, time));
+        }
+        times.sort_by_key(|(off, _)| *off);
+
+        // Bare month/day markers, dated by the reference before them. One
+        // without a month stays in the month last named for that year, and
+        // a 干支 day is counted from that month's 朔 once one is recorded.
+        // Only annals run month by month under one era-year; elsewhere the
+        // reference before a marker may be pages back.
+        if section != Section::BenJi {
+            return (times, unresolved);
+        }
+        let mut markers = Vec::new();
+        // (base reference offset, month, leap) of the last marker
+        let mut current: Option<(usize, Option<u8>, bool)> = None;
+        for caps in RE_DAY_MARKER.captures_iter(content) {
+            let marker = caps.get(1).unwrap();
+            let ganzhi = caps.get(3).map(|m| m.as_str());
+            if (caps.get(2).is_none() && ganzhi.is_none())
+                || !content[marker.end()..].starts_with('，')
+                || dated_spans.iter().any(|r| r.contains(&marker.start()))
+            {
+                continue;
+            }
+            let before = times.partition_point(|(off, _)| *off < marker.start());
+            let Some((base_at, base)) = before.checked_sub(1).map(|i| &times[i]) else {
+                continue;
+            };
+            let (month, is_leap) = match caps.get(2) {
+                Some(m) => (parse_cn_month(m.as_str()), m.as_str().starts_with('閏')),
+                None => match current {
+                    Some((at, month, leap)) if at == *base_at => (month, leap),
+                    _ => (base.month, base.is_leap),
+                },
+            };
+            current = Some((*base_at, month, is_leap));
+
+            let day = match (month, ganzhi) {
+                (Some(m), Some(d)) => {
+                    let key = (base.regime.clone(), base.era.clone(), base.year, m, is_leap);
+                    if caps.get(4).is_some() {
+                        shuo.insert(key, d.to_string());
+                        Some(1)
+                    } else {
+                        shuo.get(&key).and_then(|first| day_of_month(first, d))
+                    }
+                }
+                _ => None,
+            };
+            markers.push((
+                marker.start(),
+                TimeRef {
+                    month,
+                    is_leap,
+                    day_ganzhi: ganzhi.map(str::to_string),
+                    day_num: None,
+                    day,
+                    // Whether the era ran that long is the base's to flag
+                    suspect: false,
+                    precision: if month.is_some() {
+                        TimePrecision::Exact
+                    } else {
+                        TimePrecision::Year
+                    },
+                    origin: TimeOrigin::DayMarker,
+                    raw: marker.as_str().to_string(),
+                    byte_offset: marker.start(),
+                    ..base.clone()
+                },
+            ));
+        }
+        times.extend(markers);
+        times.sort_by_key(|(off, _)| *off);
+
+        (times, unresolved)
     }
 
     /// Extract all place references from a context string.
@@ -458,12 +1746,11 @@ impl EventScanner {
                     continue;
                 }
                 let suffix = caps.get(2).map(|m| m.as_str().to_string());
-                let is_qiao =
-                    name.starts_with('南') && name.ends_with('州') && name.chars().count() >= 3;
+                let is_qiao = detect_qiao(&name, context);
                 places.push(PlaceRef {
-                    name,
                     is_qiao,
                     role_suffix: suffix,
+                    ..PlaceRef::new(name)
                 });
             }
         }
@@ -472,20 +1759,19 @@ impl EventScanner {
 
     /// Extract a place reference from a title string like "郢州刺史".
     /// Falls back to detecting bare administrative places like "梁州".
-    fn extract_place_from_title(&self, title_str: &str) -> Option<PlaceRef> {
+    /// `context` is the prose around the title, read for 僑置 markers.
+    fn extract_place_from_title(&self, title_str: &str, context: &str) -> Option<PlaceRef> {
         // Primary: match "{place}{role_suffix}" pattern (e.g., "郢州刺史")
         if let Some(caps) = self.re_place_title.captures(title_str) {
             let place_name = caps.get(1)?.as_str().to_string();
             let suffix = caps.get(2).map(|m| m.as_str().to_string());
 
-            let is_qiao = place_name.starts_with('南')
-                && place_name.ends_with('州')
-                && place_name.chars().count() >= 3;
+            let is_qiao = detect_qiao(&place_name, context);
 
             return Some(PlaceRef {
-                name: place_name,
                 is_qiao,
                 role_suffix: suffix,
+                ..PlaceRef::new(place_name)
             });
         }
 
@@ -497,12 +1783,9 @@ impl EventScanner {
             && admin_suffixes.contains(&last)
             && is_plausible_place(title_str)
         {
-            let is_qiao =
-                title_str.starts_with('南') && title_str.ends_with('州') && char_count >= 3;
             return Some(PlaceRef {
-                name: title_str.to_string(),
-                is_qiao,
-                role_suffix: None,
+                is_qiao: detect_qiao(title_str, context),
+                ..PlaceRef::new(title_str)
             });
         }
 
@@ -510,25 +1793,89 @@ impl EventScanner {
     }
 
     /// Detect if a battle target string is a place name.
-    fn detect_place_target(target: &str) -> Option<PlaceRef> {
-        let geo_suffixes: &[char] = &[
-            '州', '郡', '縣', '城', '關', '塞', '鎮', '壁', '山', '水', '河', '江', '池', '谷',
-            '嶺', '津', '渡', '橋', '亭', '營', '壘',
-        ];
+    fn detect_place_target(target: &str, context: &str) -> Option<PlaceRef> {
         let last = target.chars().last()?;
-        if geo_suffixes.contains(&last) {
+        if GEO_SUFFIXES.contains(&last) {
+            let collective = collective_region(target);
             Some(PlaceRef {
-                name: target.to_string(),
-                is_qiao: target.starts_with('南')
-                    && target.ends_with('州')
-                    && target.chars().count() >= 3,
-                role_suffix: None,
+                is_qiao: detect_qiao(target, context),
+                collective: collective.is_some(),
+                region: collective.flatten(),
+                ..PlaceRef::new(target)
             })
         } else {
             None
         }
     }
 
+    /// Detect if a battle target string is a person: a surname and a
+    /// one- or two-char given name, optionally followed by 軍/眾 (攻王進軍).
+    /// Cities named without a suffix read like names (江陵, 許昌), so known
+    /// places are excluded.
+    fn detect_person_target(target: &str) -> Option<String> {
+        let name = target
+            .trim_end_matches('也')
+            .trim_end_matches(['軍', '眾', '衆']);
+        let plausible = is_plausible_name(name)
+            && !name.chars().any(|c| GEO_SUFFIXES.contains(&c))
+            && !BARE_CITIES.contains(&name)
+            && !REGIONS.contains(&name)
+            && crate::gazetteer::geocode(name).is_none();
+        plausible.then(|| name.to_string())
+    }
+
+    /// Era-only references (太和中朝議) in `content` and the `unresolved`
+    /// sexagenary years, each scoped up to the next time reference of any
+    /// kind, `times` being the dated ones `extract_times` found there.
+    fn era_refs(
+        content: &str,
+        times: &[(usize, TimeRef)],
+        unresolved: &[(usize, TimeRef)],
+        book: Book,
+        source_file: &str,
+    ) -> Vec<TimeScope> {
+        let mut times = times.to_vec();
+        let first_era = times.len();
+        times.extend_from_slice(unresolved);
+        for caps in RE_ERA_ONLY.captures_iter(content) {
+            let era = caps.get(1).unwrap();
+            let suffix = caps.get(2).unwrap();
+            let regime = regime::resolve_era(era.as_str(), book)
+                .unwrap_or_else(|| regime::default_regime(book));
+            times.push((
+                era.start(),
+                TimeRef {
+                    era: era.as_str().to_string(),
+                    regime: regime.as_chinese().to_string(),
+                    year: 0,
+                    month: None,
+                    is_leap: false,
+                    day_ganzhi: None,
+                    day_num: None,
+                    day: None,
+                    year_ganzhi: None,
+                    suspect: false,
+                    precision: TimePrecision::Era,
+                    origin: TimeOrigin::Era,
+                    raw: content[era.start()..suffix.end()].to_string(),
+                    byte_offset: era.start(),
+                },
+            ));
+        }
+        if times.len() == first_era {
+            return Vec::new();
+        }
+        times.sort_by_key(|(off, _)| *off);
+        let mut scopes = Self::build_time_scopes(&times, content.len(), source_file);
+        scopes.retain(|s| {
+            matches!(
+                s.time.precision,
+                TimePrecision::Era | TimePrecision::Sexagenary
+            )
+        });
+        scopes
+    }
+
     /// Find the closest preceding time reference for a given byte offset.
     fn find_time_context(times: &[(usize, TimeRef)], event_offset: usize) -> Option<TimeRef> {
         // Find the last time ref that appears BEFORE this event
@@ -566,320 +1913,4057 @@ impl EventScanner {
         scopes
     }
 
+    /// Troop count stated in the sentence around `offset`, if any.
+    /// A bare number needs a 百/千/萬 place value or a following 人/騎,
+    /// so "將兵三日" is not read as three soldiers.
+    fn troops_near(&self, content: &str, offset: usize) -> Option<u32> {
+        let start = content[..offset]
+            .rfind('。')
+            .map_or(0, |i| i + '。'.len_utf8());
+        let end = content[offset..]
+            .find('。')
+            .map_or(content.len(), |i| offset + i);
+        self.re_troops
+            .captures_iter(&content[start..end])
+            .find_map(|caps| {
+                let num = caps.get(1).unwrap().as_str();
+                let has_place = num.contains(['百', '千', '萬']);
+                (has_place || caps.get(2).is_some())
+                    .then(|| parse_cn_count(num))
+                    .flatten()
+            })
+    }
+
+    /// Unit descriptors (部曲, 三營) in the sentence around `offset`, in
+    /// text order without repeats. Only called for battles and dispatches,
+    /// where 營/軍 after a numeral is a formation, not a camp or an office.
+    fn units_near(&self, content: &str, offset: usize) -> Vec<String> {
+        let start = content[..offset]
+            .rfind('。')
+            .map_or(0, |i| i + '。'.len_utf8());
+        let end = content[offset..]
+            .find('。')
+            .map_or(content.len(), |i| offset + i);
+        let mut units: Vec<String> = Vec::new();
+        for m in self.re_units.find_iter(&content[start..end]) {
+            if !units.iter().any(|u| u == m.as_str()) {
+                units.push(m.as_str().to_string());
+            }
+        }
+        units
+    }
+
+    /// Whether a name captured at `start` should produce an event.
+    ///
+    /// 載記 names are foreign (禿髮烏孤, 乞伏乾歸) and often only partly
+    /// covered by the surname table, so the name regex latches onto tail
+    /// fragments; there a name must also start a clause or follow a title.
+    fn accept_name(name: &str, content: &str, start: usize, section: Section) -> bool {
+        if crate::intext::is_false_positive_name(name) {
+            return false;
+        }
+        if section != Section::ZaiJi {
+            return true;
+        }
+        let before = &content[..start];
+        match before.chars().next_back() {
+            None => true,
+            Some(c) if !c.is_alphabetic() => true,
+            Some(c) if "以遣使令命及與而乃遂將從率帥子弟兄".contains(c) => true,
+            _ => TITLE_SUFFIXES
+                .iter()
+                .chain(STANDALONE_TITLES)
+                .any(|t| before.ends_with(t)),
+        }
+    }
+
     /// Scan a single file for events and time scopes.
+    ///
+    /// Patterns run over the text with intra-sentence line breaks joined,
+    /// so an event wrapped across two lines is still matched; all byte
+    /// offsets in the result refer to the original `source` text.
+    ///
+    /// `section` tunes attribution: in 本紀 a clause-initial "遷X" with no
+    /// subject is the emperor's own career (named from the juan directory,
+    /// see `annals_subject`); in the 列傳 of a known person it is the
+    /// biography owner's, and in other files it is left alone. Such a
+    /// clause inside an appointment already matched with a name is not
+    /// counted again. In a 列傳 file whose subject is a known person, a
+    /// bare given name before an event verb (淵遷尚書令 in 褚淵's
+    /// biography) is read as the full name, and the subject's events carry
+    /// the file as `person_id`; other files never see that subject's given
+    /// name.
+    #[allow(dead_code)] // scan_corpus takes the era refs too; used by tests
     pub fn scan_file(
         &self,
-        content: &str,
+        source: &str,
         book: Book,
+        section: Section,
         source_file: &str,
     ) -> (Vec<Event>, Vec<TimeScope>) {
+        let (events, scopes, _) = self.scan_file_with_era_refs(source, book, section, source_file);
+        (events, scopes)
+    }
+
+    /// `scan_file`, plus the file's era-only references (see `era_refs`),
+    /// read from the same extracted times.
+    pub fn scan_file_with_era_refs(
+        &self,
+        source: &str,
+        book: Book,
+        section: Section,
+        source_file: &str,
+    ) -> (Vec<Event>, Vec<TimeScope>, Vec<TimeScope>) {
+        let mut joined = JoinedText::new(source);
+        let bio_subject = self
+            .bio_subjects
+            .get(source_file)
+            .filter(|_| section == Section::LieZhuan);
+        if let Some(subject) = bio_subject {
+            let givens: Vec<&str> = std::iter::once(&subject.given)
+                .chain(&subject.renamed)
+                .map(String::as_str)
+                .collect();
+            joined.attribute_given_names(&subject.surname, &givens);
+        }
+        let content = joined.scanned();
         let mut events = Vec::new();
-        let times = self.extract_times(content, book);
-        let scopes = Self::build_time_scopes(&times, content.len(), source_file);
+        let (times, unresolved) = self.extract_times(content, book, section);
+        let mut scopes = Self::build_time_scopes(&times, content.len(), source_file);
+        let mut era_refs = Self::era_refs(content, &times, &unresolved, book, source_file);
 
-        // Appointments
+        // Appointments. Their spans are kept so a subject-omitted 遷/除
+        // inside one is not attributed a second time below.
+        let mut named_spans = Vec::new();
         for caps in self.re_appointment.captures_iter(content) {
             let full = caps.get(0).unwrap();
-            let person = caps.get(1).unwrap().as_str();
-            let new_title = caps.get(2).unwrap().as_str();
+            named_spans.push(full.range());
+            // The greedy name pattern swallows the 並 of "劉毅並為": drop
+            // it only right before 為, and only if a full name is left
+            let name = caps.get(2).unwrap();
+            let mut person = person_at(content, name);
+            if content[name.end()..].starts_with('為')
+                && let Some(rest) = person.strip_suffix(['並', '并', '俱'])
+                && is_plausible_name(rest)
+            {
+                person = rest;
+            }
+            let new_title = caps.get(3).unwrap().as_str();
 
             if crate::intext::is_false_positive_name(person) {
                 continue;
             }
 
-            let place = self.extract_place_from_title(new_title);
             let time = Self::find_time_context(&times, full.start());
-            let context = extract_context(content, full.start(), 30);
+            let context = self.context(EventKind::APPOINTMENT, content, full.start());
+            let place = self.extract_place_from_title(new_title, &context);
+            let locations = self.extract_places_from_context(&context);
+
+            for appointee in co_appointees(caps.get(1).unwrap().as_str())
+                .into_iter()
+                .chain([person])
+            {
+                events.push(Event {
+                    extracted_by: self.provenance("re_appointment"),
+                    ..Event::new(
+                        EventKind::Appointment {
+                            person: appointee.to_string(),
+                            new_title: new_title.trim().to_string(),
+                            place: place.clone(),
+                        },
+                        time.clone(),
+                        source_file,
+                        full.start(),
+                        context.clone(),
+                        locations.clone(),
+                    )
+                });
+            }
+        }
+
+        // Promotions (遷/轉)
+        let subject = (section == Section::BenJi)
+            .then(|| annals_subject(source_file))
+            .flatten();
+        // Who a clause with no subject at all (尋遷X, 除X) is about: the
+        // emperor in 本紀, the biography owner in their own 列傳
+        let omitted_subject = subject
+            .clone()
+            .or_else(|| bio_subject.map(BioSubject::full_name));
+        let in_named = |at: usize| named_spans.iter().any(|r| r.contains(&at));
+        for caps in self.re_promotion.captures_iter(content) {
+            let verb = caps.get(2).unwrap();
+            let new_title = caps.get(3).unwrap().as_str();
+            if !is_office(new_title) || population_move(new_title).is_some() {
+                continue; // 遷都, 遷於X, 遷其民於X ...
+            }
+
+            let (person, offset) = match caps.get(1) {
+                Some(m) => {
+                    let person = person_at(content, m);
+                    if !Self::accept_name(person, content, m.start(), section) {
+                        continue;
+                    }
+                    (person, m.start())
+                }
+                None => match omitted_subject.as_deref() {
+                    Some(s) if !in_named(verb.start()) => (s, verb.start()),
+                    _ => continue,
+                },
+            };
+
+            let time = Self::find_time_context(&times, offset);
+            let context = self.context(EventKind::APPOINTMENT, content, offset);
+            let place = self.extract_place_from_title(new_title, &context);
+            let locations = self.extract_places_from_context(&context);
+
+            events.push(Event {
+                extracted_by: self.provenance("re_promotion"),
+                ..Event::new(
+                    EventKind::Appointment {
+                        person: person.to_string(),
+                        new_title: new_title.trim().to_string(),
+                        place,
+                    },
+                    time,
+                    source_file,
+                    offset,
+                    context,
+                    locations,
+                )
+            });
+        }
+
+        // Direct appointments (拜X侍中, subject-omitted 除冠軍將軍)
+        for caps in self.re_direct_appointment.captures_iter(content) {
+            let verb = caps.get(1).unwrap();
+            let rest = caps.get(2).unwrap();
+            let (person, offset, new_title) = match split_appointee(rest.as_str()) {
+                Some((name, title)) => {
+                    if !Self::accept_name(name, content, rest.start(), section) {
+                        continue;
+                    }
+                    (name, rest.start(), title)
+                }
+                // Subject omitted: only at a clause start or after an adverb,
+                // not in "新除中護軍沈恪" where a title + name follows
+                None if is_office(rest.as_str()) && omitted_subject_ok(content, verb.start()) => {
+                    match omitted_subject.as_deref() {
+                        Some(s) if !in_named(verb.start()) => (s, verb.start(), rest.as_str()),
+                        _ => continue,
+                    }
+                }
+                None => continue, // 拜表, 除名, 授首 ...
+            };
+
+            let time = Self::find_time_context(&times, offset);
+            let context = self.context(EventKind::APPOINTMENT, content, offset);
+            let place = self.extract_place_from_title(new_title, &context);
+            let locations = self.extract_places_from_context(&context);
+
+            events.push(Event {
+                extracted_by: self.provenance("re_direct_appointment"),
+                ..Event::new(
+                    EventKind::Appointment {
+                        person: person.to_string(),
+                        new_title: new_title.to_string(),
+                        place,
+                    },
+                    time,
+                    source_file,
+                    offset,
+                    context,
+                    locations,
+                )
+            });
+        }
+
+        // Tenure ends (罷荊州, 去吳郡)
+        for caps in self.re_tenure_end.captures_iter(content) {
+            let full = caps.get(0).unwrap();
+            let verb = caps.get(2).unwrap().as_str();
+            let office_or_place = caps.get(3).unwrap().as_str();
+            let place = office_or_place
+                .find(['州', '郡'])
+                .map(|i| &office_or_place[..i + '州'.len_utf8()]);
+            match place {
+                Some(p) if !is_plausible_place(p) => continue,
+                None if verb == "去" => continue,
+                _ => {}
+            }
+            // 東南去瓜州四千六百里 is a distance, not a departure
+            if verb == "去"
+                && content[full.end()..]
+                    .chars()
+                    .next()
+                    .is_some_and(|c| "一二三四五六七八九十百千萬數里".contains(c))
+            {
+                continue;
+            }
+            let person = caps
+                .get(1)
+                .filter(|&m| Self::accept_name(person_at(content, m), content, m.start(), section))
+                .map(|m| person_at(content, m).to_string());
+
+            let time = Self::find_time_context(&times, full.start());
+            let context = self.context(EventKind::TENURE_END, content, full.start());
             let locations = self.extract_places_from_context(&context);
 
             events.push(Event {
-                kind: EventKind::Appointment {
-                    person: person.to_string(),
-                    new_title: new_title.trim().to_string(),
+                extracted_by: self.provenance("re_tenure_end"),
+                ..Event::new(
+                    EventKind::TenureEnd {
+                        person,
+                        verb: verb.to_string(),
+                        office_or_place: office_or_place.to_string(),
+                        successor: None,
+                    },
+                    time,
+                    source_file,
+                    full.start(),
+                    context,
+                    locations,
+                )
+            });
+        }
+
+        // Recalls: 徵還 leaves whatever post the person last took up in
+        // this file; with no earlier appointment there is nothing to end.
+        for caps in self.re_recall.captures_iter(content) {
+            let full = caps.get(0).unwrap();
+            let person = person_at(content, caps.get(1).unwrap());
+            if !Self::accept_name(person, content, full.start(), section) {
+                continue;
+            }
+            let last_post = events.iter().rev().find_map(|e| match &e.kind {
+                EventKind::Appointment {
+                    person: p,
+                    new_title,
                     place,
+                } if p == person && e.byte_offset < full.start() => Some(
+                    place
+                        .as_ref()
+                        .map_or(new_title.clone(), |pl| pl.name.clone()),
+                ),
+                _ => None,
+            });
+            let Some(office_or_place) = last_post else {
+                continue;
+            };
+
+            let time = Self::find_time_context(&times, full.start());
+            let context = self.context(EventKind::TENURE_END, content, full.start());
+            let locations = self.extract_places_from_context(&context);
+
+            events.push(Event {
+                extracted_by: self.provenance("re_recall"),
+                ..Event::new(
+                    EventKind::TenureEnd {
+                        person: Some(person.to_string()),
+                        verb: "徵還".to_string(),
+                        office_or_place,
+                        successor: None,
+                    },
+                    time,
+                    source_file,
+                    full.start(),
+                    context,
+                    locations,
+                )
+            });
+        }
+
+        // Replacements: 代X為Y ends X's tenure in favour of the subject
+        for caps in self.re_succession.captures_iter(content) {
+            let full = caps.get(0).unwrap();
+            let predecessor_match = caps.get(2).unwrap();
+            let predecessor = person_at(content, predecessor_match);
+            let office = caps.get(3).unwrap().as_str();
+            if !is_office(office)
+                || !Self::accept_name(predecessor, content, predecessor_match.start(), section)
+            {
+                continue;
+            }
+            let successor = caps
+                .get(1)
+                .map(|m| person_at(content, m))
+                .filter(|p| !crate::intext::is_false_positive_name(p))
+                .map(str::to_string);
+
+            let time = Self::find_time_context(&times, full.start());
+            let context = self.context(EventKind::TENURE_END, content, full.start());
+            let locations = self.extract_places_from_context(&context);
+
+            events.push(Event {
+                extracted_by: self.provenance("re_succession"),
+                ..Event::new(
+                    EventKind::TenureEnd {
+                        person: Some(predecessor.to_string()),
+                        verb: "代".to_string(),
+                        office_or_place: office.to_string(),
+                        successor,
+                    },
+                    time,
+                    source_file,
+                    full.start(),
+                    context,
+                    locations,
+                )
+            });
+        }
+
+        // Declines: an unnamed decliner is whoever was just appointed in
+        // the same sentence, or failing that the annals subject
+        for caps in self.re_decline.captures_iter(content) {
+            let full = caps.get(0).unwrap();
+            let verb = caps.get(2).unwrap();
+            let named_office = office_prefix(caps.get(3).unwrap().as_str());
+            if verb.as_str() == "讓" && named_office.is_none() {
+                continue; // 讓 without an office: 謙讓, 責讓 ...
+            }
+            let sentence_start = content[..verb.start()]
+                .rfind('。')
+                .map_or(0, |i| i + '。'.len_utf8());
+            let appointment = events.iter().rev().find_map(|e| match &e.kind {
+                EventKind::Appointment {
+                    person, new_title, ..
+                } if (sentence_start..verb.start()).contains(&e.byte_offset) => {
+                    Some((person.clone(), new_title.clone()))
+                }
+                _ => None,
+            });
+            let named = caps
+                .get(1)
+                .filter(|&m| Self::accept_name(person_at(content, m), content, m.start(), section))
+                .map(|m| person_at(content, m).to_string());
+            let person = named
+                .or_else(|| appointment.as_ref().map(|(p, _)| p.clone()))
+                .or_else(|| subject.clone());
+            let office = named_office
+                .map(str::to_string)
+                .or_else(|| appointment.map(|(_, t)| t))
+                .or_else(|| {
+                    preceding_office(&content[sentence_start..verb.start()]).map(str::to_string)
+                });
+
+            let time = Self::find_time_context(&times, full.start());
+            let context = self.context(EventKind::DECLINE, content, full.start());
+            let locations = self.extract_places_from_context(&context);
+
+            events.push(Event {
+                extracted_by: self.provenance("re_decline"),
+                ..Event::new(
+                    EventKind::Decline {
+                        person,
+                        verb: verb.as_str().to_string(),
+                        office,
+                    },
+                    time,
+                    source_file,
+                    full.start(),
+                    context,
+                    locations,
+                )
+            });
+        }
+
+        // Investitures (立王進為太子); 自立/篡立 is taking the throne oneself
+        for caps in self.re_investiture.captures_iter(content) {
+            let full = caps.get(0).unwrap();
+            let after_name = caps.get(1).map_or(full.start(), |m| m.end());
+            let verb_at = after_name + content[after_name..].find('立').unwrap();
+            if content[..verb_at].ends_with(['自', '篡']) {
+                continue;
+            }
+            // The greedy name pattern swallows the 乃 of "王進乃立"
+            let named = caps
+                .get(1)
+                .map(|m| {
+                    (
+                        person_at(content, m)
+                            .trim_end_matches(['乃', '遂', '共', '更', '復', '迎', '議', '謀']),
+                        m,
+                    )
+                })
+                .filter(|(name, m)| Self::accept_name(name, content, m.start(), section))
+                .map(|(name, _)| name.to_string());
+            let role = caps.get(3).unwrap().as_str();
+            let (person, by) = match caps.get(2) {
+                // An empress is recorded by her clan: 立妃王氏為皇后
+                Some(m) if role == "皇后" && crate::intext::is_clan_reference(m.as_str()) => {
+                    (m.as_str().to_string(), named.or_else(|| subject.clone()))
+                }
+                Some(m) if crate::intext::is_false_positive_name(person_at(content, m)) => continue,
+                Some(m) => (
+                    person_at(content, m).to_string(),
+                    named.or_else(|| subject.clone()),
+                ),
+                // 王進立為太子 / 立為皇太子: the one named, else the annals
+                // subject before accession, was installed
+                None => match named.or_else(|| subject.clone()) {
+                    Some(p) => (p, None),
+                    None => continue,
                 },
-                time,
-                source_file: source_file.to_string(),
-                byte_offset: full.start(),
-                context,
-                locations,
+            };
+
+            let time = Self::find_time_context(&times, full.start());
+            let context = self.context(EventKind::INVESTITURE, content, full.start());
+            let locations = self.extract_places_from_context(&context);
+
+            events.push(Event {
+                extracted_by: self.provenance("re_investiture"),
+                ..Event::new(
+                    EventKind::Investiture {
+                        person,
+                        role: role.to_string(),
+                        by,
+                    },
+                    time,
+                    source_file,
+                    full.start(),
+                    context,
+                    locations,
+                )
+            });
+        }
+
+        // Pacifications (平關中, 定河北). 平/定 are common in names and
+        // compounds (安定, 太平), so the verb must follow a name, a clause
+        // boundary or an adverb, and the place must end the clause or be
+        // followed by 之/後: 平昌二郡太守 and 定四州諸軍事 are place names,
+        // as is 平原 in a county list (、平原十縣).
+        for caps in self.re_pacification.captures_iter(content) {
+            let full = caps.get(0).unwrap();
+            let verb = caps.get(2).unwrap();
+            let named = caps
+                .get(1)
+                .filter(|&m| Self::accept_name(person_at(content, m), content, m.start(), section))
+                .map(|m| {
+                    person_at(content, m)
+                        .trim_end_matches(['已', '既', '遂', '乃'])
+                        .to_string()
+                });
+            if named.is_none()
+                && !content[..verb.start()].chars().next_back().is_none_or(|c| {
+                    (!c.is_alphabetic() && c != '、') || "遂乃既已悉盡并而因又復竟".contains(c)
+                })
+            {
+                continue;
+            }
+            let rest = caps.get(3).unwrap();
+            let context = self.context(EventKind::PACIFICATION, content, full.start());
+            let Some(place) = pacified_place(rest.as_str(), &context) else {
+                continue;
+            };
+            let after = &content[rest.start() + place.name.len()..];
+            if !after
+                .chars()
+                .next()
+                .is_none_or(|c| !c.is_alphabetic() || "之後地境而還乃遂".contains(c))
+            {
+                continue;
+            }
+
+            let time = Self::find_time_context(&times, full.start());
+            let locations = self.extract_places_from_context(&context);
+
+            events.push(Event {
+                extracted_by: self.provenance("re_pacification"),
+                ..Event::new(
+                    EventKind::Pacification {
+                        person: named.or_else(|| subject.clone()),
+                        verb: verb.as_str().to_string(),
+                        place,
+                    },
+                    time,
+                    source_file,
+                    full.start(),
+                    context,
+                    locations,
+                )
             });
         }
 
         // Battles
         for caps in self.re_battle.captures_iter(content) {
             let full = caps.get(0).unwrap();
-            let person = caps.get(1).unwrap().as_str();
+            let person = person_at(content, caps.get(1).unwrap());
             let verb = caps.get(2).unwrap().as_str();
             let target = caps.get(3).unwrap().as_str();
 
-            if crate::intext::is_false_positive_name(person) {
+            if !Self::accept_name(person, content, full.start(), section) {
                 continue;
             }
 
-            let target_place = Self::detect_place_target(target);
+            let context = self.context(EventKind::BATTLE, content, full.start());
+            let target_place = Self::detect_place_target(target, &context);
+            let target_person = target_place
+                .is_none()
+                .then(|| Self::detect_person_target(target))
+                .flatten();
             let time = Self::find_time_context(&times, full.start());
-            let context = extract_context(content, full.start(), 30);
             let locations = self.extract_places_from_context(&context);
 
             events.push(Event {
-                kind: EventKind::Battle {
-                    person: person.to_string(),
-                    verb: verb.to_string(),
-                    target: target.to_string(),
-                    target_place,
-                },
-                time,
-                source_file: source_file.to_string(),
-                byte_offset: full.start(),
-                context,
-                locations,
+                extracted_by: self.provenance("re_battle"),
+                ..Event::new(
+                    EventKind::Battle {
+                        person: person.to_string(),
+                        verb: verb.to_string(),
+                        target: target.to_string(),
+                        target_place,
+                        target_person,
+                        troops: self.troops_near(content, full.start()),
+                        units: self.units_near(content, full.start()),
+                    },
+                    time,
+                    source_file,
+                    full.start(),
+                    context,
+                    locations,
+                )
             });
         }
 
-        // Deaths
+        // Deaths. Where each death notice's own 贈 title starts, so the
+        // posthumous-grant pass does not count it again.
+        let mut honour_grants = Vec::new();
         for caps in self.re_death.captures_iter(content) {
             let full = caps.get(0).unwrap();
-            let person = caps.get(1).unwrap().as_str();
+            let name = caps.get(1).unwrap();
+            let person = person_at(content, name);
             let verb = caps.get(2).unwrap().as_str();
 
-            if crate::intext::is_false_positive_name(person) {
+            if !Self::accept_name(person, content, name.start(), section) {
                 continue;
             }
 
             let time = Self::find_time_context(&times, full.start());
-            let context = extract_context(content, full.start(), 30);
+            let context = self.context(EventKind::DEATH, content, full.start());
             let locations = self.extract_places_from_context(&context);
+            let (posthumous_title, posthumous_name) = death_honours(&content[full.end()..]);
+            if let Some((at, _)) = posthumous_title {
+                honour_grants.push(full.end() + at);
+            }
 
             events.push(Event {
-                kind: EventKind::Death {
-                    person: person.to_string(),
-                    verb: verb.to_string(),
-                },
-                time,
-                source_file: source_file.to_string(),
-                byte_offset: full.start(),
-                context,
-                locations,
+                extracted_by: self.provenance("re_death"),
+                ..Event::new(
+                    EventKind::Death {
+                        person: person.to_string(),
+                        verb: verb.to_string(),
+                        manner: DeathManner::Natural,
+                        killer: None,
+                        posthumous_title: posthumous_title.map(|(_, t)| t.to_string()),
+                        posthumous_name: posthumous_name.map(str::to_string),
+                        age_at_death: death_age(&content[full.end()..]),
+                    },
+                    time,
+                    source_file,
+                    full.start(),
+                    context,
+                    locations,
+                )
             });
         }
 
-        (events, scopes)
-    }
+        // Violent deaths
+        for caps in self.re_violent_death.captures_iter(content) {
+            let full = caps.get(0).unwrap();
+            let person = person_at(content, caps.get(1).unwrap());
 
-    /// Scan the entire corpus.
-    pub fn scan_corpus(&self, bio_files: &[BiographyFile]) -> (Vec<Event>, TimeIndex, EventStats) {
-        let mut all_events = Vec::new();
-        let mut all_scopes = Vec::new();
-        let mut era_dist: HashMap<String, usize> = HashMap::new();
-        let mut place_counts: HashMap<String, usize> = HashMap::new();
-        let mut time_set = std::collections::HashSet::new();
-        let mut appointments = 0usize;
-        let mut battles = 0usize;
-        let mut deaths = 0usize;
+            if !Self::accept_name(person, content, full.start(), section) {
+                continue;
+            }
 
-        for bio in bio_files {
-            let content = match fs::read_to_string(&bio.path) {
-                Ok(c) => c,
-                Err(_) => continue,
+            let killer = caps.get(2).map(|m| person_at(content, m).to_string());
+            let verb = caps
+                .get(3)
+                .or_else(|| caps.get(4))
+                .unwrap()
+                .as_str()
+                .to_string();
+            let manner = match verb.as_str() {
+                "誅" | "被誅" | "伏誅" | "棄市" => DeathManner::Executed,
+                _ => DeathManner::Killed,
             };
 
-            let (events, scopes) =
-                self.scan_file(&content, bio.source.book, &bio.path.display().to_string());
-
-            for e in &events {
-                match &e.kind {
-                    EventKind::Appointment { place, .. } => {
-                        appointments += 1;
-                        if let Some(p) = place {
-                            *place_counts.entry(p.name.clone()).or_insert(0) += 1;
-                        }
-                    }
-                    EventKind::Battle { target_place, .. } => {
-                        battles += 1;
-                        if let Some(p) = target_place {
-                            *place_counts.entry(p.name.clone()).or_insert(0) += 1;
-                        }
-                    }
-                    EventKind::Death { .. } => {
-                        deaths += 1;
-                    }
-                }
-                if let Some(t) = &e.time {
-                    *era_dist
-                        .entry(format!("{}/{}", t.regime, t.era))
-                        .or_insert(0) += 1;
-                    time_set.insert(format!("{}{}", t.era, t.year));
-                }
+            let time = Self::find_time_context(&times, full.start());
+            let context = self.context(EventKind::DEATH, content, full.start());
+            let locations = self.extract_places_from_context(&context);
+            let (posthumous_title, posthumous_name) = death_honours(&content[full.end()..]);
+            if let Some((at, _)) = posthumous_title {
+                honour_grants.push(full.end() + at);
             }
 
-            all_events.extend(events);
-            all_scopes.extend(scopes);
+            events.push(Event {
+                extracted_by: self.provenance("re_violent_death"),
+                ..Event::new(
+                    EventKind::Death {
+                        person: person.to_string(),
+                        verb,
+                        manner,
+                        killer,
+                        posthumous_title: posthumous_title.map(|(_, t)| t.to_string()),
+                        posthumous_name: posthumous_name.map(str::to_string),
+                        age_at_death: death_age(&content[full.end()..]),
+                    },
+                    time,
+                    source_file,
+                    full.start(),
+                    context,
+                    locations,
+                )
+            });
         }
 
-        let mut top_places: Vec<(String, usize)> = place_counts.into_iter().collect();
-        top_places.sort_by(|a, b| b.1.cmp(&a.1));
-        top_places.truncate(30);
+        // Passive defeats: the patient is the subject (see `EventKind::Defeat`)
+        for caps in self.re_defeat.captures_iter(content) {
+            let full = caps.get(0).unwrap();
+            let person = person_at(content, caps.get(1).unwrap());
 
-        let stats = EventStats {
-            total_events: all_events.len(),
-            appointments,
-            battles,
-            deaths,
-            unique_time_refs: time_set.len(),
-            unique_places: top_places.len(),
-            era_distribution: era_dist,
-            top_places,
-        };
+            if !Self::accept_name(person, content, full.start(), section) {
+                continue;
+            }
 
-        let time_index = TimeIndex { scopes: all_scopes };
+            let time = Self::find_time_context(&times, full.start());
+            let context = self.context(EventKind::DEFEAT, content, full.start());
+            let locations = self.extract_places_from_context(&context);
 
-        (all_events, time_index, stats)
-    }
-}
+            events.push(Event {
+                extracted_by: self.provenance("re_defeat"),
+                ..Event::new(
+                    EventKind::Defeat {
+                        person: person.to_string(),
+                        verb: caps.get(3).unwrap().as_str().to_string(),
+                        victor: person_at(content, caps.get(2).unwrap()).to_string(),
+                    },
+                    time,
+                    source_file,
+                    full.start(),
+                    context,
+                    locations,
+                )
+            });
+        }
 
-/// Return the index of an era name within ERA_NAMES for a given regime.
-/// Used to sort eras chronologically within a regime.
-pub fn era_sort_key(regime_chinese: &str, era_name: &str) -> usize {
-    regime::ERA_NAMES
-        .iter()
-        .enumerate()
-        .find(|(_, e)| e.name == era_name && e.regime.as_chinese() == regime_chinese)
-        .map(|(i, _)| i)
-        .unwrap_or(usize::MAX)
+        // Enfeoffments
+        for caps in self.re_enfeoffment.captures_iter(content) {
+            let full = caps.get(0).unwrap();
+            let verb = caps.get(2).unwrap().as_str();
+            let Some((fief, rank)) = split_fief(caps.get(4).unwrap().as_str()) else {
+                continue;
+            };
+            // 追封 is a posthumous grant, not a living enfeoffment
+            if content[..caps.get(2).unwrap().start()].ends_with('追') {
+                continue;
+            }
+
+            // "封王進為X侯" names the recipient after the verb; otherwise a
+            // name right before the verb is the subject ("王進改封X王").
+            let person = caps
+                .get(3)
+                .or_else(|| caps.get(1))
+                .map(|m| person_at(content, m))
+                .filter(|p| !crate::intext::is_false_positive_name(p))
+                .map(str::to_string);
+
+            let time = Self::find_time_context(&times, full.start());
+            let context = self.context(EventKind::ENFEOFFMENT, content, full.start());
+            let locations = self.extract_places_from_context(&context);
+
+            events.push(Event {
+                extracted_by: self.provenance("re_enfeoffment"),
+                ..Event::new(
+                    EventKind::Enfeoffment {
+                        person,
+                        verb: verb.to_string(),
+                        fief,
+                        rank,
+                        is_reassignment: matches!(verb, "改封" | "徙封"),
+                    },
+                    time,
+                    source_file,
+                    full.start(),
+                    context,
+                    locations,
+                )
+            });
+        }
+
+        // Posthumous grants. An unnamed recipient is whoever died earlier in
+        // the sentence, or in the sentence just before when the grant opens
+        // one (薨，贈司空 / 卒。追贈司空). The 贈 a death notice already took
+        // as its honour (see `death_honours`) is not counted again; a later
+        // one in the same sentence is.
+        for caps in self.re_posthumous.captures_iter(content) {
+            let full = caps.get(0).unwrap();
+            let verb = caps.get(1).unwrap().as_str();
+            let raw_title = caps.get(3).unwrap();
+            let Some(title) = posthumous_title(verb, raw_title.as_str()) else {
+                continue;
+            };
+            if honour_grants.contains(&raw_title.start()) {
+                continue;
+            }
+            // 贈王進侍中: the recipient glued to the office, without 為
+            let (glued, title) = match split_appointee(title) {
+                Some((name, office)) if caps.get(2).is_none() => (Some(name), office),
+                _ => (None, title),
+            };
+            let named = caps
+                .get(2)
+                .filter(|&m| Self::accept_name(person_at(content, m), content, m.start(), section))
+                .map(|m| person_at(content, m))
+                .or(glued)
+                .map(str::to_string);
+            let window_start = content[..full.start()]
+                .trim_end_matches('。')
+                .rfind('。')
+                .map_or(0, |i| i + '。'.len_utf8());
+            let deceased = || {
+                events.iter().rev().find_map(|e| match &e.kind {
+                    EventKind::Death { person, .. }
+                        if (window_start..full.start()).contains(&e.byte_offset) =>
+                    {
+                        Some(person.clone())
+                    }
+                    _ => None,
+                })
+            };
+            let person = named.or_else(deceased);
+
+            let time = Self::find_time_context(&times, full.start());
+            let context = self.context(EventKind::POSTHUMOUS_GRANT, content, full.start());
+            let locations = self.extract_places_from_context(&context);
+
+            events.push(Event {
+                extracted_by: self.provenance("re_posthumous"),
+                ..Event::new(
+                    EventKind::PosthumousGrant {
+                        person,
+                        verb: verb.to_string(),
+                        title: title.to_string(),
+                        retroactive: verb.starts_with('追'),
+                    },
+                    time,
+                    source_file,
+                    full.start(),
+                    context,
+                    locations,
+                )
+            });
+        }
+
+        // Abdications. 遜位 is also an official stepping down (以疾遜位),
+        // so it counts only for a ruler (X帝/X主) or, in 本紀, the emperor.
+        for caps in self.re_abdication.captures_iter(content) {
+            let full = caps.get(0).unwrap();
+            let verb = caps.get(2).unwrap();
+            let target = caps.get(3).map(|m| &m.as_str()['於'.len_utf8()..]);
+            // 受禪 is the receiving side, 坐禪 meditation; 蒙遜 is 沮渠蒙遜
+            if content[..verb.start()].ends_with(['受', '坐', '蒙'])
+                || (matches!(verb.as_str(), "禪" | "遜") && target.is_none())
+            {
+                continue;
+            }
+            // 顯祖將禪位 / 德宗手詔禪位: the name pattern runs into the verb
+            let ruler = caps.get(1).filter(|m| {
+                m.as_str().ends_with(['帝', '主'])
+                    || (Self::accept_name(person_at(content, *m), content, m.start(), section)
+                        && !m.as_str().ends_with(['將', '告', '詔', '終', '而', '欲']))
+            });
+            let person = match ruler.map(|m| person_at(content, m)) {
+                Some("帝" | "主") | None if section == Section::BenJi => subject.clone(),
+                Some("帝" | "主") => None,
+                Some(r) if r.ends_with(['帝', '主']) => Some(r.to_string()),
+                Some(r) if verb.as_str().starts_with('禪') => Some(r.to_string()),
+                None if verb.as_str().starts_with('禪') => None,
+                _ => continue,
+            };
+            // 遜于東邸 names where the ruler withdrew to, not a successor
+            let to = target
+                .filter(|_| verb.as_str().starts_with('禪'))
+                .and_then(abdication_successor);
+
+            let time = Self::find_time_context(&times, full.start());
+            let context = self.context(EventKind::ABDICATION, content, full.start());
+            let locations = self.extract_places_from_context(&context);
+
+            events.push(Event {
+                extracted_by: self.provenance("re_abdication"),
+                ..Event::new(
+                    EventKind::Abdication {
+                        person,
+                        verb: verb.as_str().to_string(),
+                        to: to.map(str::to_string),
+                    },
+                    time,
+                    source_file,
+                    full.start(),
+                    context,
+                    locations,
+                )
+            });
+        }
+
+        // Dispatches: the sender is named before 遣, or in 本紀 is the emperor
+        for caps in self.re_dispatch.captures_iter(content) {
+            let full = caps.get(0).unwrap();
+            let agent_match = caps.get(2).unwrap();
+            let agent = person_at(content, agent_match);
+            if !Self::accept_name(agent, content, agent_match.start(), section) {
+                continue;
+            }
+            let dispatcher = caps
+                .get(1)
+                .map(|m| person_at(content, m))
+                .filter(|p| !crate::intext::is_false_positive_name(p))
+                .map(str::to_string)
+                .or_else(|| subject.clone());
+            let purpose = format!(
+                "{}{}",
+                caps.get(3).unwrap().as_str(),
+                caps.get(4).unwrap().as_str()
+            );
+
+            let time = Self::find_time_context(&times, full.start());
+            let context = self.context(EventKind::DISPATCH, content, full.start());
+            let locations = self.extract_places_from_context(&context);
+
+            events.push(Event {
+                extracted_by: self.provenance("re_dispatch"),
+                ..Event::new(
+                    EventKind::Dispatch {
+                        dispatcher,
+                        agent: agent.to_string(),
+                        purpose,
+                        troops: self.troops_near(content, full.start()),
+                        units: self.units_near(content, full.start()),
+                    },
+                    time,
+                    source_file,
+                    full.start(),
+                    context,
+                    locations,
+                )
+            });
+        }
+
+        // Usurper era declarations
+        for caps in self.re_usurpation.captures_iter(content) {
+            let full = caps.get(0).unwrap();
+            let era = caps.get(2).or_else(|| caps.get(3)).unwrap().as_str();
+            // The greedy name pattern swallows the 自 of "自稱帝"
+            let person = caps
+                .get(1)
+                .map(|m| person_at(content, m))
+                .map(|p| p.strip_suffix('自').unwrap_or(p))
+                .filter(|p| !crate::intext::is_false_positive_name(p))
+                .map(str::to_string);
+
+            let time = Self::find_time_context(&times, full.start());
+            let context = self.context(EventKind::USURPATION_ERA, content, full.start());
+            let locations = self.extract_places_from_context(&context);
+
+            events.push(Event {
+                extracted_by: self.provenance("re_usurpation"),
+                ..Event::new(
+                    EventKind::UsurpationEra {
+                        person,
+                        era: era.to_string(),
+                        known_era: regime::ERA_NAMES.iter().any(|e| e.name == era),
+                    },
+                    time,
+                    source_file,
+                    full.start(),
+                    context,
+                    locations,
+                )
+            });
+        }
+
+        // Population moves
+        for caps in self.re_population.captures_iter(content) {
+            let verb = caps.get(2).unwrap();
+            let Some((group, count, to)) = population_move(caps.get(3).unwrap().as_str()) else {
+                continue;
+            };
+            let named = caps
+                .get(1)
+                .filter(|&m| Self::accept_name(person_at(content, m), content, m.start(), section));
+            let (person, offset) = match named {
+                Some(m) => (Some(person_at(content, m).to_string()), m.start()),
+                None => (subject.clone(), verb.start()),
+            };
+
+            let time = Self::find_time_context(&times, offset);
+            let context = self.context(EventKind::POPULATION_MOVE, content, offset);
+            let locations = self.extract_places_from_context(&context);
+
+            events.push(Event {
+                extracted_by: self.provenance("re_population"),
+                ..Event::new(
+                    EventKind::PopulationMove {
+                        person,
+                        verb: verb.as_str().to_string(),
+                        group: group.map(str::to_string),
+                        count: count.map(|(n, _)| n),
+                        unit: count.map(|(_, u)| u.to_string()),
+                        to: to.map(str::to_string),
+                    },
+                    time,
+                    source_file,
+                    offset,
+                    context,
+                    locations,
+                )
+            });
+        }
+
+        // Diplomatic missions and court visits
+        let missions = self
+            .re_diplomacy
+            .captures_iter(content)
+            .map(|caps| {
+                let sender = caps.get(1).and_then(|m| diplomatic_sender(m.as_str()));
+                let offset = caps.get(0).unwrap().start();
+                (
+                    sender,
+                    caps.get(2).unwrap().as_str(),
+                    caps.get(3),
+                    offset,
+                    "re_diplomacy",
+                )
+            })
+            .chain(
+                self.re_court_visit
+                    .captures_iter(content)
+                    .filter_map(|caps| {
+                        let name = caps.get(1).unwrap();
+                        Self::accept_name(name.as_str(), content, name.start(), section).then(
+                            || {
+                                let sender = Some(person_at(content, name).to_string());
+                                (sender, "朝", caps.get(2), name.start(), "re_court_visit")
+                            },
+                        )
+                    }),
+            )
+            .collect::<Vec<_>>();
+        for (sender, kind, target, offset, rule) in missions {
+            let time = Self::find_time_context(&times, offset);
+            let context = self.context(EventKind::DIPLOMACY, content, offset);
+            let locations = self.extract_places_from_context(&context);
+
+            events.push(Event {
+                extracted_by: self.provenance(rule),
+                ..Event::new(
+                    EventKind::Diplomacy {
+                        sender,
+                        target: target.map(|m| m.as_str().to_string()),
+                        kind: kind.to_string(),
+                    },
+                    time,
+                    source_file,
+                    offset,
+                    context,
+                    locations,
+                )
+            });
+        }
+
+        // Omens and disasters. A bare 旱 counts only at the end of a clause
+        // (京師旱。): not in 水旱, the stock pair "floods and droughts", nor
+        // in 爲旱而祭 or the 新羅 title 旱支.
+        for m in self.re_omen.find_iter(content) {
+            let clause_end = content[m.end()..]
+                .chars()
+                .next()
+                .is_none_or(|c| "，。；：\n\r".contains(c));
+            if m.as_str() == "旱" && (content[..m.start()].ends_with('水') || !clause_end) {
+                continue;
+            }
+            let time = Self::find_time_context(&times, m.start());
+            let context = self.context(EventKind::OMEN, content, m.start());
+            let locations = self.extract_places_from_context(&context);
+
+            events.push(Event {
+                extracted_by: self.provenance("re_omen"),
+                ..Event::new(
+                    EventKind::Omen {
+                        phenomenon: m.as_str().to_string(),
+                        place: omen_place(&content[..m.start()], &context),
+                    },
+                    time,
+                    source_file,
+                    m.start(),
+                    context,
+                    locations,
+                )
+            });
+        }
+
+        // Rebellions
+        for caps in self.re_rebellion.captures_iter(content) {
+            let full = caps.get(0).unwrap();
+            let name = caps.get(1).unwrap();
+            let person = person_at(content, name);
+            let verb = caps.get(2).unwrap().as_str();
+
+            let next = content[full.end()..].chars().next();
+            if (verb == "反" && next.is_some_and(|c| "覆正側間顧命國".contains(c)))
+                || !Self::accept_name(person, content, name.start(), section)
+            {
+                continue;
+            }
+
+            let context = self.context(EventKind::REBELLION, content, full.start());
+            let place = caps.get(3).map(|m| {
+                let name = m.as_str().trim_end_matches(['以', '而']).to_string();
+                PlaceRef {
+                    is_qiao: detect_qiao(&name, &context),
+                    ..PlaceRef::new(name)
+                }
+            });
+            let time = Self::find_time_context(&times, full.start());
+            let locations = self.extract_places_from_context(&context);
+
+            events.push(Event {
+                extracted_by: self.provenance("re_rebellion"),
+                ..Event::new(
+                    EventKind::Rebellion {
+                        person: person.to_string(),
+                        verb: verb.to_string(),
+                        place,
+                    },
+                    time,
+                    source_file,
+                    full.start(),
+                    context,
+                    locations,
+                )
+            });
+        }
+
+        // Surrenders
+        for caps in self.re_surrender.captures_iter(content) {
+            let full = caps.get(0).unwrap();
+            let name = caps.get(1).unwrap();
+            let person = person_at(content, name);
+            let verb = caps.get(2).unwrap().as_str();
+            let to = caps.get(3).or(caps.get(4)).map(|m| m.as_str().to_string());
+
+            let next = content[full.end()..].chars().next();
+            let demotion = to.is_none() && next.is_some_and(|c| "為爲號封爵階等".contains(c));
+            if demotion || !Self::accept_name(person, content, name.start(), section) {
+                continue;
+            }
+
+            let time = Self::find_time_context(&times, full.start());
+            let context = self.context(EventKind::SURRENDER, content, full.start());
+            let locations = self.extract_places_from_context(&context);
+
+            events.push(Event {
+                extracted_by: self.provenance("re_surrender"),
+                ..Event::new(
+                    EventKind::Surrender {
+                        person: person.to_string(),
+                        verb: verb.to_string(),
+                        to,
+                    },
+                    time,
+                    source_file,
+                    full.start(),
+                    context,
+                    locations,
+                )
+            });
+        }
+
+        // Marriages
+        for caps in self.re_marriage.captures_iter(content) {
+            let full = caps.get(0).unwrap();
+            let name = caps.get(1).unwrap();
+            let person = person_at(content, name);
+            let verb = caps.get(2).unwrap().as_str();
+            let Some(spouse) = marriage_spouse(verb, caps.get(3).unwrap().as_str()) else {
+                continue;
+            };
+            if !starts_clause(content, name.start())
+                || person.ends_with(MARRIAGE_NAME_TAILS)
+                || !Self::accept_name(person, content, name.start(), section)
+            {
+                continue;
+            }
+
+            let time = Self::find_time_context(&times, full.start());
+            let context = self.context(EventKind::MARRIAGE, content, full.start());
+            let locations = self.extract_places_from_context(&context);
+
+            events.push(Event {
+                extracted_by: self.provenance("re_marriage"),
+                ..Event::new(
+                    EventKind::Marriage {
+                        person: person.to_string(),
+                        verb: verb.to_string(),
+                        spouse,
+                    },
+                    time,
+                    source_file,
+                    full.start(),
+                    context,
+                    locations,
+                )
+            });
+        }
+
+        // The subject's events, under any of their names, are theirs
+        if let Some(subject) = bio_subject {
+            let full = subject.full_name();
+            let renamed: Vec<String> = subject
+                .renamed
+                .iter()
+                .map(|g| format!("{}{g}", subject.surname))
+                .collect();
+            for e in &mut events {
+                if let Some(person) = e.person_name_mut()
+                    && (*person == full || renamed.contains(person))
+                {
+                    person.clone_from(&full);
+                    e.person_id = Some(source_file.to_string());
+                }
+            }
+        }
+
+        merge_adjacent(&mut events, content, self.merge_window);
+
+        // Same rule as `find_time_context`: the last scope starting before the event
+        for e in &mut events {
+            e.scope_index = scopes
+                .iter()
+                .rposition(|s| s.span.byte_start < e.byte_offset);
+        }
+
+        // Context and places come from the text as written, without the
+        // surnames put in for the subject's bare given name
+        if joined.attributed.is_some() {
+            for e in &mut events {
+                let at = joined.to_joined(e.byte_offset);
+                e.context = self.context(e.kind.name(), &joined.text, at);
+                e.locations = self.extract_places_from_context(&e.context);
+            }
+        }
+
+        resolve_place_ids(&mut events);
+        joined.map_to_source(&mut events, &mut scopes);
+        joined.map_to_source(&mut [], &mut era_refs);
+        (events, scopes, era_refs)
+    }
+
+    /// Scan the entire corpus.
+    pub fn scan_corpus(&self, bio_files: &[BiographyFile]) -> (Vec<Event>, TimeIndex, EventStats) {
+        let mut all_events = Vec::new();
+        let mut all_scopes = Vec::new();
+        let mut era_refs = Vec::new();
+        let mut era_dist: HashMap<String, usize> = HashMap::new();
+        let mut place_counts: HashMap<String, usize> = HashMap::new();
+        let mut time_set = std::collections::HashSet::new();
+        let mut appointments = 0usize;
+        let mut battles = 0usize;
+        let mut deaths = 0usize;
+        let mut enfeoffments = 0usize;
+        let mut defeats = 0usize;
+        let mut dispatches = 0usize;
+        let mut tenure_ends = 0usize;
+        let mut declines = 0usize;
+        let mut investitures = 0usize;
+        let mut pacifications = 0usize;
+        let mut posthumous_grants = 0usize;
+        let mut abdications = 0usize;
+        let mut usurpation_eras = 0usize;
+        let mut population_moves = 0usize;
+        let mut diplomatic_missions = 0usize;
+        let mut omens = 0usize;
+        let mut rebellions = 0usize;
+        let mut surrenders = 0usize;
+        let mut marriages = 0usize;
+
+        // Files are scanned in parallel; collecting keeps corpus order, so
+        // the merge below is the same whatever order the threads finish in.
+        let scanned: Vec<_> = bio_files
+            .par_iter()
+            .filter_map(|bio| {
+                let content = read_text(&bio.path).ok()?;
+                let path = bio.path.display().to_string();
+                Some(self.scan_file_with_era_refs(
+                    &content,
+                    bio.source.book,
+                    bio.source.section,
+                    &path,
+                ))
+            })
+            .collect();
+
+        let mut suspect_time_refs = 0usize;
+        let mut unresolved_ganzhi_years = 0usize;
+        for (mut events, scopes, refs) in scanned {
+            suspect_time_refs += scopes
+                .iter()
+                .filter(|s| s.time.suspect && s.time.names_era())
+                .count();
+            // Scope indices are file-local until rebased onto the corpus index
+            for e in &mut events {
+                if let Some(i) = &mut e.scope_index {
+                    *i += all_scopes.len();
+                }
+            }
+
+            for e in &events {
+                match &e.kind {
+                    EventKind::Appointment { place, .. } => {
+                        appointments += 1;
+                        if let Some(p) = place {
+                            *place_counts.entry(p.name.clone()).or_insert(0) += 1;
+                        }
+                    }
+                    EventKind::Battle { target_place, .. } => {
+                        battles += 1;
+                        if let Some(p) = target_place {
+                            *place_counts.entry(p.name.clone()).or_insert(0) += 1;
+                        }
+                    }
+                    EventKind::Death { .. } => {
+                        deaths += 1;
+                    }
+                    EventKind::Enfeoffment { .. } => {
+                        enfeoffments += 1;
+                    }
+                    EventKind::Defeat { .. } => {
+                        defeats += 1;
+                    }
+                    EventKind::Dispatch { .. } => {
+                        dispatches += 1;
+                    }
+                    EventKind::TenureEnd { .. } => {
+                        tenure_ends += 1;
+                    }
+                    EventKind::Decline { .. } => {
+                        declines += 1;
+                    }
+                    EventKind::Investiture { .. } => {
+                        investitures += 1;
+                    }
+                    EventKind::Pacification { place, .. } => {
+                        pacifications += 1;
+                        *place_counts.entry(place.name.clone()).or_insert(0) += 1;
+                    }
+                    EventKind::PosthumousGrant { .. } => {
+                        posthumous_grants += 1;
+                    }
+                    EventKind::Abdication { .. } => {
+                        abdications += 1;
+                    }
+                    EventKind::UsurpationEra { .. } => {
+                        usurpation_eras += 1;
+                    }
+                    EventKind::PopulationMove { .. } => {
+                        population_moves += 1;
+                    }
+                    EventKind::Diplomacy { .. } => {
+                        diplomatic_missions += 1;
+                    }
+                    EventKind::Omen { .. } => {
+                        omens += 1;
+                    }
+                    EventKind::Rebellion { .. } => {
+                        rebellions += 1;
+                    }
+                    EventKind::Surrender { .. } => {
+                        surrenders += 1;
+                    }
+                    EventKind::Marriage { .. } => {
+                        marriages += 1;
+                    }
+                }
+                if let Some(t) = &e.time {
+                    *era_dist
+                        .entry(format!("{}/{}", t.regime, t.era))
+                        .or_insert(0) += 1;
+                    time_set.insert(format!("{}{}", t.era, t.year));
+                }
+            }
+
+            unresolved_ganzhi_years += refs
+                .iter()
+                .filter(|s| s.time.precision == TimePrecision::Sexagenary)
+                .count();
+            all_events.extend(events);
+            all_scopes.extend(scopes);
+            era_refs.extend(refs);
+        }
+
+        let mut top_places: Vec<(String, usize)> = place_counts.into_iter().collect();
+        // Ties broken by name so the cut at 30 does not follow hash order
+        top_places.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        top_places.truncate(30);
+
+        let stats = EventStats {
+            total_events: all_events.len(),
+            appointments,
+            battles,
+            deaths,
+            enfeoffments,
+            defeats,
+            dispatches,
+            tenure_ends,
+            declines,
+            investitures,
+            pacifications,
+            posthumous_grants,
+            abdications,
+            usurpation_eras,
+            population_moves,
+            diplomatic_missions,
+            omens,
+            rebellions,
+            surrenders,
+            marriages,
+            unique_time_refs: time_set.len(),
+            suspect_time_refs,
+            unresolved_ganzhi_years,
+            unique_places: top_places.len(),
+            era_distribution: era_dist,
+            top_places,
+        };
+
+        let time_index = TimeIndex {
+            scopes: all_scopes,
+            era_refs,
+        };
+
+        (all_events, time_index, stats)
+    }
+}
+
+/// Split "王進侍中" into ("王進", "侍中"): a known-surname name of 2-3 chars
+/// followed by an office. None when the text is an office alone:
+/// "黃門侍郎" is not 黃門 + 侍郎 (see `OFFICE_HEADS`), and "游擊將軍" or
+/// "吳興太守" is not a name + bare suffix, since 將軍/太守 need a qualifier;
+/// "江夏王前軍長史" is a prince's staff post, not 江夏 + 王前軍長史.
+fn split_appointee(s: &str) -> Option<(&str, &str)> {
+    let ends: Vec<usize> = s
+        .char_indices()
+        .map(|(i, c)| i + c.len_utf8())
+        .take(3)
+        .collect();
+    ends.iter().skip(1).rev().find_map(|&end| {
+        let (name, title) = s.split_at(end);
+        let (surname, given) = split_name(name)?;
+        let plausible = is_known_surname(&surname)
+            && (1..=2).contains(&given.chars().count())
+            && !given.contains(['州', '郡', '縣'])
+            && !crate::intext::is_false_positive_name(name)
+            && !OFFICE_HEADS.iter().any(|h| name.starts_with(h))
+            && !TITLE_SUFFIXES.iter().any(|t| title.starts_with(t))
+            && !title.starts_with(['王', '公', '侯'])
+            && is_office(title);
+        plausible.then_some((name, title))
+    })
+}
+
+/// A known surname followed by a one- or two-char given name, not on the
+/// false-positive list.
+fn is_plausible_name(name: &str) -> bool {
+    split_name(name).is_some_and(|(surname, given)| {
+        is_known_surname(&surname)
+            && (1..=2).contains(&given.chars().count())
+            && given.chars().all(char::is_alphabetic)
+    }) && !crate::intext::is_false_positive_name(name)
+}
+
+/// The appointees listed before the last one in 以X、Y及Z為: `prefix` is
+/// the text between 以 and the last name ("王進、張華及"). Empty unless
+/// it ends in a conjunction and every item is a plain name, so a title
+/// or phrase before a single appointee (以前將軍王進為) is never split.
+fn co_appointees(prefix: &str) -> Vec<&str> {
+    let Some(list) = prefix.strip_suffix(['、', '及', '與']) else {
+        return Vec::new();
+    };
+    let names: Vec<&str> = list.split(['、', '及', '與']).collect();
+    if names.iter().all(|n| is_plausible_name(n)) {
+        names
+    } else {
+        Vec::new()
+    }
+}
+
+/// Whether a subject-less verb at `offset` starts a clause (or follows an
+/// adverb such as 尋/又/俄), so the omitted subject is the file's subject.
+fn omitted_subject_ok(content: &str, offset: usize) -> bool {
+    content[..offset]
+        .chars()
+        .next_back()
+        .is_none_or(|c| !c.is_alphabetic() || "尋又俄累仍復乃後遂超改便即卽".contains(c))
+}
+
+/// Verbs that set up an émigré (僑) unit: 僑立南琅邪郡, 僑置徐州.
+const QIAO_MARKERS: &[&str] = &["僑置", "僑立", "僑寄"];
+
+/// How many characters may separate a 僑置 marker from the place name.
+const QIAO_WINDOW: usize = 4;
+
+/// Whether `name` is an émigré (僑置) unit: a 南-prefixed province
+/// (南徐州), a name with the 僑郡/僑縣 suffix, or one that `context`
+/// marks with 僑置/僑立/僑寄 within a few characters of it.
+pub fn detect_qiao(name: &str, context: &str) -> bool {
+    if name.starts_with('南') && name.ends_with('州') && name.chars().count() >= 3 {
+        return true;
+    }
+    if ["僑州", "僑郡", "僑縣"].iter().any(|s| name.ends_with(s)) {
+        return true;
+    }
+    // a marker is two characters, so look QIAO_WINDOW + 2 either side
+    context.match_indices(name).any(|(at, _)| {
+        let end = at + name.len();
+        let from = context[..at]
+            .char_indices()
+            .rev()
+            .nth(QIAO_WINDOW + 1)
+            .map_or(0, |(i, _)| i);
+        let to = context[end..]
+            .char_indices()
+            .nth(QIAO_WINDOW + 2)
+            .map_or(context.len(), |(i, _)| end + i);
+        let (before, after) = (&context[from..at], &context[end..to]);
+        QIAO_MARKERS
+            .iter()
+            .any(|m| before.contains(m) || after.contains(m))
+    })
 }
 
-/// Compute the exact AD year for a time reference.
-///
-/// Uses the per-era `start_ad` from `ERA_NAMES` (scraped from Wikipedia)
-/// to give a precise result: `start_ad + (year - 1)`.
-pub fn exact_ad_year(regime_chinese: &str, era_name: &str, year: u8) -> Option<u16> {
-    let entry = regime::ERA_NAMES
-        .iter()
-        .find(|e| e.regime.as_chinese() == regime_chinese && e.name == era_name)?;
-    Some(entry.start_ad + (year as u16 - 1))
-}
+/// Last characters that mark a place name: 壽陽城, 潼關, 江陵縣 ...
+const GEO_SUFFIXES: &[char] = &[
+    '州', '郡', '縣', '城', '關', '塞', '鎮', '壁', '山', '水', '河', '江', '池', '谷', '嶺', '津',
+    '渡', '橋', '亭', '營', '壘',
+];
+
+/// Battle targets named without a place suffix that are not commanderies
+/// in the gazetteer: cities, forts and frontier towns.
+const BARE_CITIES: &[&str] = &[
+    "江陵", "成都", "建康", "建業", "京口", "壽陽", "壽春", "襄陽", "武昌", "許昌", "洛陽", "長安",
+    "黎陽", "金墉", "錢塘", "林邑", "和龍", "巴東", "魏興", "梁父", "武街", "盱眙", "歷陽", "姑孰",
+    "石頭", "新亭", "滑臺", "虎牢", "碻磝", "懸瓠", "合肥", "尋陽", "夏口", "湓口", "巴陵", "上邽",
+    "枹罕", "統萬", "姑臧", "廣固", "鄴", "晉陽", "蒲坂", "潼關", "漢中", "南鄭", "白帝", "巴丘",
+    "鍾離", "單父", "林慮", "白沙",
+];
+
+/// Regions named without an administrative suffix, longest first where
+/// one is a prefix of another.
+const REGIONS: &[&str] = &[
+    "關中", "關東", "關右", "關西", "河北", "河南", "河東", "河西", "河朔", "江南", "江北", "江東",
+    "江西", "江表", "淮南", "淮北", "淮西", "隴右", "隴西", "巴蜀", "漢中", "三吳", "山東", "中原",
+    "嶺南", "荊楚", "青齊", "京邑", "京師",
+];
+
+/// States conquered as a whole (平吳, 定蜀), written with one character.
+const STATES: &str = "吳蜀齊燕秦涼趙夏魏";
+
+/// The place pacified by 平/定 at the start of `s`: a region (關中), the
+/// shortest prefix ending in a place suffix (壽陽城), or a one-character
+/// state followed by a clause break or 之/後/地 (平吳之後).
+fn pacified_place(s: &str, context: &str) -> Option<PlaceRef> {
+    let place_ref = |name: &str| PlaceRef {
+        is_qiao: detect_qiao(name, context),
+        ..PlaceRef::new(name)
+    };
+    if let Some(r) = REGIONS.iter().find(|r| s.starts_with(*r)) {
+        return Some(place_ref(r));
+    }
+    let first = s.chars().next()?;
+    // 平城, 定州: the verb char is part of the place name
+    if GEO_SUFFIXES.contains(&first) {
+        return None;
+    }
+    let prefix_end = s
+        .char_indices()
+        .take(4)
+        .skip(1)
+        .find(|(_, c)| GEO_SUFFIXES.contains(c))
+        .map(|(i, c)| i + c.len_utf8());
+    if let Some(end) = prefix_end
+        && is_plausible_place(&s[..end])
+    {
+        let collective = collective_region(&s[..end]);
+        return Some(PlaceRef {
+            collective: collective.is_some(),
+            region: collective.flatten(),
+            ..place_ref(&s[..end])
+        });
+    }
+    let next = s[first.len_utf8()..].chars().next();
+    (STATES.contains(first) && next.is_none_or(|c| !c.is_alphabetic() || "之後地境".contains(c)))
+        .then(|| place_ref(&s[..first.len_utf8()]))
+}
+
+/// For a collective target ("江南諸郡", "數郡", "三州"), the region that
+/// qualifies it, if any: Some(Some("江南")), Some(None). None when the
+/// target is a single place.
+fn collective_region(target: &str) -> Option<Option<String>> {
+    let units = target.strip_suffix(['州', '郡', '縣', '城'])?;
+    let qualifier = units.strip_suffix(['諸', '數']).or_else(|| {
+        let q = units.trim_end_matches(|c| "二三四五六七八九十".contains(c));
+        (q.len() < units.len()).then_some(q)
+    })?;
+    Some((!qualifier.is_empty()).then(|| qualifier.to_string()))
+}
+
+/// The office at the start of `s`, up to the last title in it:
+/// "侍中還第" → "侍中", "荊州刺史" → "荊州刺史". None when `s` does not
+/// start with an office.
+fn office_prefix(s: &str) -> Option<&str> {
+    let end = TITLE_SUFFIXES
+        .iter()
+        .chain(STANDALONE_TITLES)
+        .filter_map(|t| s.rfind(t).map(|i| i + t.len()))
+        .max()?;
+    let office = &s[..end];
+    // 讓還第及侍中 is not an office: the title must come within a few chars
+    (office.chars().count() <= 8 && !office.contains(['及', '與', '而'])).then_some(office)
+}
+
+/// The title of a posthumous grant at the start of `s`: a fief and rank
+/// after 追封 (武昌縣侯), an office otherwise (司空). 贈 alone also means
+/// "to give", so anything that is not a title is rejected.
+fn posthumous_title<'a>(verb: &str, s: &'a str) -> Option<&'a str> {
+    if verb == "追封" {
+        return s
+            .char_indices()
+            .map(|(i, c)| &s[..i + c.len_utf8()])
+            .take(6)
+            .find(|prefix| split_fief(prefix).is_some());
+    }
+    office_prefix(s)
+}
+
+/// Posthumous office and 謚 from the clauses right after a death notice,
+/// within the same sentence: "薨，時年六十，贈司空，謚曰忠". The office
+/// comes with its byte offset in `after`. A grant further on is left to
+/// the standalone `PosthumousGrant` pass.
+fn death_honours(after: &str) -> (Option<(usize, &str)>, Option<&str>) {
+    let sentence = after.split(['。', '；']).next().unwrap_or_default();
+    let mut title = None;
+    let mut name = None;
+    let mut clause_start = 0;
+    // The first piece is what trails the verb itself (薨[於位])
+    for (i, clause) in sentence.split('，').enumerate().take(5) {
+        let at = clause_start;
+        clause_start += clause.len() + '，'.len_utf8();
+        if i == 0 {
+            continue;
+        }
+        if let Some(rest) = ["詔贈", "追贈", "贈"]
+            .iter()
+            .find_map(|v| clause.strip_prefix(v))
+        {
+            let offset = at + clause.len() - rest.len();
+            title = title.or(office_prefix(rest).map(|t| (offset, t)));
+        } else if let Some(rest) = clause.strip_prefix(['謚', '諡']) {
+            let rest = rest.trim_start_matches(['曰', '為', '爲']);
+            if (1..=4).contains(&rest.chars().count()) {
+                name = name.or(Some(rest));
+            }
+        }
+    }
+    (title, name)
+}
+
+/// Age at death: 時年N or 春秋N.
+static RE_DEATH_AGE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new("(?:時年|春秋)([一二三四五六七八九十百]+)").expect("death age regex")
+});
+
+/// Age given in the sentence after a death notice: "卒，時年七十二".
+fn death_age(after: &str) -> Option<u16> {
+    let sentence = after.split(['。', '；']).next().unwrap_or_default();
+    let caps = RE_DEATH_AGE.captures(sentence)?;
+    parse_cn_count(&caps[1]).and_then(|n| u16::try_from(n).ok())
+}
+
+/// The successor named after 禪位於: a prince or duke (齊王, 宋公), or a
+/// short name or state (道成, 宋, 王進). None for the usurper's 己/朕 and
+/// for longer phrases. A leading 王/公 is a surname, not the title.
+fn abdication_successor(s: &str) -> Option<&str> {
+    if let Some((i, _)) = s
+        .char_indices()
+        .skip(1)
+        .find(|(_, c)| ['王', '公'].contains(c))
+    {
+        return Some(&s[..i + '王'.len_utf8()]);
+    }
+    let s = s.trim_end_matches(['者', '焉']);
+    let plausible = (1..=3).contains(&s.chars().count())
+        && !s.starts_with(['己', '朕', '其', '皇'])
+        && !crate::intext::is_false_positive_name(s);
+    plausible.then_some(s)
+}
+
+/// Where an omen struck, from the text right before it: the capital
+/// (京師地震) or a province (雍州大水, 南徐州大水). Commanderies are left
+/// out: without a fixed name length the start of 郡 names is a guess.
+fn omen_place(before: &str, context: &str) -> Option<PlaceRef> {
+    let name = if let Some(capital) = ["京師", "京都", "京邑"]
+        .into_iter()
+        .find(|c| before.ends_with(c))
+    {
+        capital.to_string()
+    } else if before.ends_with('州') {
+        let mut chars = before.chars().rev().take(3).collect::<Vec<_>>();
+        if chars.get(2) != Some(&'南') {
+            chars.truncate(2);
+        }
+        let name: String = chars.into_iter().rev().collect();
+        if name.chars().count() < 2 || !is_plausible_place(&name) {
+            return None;
+        }
+        name
+    } else {
+        return None;
+    };
+    Some(PlaceRef {
+        is_qiao: detect_qiao(&name, context),
+        ..PlaceRef::new(name)
+    })
+}
+
+/// The sending court of a mission, from the clause before 遣使: a state
+/// (高麗國), a ruler (蕭衍) or a list of states. Dates and adverbs that
+/// open the clause are not senders.
+fn diplomatic_sender(s: &str) -> Option<String> {
+    const ADVERBS: &[char] = &[
+        '並', '各', '又', '頻', '乃', '復', '累', '始', '數', '曾', '猶', '每', '將', '尋', '遂',
+        '前', '後',
+    ];
+    let s = s.trim_matches(ADVERBS);
+    let dated = s.contains(['年', '月', '是', '歲', '時', '皆', '此']);
+    (!s.is_empty() && !dated).then(|| s.to_string())
+}
+
+/// (group, count and unit, destination) of a population move.
+type Population<'a> = (Option<&'a str>, Option<(u32, &'a str)>, Option<&'a str>);
+
+/// The population in a 遷/徙 clause: "豪族三千戶於京師" → (豪族, 3000 戶,
+/// 京師), "其民於代" → (其民, none, 代). The moved must be counted in
+/// 戶/家/口 or end in 民/百姓; anything else (遷尚書令, 徙居會稽) is not
+/// a population move.
+fn population_move(s: &str) -> Option<Population<'_>> {
+    static RE_COUNTED: LazyLock<Regex> = LazyLock::new(|| {
+        Regex::new("^(.*?)([一二三四五六七八九十百千萬餘數]+)(戶|家|口)$")
+            .expect("population regex")
+    });
+    let (head, to) = match s.find(['於', '于']) {
+        Some(i) => (&s[..i], &s[i + '於'.len_utf8()..]),
+        None => match s.find("以實") {
+            Some(i) => (&s[..i], &s[i + "以實".len()..]),
+            None => (s, ""),
+        },
+    };
+    let to = (!to.is_empty()).then_some(to);
+    if let Some(caps) = RE_COUNTED.captures(head) {
+        // 二十餘萬口 is read as 二十萬; 數千戶 is a move of unknown size
+        let num = caps.get(2).unwrap().as_str().replace('餘', "");
+        // 萬家: a bare 萬 is one 萬 here, not a place name like 統萬
+        let n = match num.strip_prefix('萬') {
+            Some(rest) => parse_cn_count(&format!("一萬{rest}")),
+            None => parse_cn_count(&num),
+        };
+        let group = caps.get(1).unwrap().as_str();
+        let unit = caps.get(3).unwrap().as_str();
+        return Some((
+            (!group.is_empty()).then_some(group),
+            n.map(|n| (n, unit)),
+            to,
+        ));
+    }
+    // 初遷之民 is "people who had moved", 徙X為民 a convict made a commoner
+    let plausible = (head.ends_with('民') || head.ends_with("百姓"))
+        && !head.starts_with(['之', '新'])
+        && !head.contains(['為', '爲', '配']);
+    (plausible && head.chars().count() <= 6).then_some((Some(head), None, to))
+}
+
+/// The office conferred by the last 為/除/拜/... clause in `s`, for an
+/// appointment too loosely worded to be extracted: "除散騎常侍，" → 散騎常侍.
+fn preceding_office(s: &str) -> Option<&str> {
+    let (i, c) = s
+        .char_indices()
+        .rfind(|(_, c)| "為爲除拜授徵遷轉".contains(*c))?;
+    let rest = &s[i + c.len_utf8()..];
+    office_prefix(rest.split(['，', '、']).next()?)
+}
+
+/// The spouse phrase of a marriage match, or None when the match is not a
+/// marriage. 尚 only counts before a princess (尚主, or a phrase up to 公主
+/// naming no one else: 子尚及山陰主 is a name); 娶 keeps the phrase up to
+/// the first 女 (娶王氏女為妻 → 王氏女); 妻以 needs a kin noun after it
+/// (妻以女, not 妻以妒忌賜死).
+fn marriage_spouse(verb: &str, phrase: &str) -> Option<Option<String>> {
+    let phrase = match verb {
+        "尚" if phrase.starts_with('主') => "主",
+        "尚" => {
+            let princess = &phrase[..phrase.find("公主")? + "公主".len()];
+            if princess.contains(['及', '與']) {
+                return None;
+            }
+            princess
+        }
+        "娶" => phrase
+            .find('女')
+            .map_or(phrase, |i| &phrase[..i + '女'.len_utf8()]),
+        _ => {
+            let kin = phrase.strip_prefix('其').unwrap_or(phrase);
+            if !kin.starts_with(['女', '妹', '姊']) && !kin.starts_with("宗女") {
+                return None;
+            }
+            phrase
+        }
+    };
+    Some((!phrase.is_empty()).then(|| phrase.to_string()))
+}
+
+/// Whether a name matched at `start` opens its clause, so it is the
+/// subject of the verb after it rather than the tail of a phrase glued to
+/// the verb (百姓忽聞其尚主).
+fn starts_clause(content: &str, start: usize) -> bool {
+    content[..start]
+        .chars()
+        .next_back()
+        .is_none_or(|c| !c.is_alphabetic())
+}
+
+/// Last chars of a clause-initial marriage "name" that is really a period
+/// or title plus an adverb: 宋末選尚公主, 齊代娶, 高祖先娶.
+const MARRIAGE_NAME_TAILS: &[char] = &['選', '代', '先', '末', '初', '乃', '又', '嘗'];
+
+/// The name captured by `m`, minus a glued-on honorific or kin prefix
+/// (see `intext::strip_name_prefix`).
+fn person_at<'a>(content: &'a str, m: regex::Match<'a>) -> &'a str {
+    crate::intext::strip_name_prefix(&content[m.start()..]).unwrap_or(m.as_str())
+}
+
+/// The emperor an annals file is about, from its juan directory:
+/// ".../00_本紀第一　武帝上/02_武帝上.txt" → 武帝. None when the juan
+/// covers several emperors (帝紀第七　顯宗成帝 康帝) or the path has no juan.
+fn annals_subject(source_file: &str) -> Option<String> {
+    let juan = std::path::Path::new(source_file)
+        .parent()?
+        .file_name()?
+        .to_str()?;
+    let tokens: Vec<&str> = juan.split_whitespace().collect();
+    match tokens.as_slice() {
+        [_, name] => {
+            let name = name.trim_end_matches(['上', '中', '下']);
+            (!name.is_empty()).then(|| name.to_string())
+        }
+        _ => None,
+    }
+}
+
+/// Private or household troop bodies named as units (將部曲五千).
+const RETAINER_UNITS: &str = "部曲|家兵|私兵";
+
+/// Noble ranks, longest first so 縣侯 wins over 侯. Bare 伯/子/男 are left
+/// out: "封其長子" and the like would swamp real grants.
+const FIEF_RANKS: &str = "郡王|郡公|縣公|郡侯|縣侯|鄉侯|亭侯|縣伯|縣子|縣男|王|公|侯";
+
+/// Split "武昌縣侯" into ("武昌", "縣侯"). The fief must be 1-4 chars.
+fn split_fief(s: &str) -> Option<(String, String)> {
+    let rank = FIEF_RANKS.split('|').find(|r| s.ends_with(r))?;
+    let fief = &s[..s.len() - rank.len()];
+    if !(1..=4).contains(&fief.chars().count()) {
+        return None;
+    }
+    Some((fief.to_string(), rank.to_string()))
+}
+
+/// Text with wrapped-line breaks removed, plus a map back to source offsets.
+struct JoinedText {
+    text: String,
+    /// (offset in `text`, total bytes removed before that offset)
+    removed: Vec<(usize, usize)>,
+    /// `text` with the biography subject's surname put before each bare
+    /// given name read as theirs (see `attribute_given_names`). Scanned in
+    /// place of `text` so the name patterns match it, but never copied
+    /// into an event.
+    attributed: Option<String>,
+    /// (offset in `attributed`, byte length) of the surnames put in, in
+    /// text order
+    inserted: Vec<(usize, usize)>,
+}
+
+impl JoinedText {
+    /// Lines shorter than this are headers/name lists, never wrapped prose.
+    const MIN_WRAPPED_LINE_CHARS: usize = 8;
+
+    /// Join a line break when the line before it is prose that stops
+    /// mid-sentence (no closing punctuation) and the next line continues
+    /// immediately. Paragraph breaks and short header lines are kept.
+    fn new(source: &str) -> Self {
+        let sentence_end = [
+            '。', '！', '？', '」', '』', '：', '；', '〉', '》', ']', '）',
+        ];
+        let mut text = String::with_capacity(source.len());
+        let mut removed = Vec::new();
+        let mut removed_total = 0;
+
+        let mut lines = source.split_inclusive('\n').peekable();
+        while let Some(line) = lines.next() {
+            let body = line.trim_end_matches(['\n', '\r']);
+            let joinable = body.chars().count() >= Self::MIN_WRAPPED_LINE_CHARS
+                && body
+                    .chars()
+                    .last()
+                    .is_some_and(|c| !sentence_end.contains(&c))
+                && lines
+                    .peek()
+                    .and_then(|next| next.chars().next())
+                    .is_some_and(|c| !c.is_whitespace());
+            if joinable {
+                text.push_str(body);
+                removed_total += line.len() - body.len();
+                removed.push((text.len(), removed_total));
+            } else {
+                text.push_str(line);
+            }
+        }
+        JoinedText {
+            text,
+            removed,
+            attributed: None,
+            inserted: Vec::new(),
+        }
+    }
+
+    /// The text the event patterns run over.
+    fn scanned(&self) -> &str {
+        self.attributed.as_deref().unwrap_or(&self.text)
+    }
+
+    /// Verbs that, right after a biography subject's bare given name,
+    /// make the name the subject of an event.
+    const SUBJECT_VERBS: &[&str] = &[
+        "遷", "轉", "除", "拜", "徵", "封", "出為", "入為", "卒", "薨", "遇害", "被害", "伏誅",
+        "固辭", "讓", "率", "討", "攻", "擊",
+    ];
+
+    /// Build `attributed`: `text` with `surname` before each
+    /// clause-initial occurrence of one of `givens` followed by a
+    /// `SUBJECT_VERBS` verb, so the name patterns see the full name. Only
+    /// the subject's own biography should be resolved: the same given name
+    /// in another file is usually someone else.
+    fn attribute_given_names(&mut self, surname: &str, givens: &[&str]) {
+        let clause_start = |before: &str| {
+            before
+                .chars()
+                .next_back()
+                .is_none_or(|c| "\n，。；：「".contains(c))
+        };
+        let mut text = String::with_capacity(self.text.len());
+        let mut mentions: Vec<(usize, &str)> = givens
+            .iter()
+            .flat_map(|g| self.text.match_indices(g))
+            .collect();
+        mentions.sort();
+        mentions.dedup_by_key(|&mut (at, _)| at);
+        let mut last = 0;
+        for (at, given) in mentions {
+            let after = &self.text[at + given.len()..];
+            if !clause_start(&self.text[..at])
+                || !Self::SUBJECT_VERBS.iter().any(|v| after.starts_with(v))
+            {
+                continue;
+            }
+            text.push_str(&self.text[last..at]);
+            self.inserted.push((text.len(), surname.len()));
+            text.push_str(surname);
+            last = at;
+        }
+        if self.inserted.is_empty() {
+            return;
+        }
+        text.push_str(&self.text[last..]);
+        self.attributed = Some(text);
+    }
+
+    /// Map a byte offset in the scanned text to `text`. An offset inside
+    /// an inserted surname maps to the given name after it.
+    fn to_joined(&self, offset: usize) -> usize {
+        offset
+            - self
+                .inserted
+                .iter()
+                .take_while(|&&(at, _)| at < offset)
+                .map(|&(at, len)| len.min(offset - at))
+                .sum::<usize>()
+    }
+
+    /// Map a byte offset in the scanned text back to the source text.
+    fn to_source(&self, offset: usize) -> usize {
+        let offset = self.to_joined(offset);
+        let idx = self.removed.partition_point(|&(at, _)| at <= offset);
+        offset + if idx == 0 { 0 } else { self.removed[idx - 1].1 }
+    }
+
+    fn map_to_source(&self, events: &mut [Event], scopes: &mut [TimeScope]) {
+        if self.removed.is_empty() && self.inserted.is_empty() {
+            return;
+        }
+        for e in events {
+            e.byte_offset = self.to_source(e.byte_offset);
+            if let Some(t) = &mut e.time {
+                t.byte_offset = self.to_source(t.byte_offset);
+            }
+        }
+        for s in scopes {
+            s.time.byte_offset = self.to_source(s.time.byte_offset);
+            s.span.byte_start = self.to_source(s.span.byte_start);
+            s.span.byte_end = self.to_source(s.span.byte_end);
+        }
+    }
+}
+
+/// Drop each event that repeats the kind and person of an earlier kept
+/// one within `window` chars of `content`. A window of 0 merges nothing.
+/// Events without a person are never merged.
+fn merge_adjacent(events: &mut Vec<Event>, content: &str, window: usize) {
+    if window == 0 {
+        return;
+    }
+    let mut order: Vec<usize> = (0..events.len()).collect();
+    order.sort_by_key(|&i| events[i].byte_offset);
+    let mut last_kept: HashMap<(&str, &str), usize> = HashMap::new();
+    let mut drop = vec![false; events.len()];
+    for i in order {
+        let e = &events[i];
+        let Some(person) = e.person_name() else {
+            continue;
+        };
+        let key = (e.kind.name(), person);
+        if let Some(&prev) = last_kept.get(&key)
+            && content[prev..e.byte_offset].chars().count() <= window
+        {
+            drop[i] = true;
+            continue;
+        }
+        last_kept.insert(key, e.byte_offset);
+    }
+    let mut drop = drop.into_iter();
+    events.retain(|_| !drop.next().unwrap());
+}
+
+/// Tag the places of each dated event with `PlaceRef::resolved_id`.
+fn resolve_place_ids(events: &mut [Event]) {
+    for e in events {
+        let Some(t) = &e.time else { continue };
+        let Some(year) = exact_ad_year(&t.regime, &t.era, t.year) else {
+            continue;
+        };
+        let place = match &mut e.kind {
+            EventKind::Appointment { place, .. } => place.as_mut(),
+            EventKind::Battle { target_place, .. } => target_place.as_mut(),
+            EventKind::Pacification { place, .. } => Some(place),
+            EventKind::Omen { place, .. } | EventKind::Rebellion { place, .. } => place.as_mut(),
+            _ => None,
+        };
+        for p in place.into_iter().chain(&mut e.locations) {
+            p.resolved_id =
+                crate::gazetteer::resolve_prefecture(&p.name, &t.regime, year).map(str::to_string);
+        }
+    }
+}
+
+/// Return the index of an era name within ERA_NAMES for a given regime.
+/// Used to sort eras chronologically within a regime.
+pub fn era_sort_key(regime_chinese: &str, era_name: &str) -> usize {
+    regime::ERA_NAMES
+        .iter()
+        .enumerate()
+        .find(|(_, e)| e.name == era_name && e.regime.as_chinese() == regime_chinese)
+        .map(|(i, _)| i)
+        .unwrap_or(usize::MAX)
+}
+
+/// Compute the exact AD year for a time reference.
+///
+/// Uses the per-era `start_ad` from `ERA_NAMES` (scraped from Wikipedia)
+/// to give a precise result: `start_ad + (year - 1)`. None for era-only
+/// references (year 0).
+pub fn exact_ad_year(regime_chinese: &str, era_name: &str, year: u8) -> Option<u16> {
+    let entry = regime::ERA_NAMES
+        .iter()
+        .find(|e| e.regime.as_chinese() == regime_chinese && e.name == era_name)?;
+    Some(entry.start_ad + (year as u16).checked_sub(1)?)
+}
+
+/// 列傳 subjects by biography file, for `JoinedText::attribute_given_names`.
+fn collect_bio_subjects(persons: &[Person]) -> HashMap<String, BioSubject> {
+    persons
+        .iter()
+        .filter(|p| p.source.section == Section::LieZhuan)
+        .filter_map(|p| match &p.kind {
+            PersonKind::Official {
+                surname,
+                given_name,
+                ..
+            } if (1..=2).contains(&given_name.chars().count()) => {
+                // A later name is aliased both bare and with the surname
+                // (see `parser::add_rename_aliases`); a courtesy name is not
+                let renamed = p
+                    .aliases
+                    .iter()
+                    .filter_map(|a| a.strip_prefix(surname.as_str()))
+                    .filter(|g| {
+                        *g != given_name
+                            && (1..=2).contains(&g.chars().count())
+                            && p.aliases.iter().any(|a| a == g)
+                    })
+                    .map(str::to_string)
+                    .collect();
+                Some((
+                    p.source.file_path.display().to_string(),
+                    BioSubject {
+                        surname: surname.clone(),
+                        given: given_name.clone(),
+                        renamed,
+                    },
+                ))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+fn collect_extra_surnames(persons: &[Person]) -> Vec<String> {
+    let mut surnames = std::collections::HashSet::new();
+    for p in persons {
+        match &p.kind {
+            PersonKind::Official { surname, .. } | PersonKind::Ruler { surname, .. } => {
+                surnames.insert(surname.clone());
+            }
+            PersonKind::Emperor {
+                surname: Some(s), ..
+            } => {
+                surnames.insert(s.clone());
+            }
+            _ => {}
+        }
+    }
+    surnames.into_iter().collect()
+}
+
+/// Check whether a string looks like a plausible administrative place name.
+/// Used to filter context-extracted place names (before 刺史/太守/內史).
+/// In Six Dynasties texts, administrative place names are 2-3 characters
+/// (e.g., 荊州, 揚州, 南兗州). Longer matches from context windows are
+/// almost always junk-prefixed (e.g., "攻暐洛州" where only "洛州" is real).
+fn is_plausible_place(name: &str) -> bool {
+    let char_count = name.chars().count();
+    // Reject very short or very long names
+    if !(2..=3).contains(&char_count) {
+        return false;
+    }
+    // Reject bracket/annotation artifacts
+    if name.contains('[') || name.contains(']') {
+        return false;
+    }
+    // Reject names starting with characters that cannot begin a place name
+    let first = name.chars().next().unwrap();
+    let bad_starts: &[char] = &[
+        '殺', '攻', '伐', '克', '陷', '討', '破', '逐', '執', // military verbs
+        '使', '令', '遣', '命', '除', '拜', '遷', '轉', '授', // appointment verbs
+        '乃', '又', '則', '其', '先', '亦', '再', '俄', '仍', // adverbs/connectives
+        '兄', '弟', '父', '母', '叔', // kinship terms
+        '偽', '僞', '故', '舊', '前', '後', '害', '盜', // modifiers/verbs
+        '加', '領', '兼', '行', '代', '署', '出', '入', '功', // official action words
+        '是', '走', '率', '擒', '獲', '斬', '在', '及', // misc verbs
+        '與', '隨', '自', '累', '左', '右', '號', '詔', '贈', // misc
+        '遙', '重', '衆', '勒', '從', '結', '更', '如', '乘', // misc
+        '時', '方', '永', '爲', '歷', '曆', '瑗', '苗', '宋', // temporal/surnames/misc
+    ];
+    !bad_starts.contains(&first)
+}
+
+fn extract_context(text: &str, byte_offset: usize, char_radius: usize) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut byte_pos = 0;
+    let mut char_idx = chars.len();
+    for (i, ch) in chars.iter().enumerate() {
+        if byte_pos >= byte_offset {
+            char_idx = i;
+            break;
+        }
+        byte_pos += ch.len_utf8();
+    }
+
+    let start = char_idx.saturating_sub(char_radius);
+    let end = (char_idx + char_radius).min(chars.len());
+
+    // Keep only the line the match is on
+    let start = chars[start..char_idx]
+        .iter()
+        .rposition(|&c| c == '\n')
+        .map_or(start, |i| start + i + 1);
+    let end = chars[char_idx..end]
+        .iter()
+        .position(|&c| c == '\n' || c == '\r')
+        .map_or(end, |i| char_idx + i);
+    chars[start..end].iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture;
+
+    #[test]
+    fn test_era_coverage_ratio() {
+        let era = regime::EraEntry {
+            name: "元嘉",
+            regime: regime::Regime::LiuSong,
+            start_ad: 424,
+            end_ad: 433,
+        };
+        let timeline = |years: &[u8]| EraTimeline {
+            era: "元嘉".into(),
+            years: years
+                .iter()
+                .map(|&year| TimePoint {
+                    era: "元嘉".into(),
+                    year,
+                    occurrence_count: 1,
+                    files: vec!["t".into()],
+                })
+                .collect(),
+            coverage: None,
+        };
+        assert_eq!(timeline(&[1, 3, 5, 7, 9]).coverage_ratio(&era), 0.5);
+        let overfull: Vec<u8> = (1..=12).collect();
+        assert_eq!(timeline(&overfull).coverage_ratio(&era), 1.0);
+    }
+
+    #[test]
+    fn test_detect_qiao() {
+        assert!(detect_qiao("南徐州", "僑立南徐州"));
+        assert!(!detect_qiao("徐州", "以為徐州刺史"));
+        // a 南 commandery, not a province: only the marker gives it away
+        assert!(detect_qiao("南琅邪郡", "僑立南琅邪郡於江乘"));
+        assert!(detect_qiao("琅邪郡", "於江乘僑置琅邪郡"));
+        assert!(!detect_qiao("琅邪郡", "僑置徐州，後數年乃以琅邪郡屬之"));
+        assert!(detect_qiao("南沛僑郡", ""));
+    }
+
+    #[test]
+    fn test_exact_ad_year_liu_song() {
+        // 元嘉 started in AD 424
+        assert_eq!(exact_ad_year("劉宋", "元嘉", 1), Some(424));
+        assert_eq!(exact_ad_year("劉宋", "元嘉", 30), Some(453));
+    }
+
+    #[test]
+    fn test_exact_ad_year_northern_wei() {
+        // 太和 started in AD 477
+        assert_eq!(exact_ad_year("北魏", "太和", 1), Some(477));
+        assert_eq!(exact_ad_year("北魏", "太和", 23), Some(499));
+    }
+
+    #[test]
+    fn test_exact_ad_year_cross_regime_ordering() {
+        // 劉宋/元嘉 (AD 424) should be BEFORE 北魏/太和 (AD 477)
+        let song = exact_ad_year("劉宋", "元嘉", 1).unwrap();
+        let wei = exact_ad_year("北魏", "太和", 1).unwrap();
+        assert!(
+            song < wei,
+            "劉宋/元嘉({song}) should be before 北魏/太和({wei})"
+        );
+    }
+
+    #[test]
+    fn test_exact_ad_year_dynasty_succession() {
+        // 義熙14年 = AD 418, 元嘉1年 = AD 424 — gap of 6 years (劉宋永初 in between)
+        let yixi = exact_ad_year("東晉", "義熙", 14).unwrap();
+        let yuanjia = exact_ad_year("劉宋", "元嘉", 1).unwrap();
+        assert_eq!(yixi, 418);
+        assert_eq!(yuanjia, 424);
+        let gap = yuanjia.abs_diff(yixi);
+        assert!(gap <= 10, "義熙14→元嘉1 gap should be small, got {gap}");
+    }
+
+    fn time_ref(regime: &str, era: &str, year: u8, month: Option<u8>) -> TimeRef {
+        TimeRef {
+            era: era.into(),
+            regime: regime.into(),
+            year,
+            month,
+            is_leap: false,
+            day_ganzhi: None,
+            day_num: None,
+            day: None,
+            year_ganzhi: None,
+            suspect: false,
+            precision: TimePrecision::Year,
+            origin: TimeOrigin::Era,
+            raw: String::new(),
+            byte_offset: 0,
+        }
+    }
+
+    #[test]
+    fn test_event_scope_contains_offset() {
+        let scanner = EventScanner::new(&[]);
+        let text = "元嘉三年，王進卒。\n元嘉五年，以張華為侍中，\n遷中書令。";
+        let (events, scopes) = scanner.scan_file(text, Book::SongShu, Section::LieZhuan, "t");
+        assert!(!events.is_empty());
+        for e in &events {
+            let scope = &scopes[e.scope_index.expect("scoped event")];
+            let span = &scope.span;
+            assert!(span.byte_start <= e.byte_offset && e.byte_offset < span.byte_end);
+            assert_eq!(scope.time.year, e.time.as_ref().unwrap().year);
+        }
+    }
+
+    #[test]
+    fn test_file_eras_year_range() {
+        let scanner = EventScanner::new(&[]);
+        let text = "元嘉五年，王進卒。泰始二年，張華遷侍中。元嘉三年，張華為王進所殺。";
+        let (_, scopes) = scanner.scan_file(text, Book::SongShu, Section::LieZhuan, "a.txt");
+        let index = TimeIndex {
+            scopes,
+            era_refs: Vec::new(),
+        };
+        let eras = &index.file_eras()["a.txt"];
+        assert_eq!(eras.len(), 2);
+        assert_eq!(
+            (eras[0].era.as_str(), eras[0].year_from, eras[0].year_to),
+            ("元嘉", 3, 5)
+        );
+        assert_eq!(eras[1].era, "泰始");
+    }
+
+    #[test]
+    fn test_iso_approx_year_only() {
+        // 南齊 建元 starts 479
+        let t = time_ref("南齊", "建元", 1, None);
+        assert_eq!(t.to_iso_approx().as_deref(), Some("0479"));
+    }
+
+    #[test]
+    fn test_iso_approx_with_month() {
+        // 劉宋 元徽五年 = 477
+        let t = time_ref("劉宋", "元徽", 5, Some(5));
+        assert_eq!(t.to_iso_approx().as_deref(), Some("0477-05"));
+        assert_eq!(time_ref("劉宋", "不存", 1, None).to_iso_approx(), None);
+    }
+
+    #[test]
+    fn test_scan_time_leap_month() {
+        let scanner = EventScanner::new(&[]);
+        let (_, scopes) =
+            scanner.scan_file("元嘉三年閏五月甲子，", Book::SongShu, Section::BenJi, "t");
+        let t = &scopes[0].time;
+        assert_eq!((t.month, t.is_leap), (Some(5), true));
+    }
+
+    #[test]
+    fn test_scan_time_numbered_day() {
+        let scanner = EventScanner::new(&[]);
+        let day = |text: &str| {
+            let (_, scopes) = scanner.scan_file(text, Book::SongShu, Section::BenJi, "t");
+            let t = scopes[0].time.clone();
+            (t.month, t.day_ganzhi, t.day_num)
+        };
+        assert_eq!(day("元嘉三年五月十五日，"), (Some(5), None, Some(15)));
+        assert_eq!(
+            day("元嘉三年五月丁巳，"),
+            (Some(5), Some("丁巳".to_string()), None)
+        );
+        assert_eq!(day("元嘉三年五月，"), (Some(5), None, None));
+        assert_eq!(day("元嘉三年五月四十日，"), (Some(5), None, None));
+    }
+
+    #[test]
+    fn test_scan_time_precision() {
+        let scanner = EventScanner::new(&[]);
+        let (_, scopes) = scanner.scan_file(
+            "元嘉三年五月，王進卒。元嘉五年，張華卒。",
+            Book::SongShu,
+            Section::BenJi,
+            "t",
+        );
+        let precisions: Vec<TimePrecision> = scopes.iter().map(|s| s.time.precision).collect();
+        assert_eq!(precisions, [TimePrecision::Exact, TimePrecision::Year]);
+        assert!(TimePrecision::Era < TimePrecision::Year);
+        assert!(TimePrecision::Relative < TimePrecision::Era);
+    }
+
+    #[test]
+    fn test_scan_time_keeps_longest_era() {
+        let scanner = EventScanner::new(&[]);
+        let (_, scopes) = scanner.scan_file(
+            "中大通三年，大通二年。太平真君五年。",
+            Book::LiangShu,
+            Section::LieZhuan,
+            "t",
+        );
+        let eras: Vec<(&str, u8)> = scopes
+            .iter()
+            .map(|s| (s.time.era.as_str(), s.time.year))
+            .collect();
+        assert_eq!(eras, vec![("中大通", 3), ("大通", 2), ("太平真君", 5)]);
+        assert_eq!(scopes[2].time.regime, "北魏");
+    }
+
+    #[test]
+    fn test_exact_ad_year_unknown_era() {
+        assert!(exact_ad_year("劉宋", "不存在", 1).is_none());
+    }
+
+    #[test]
+    fn test_exact_ad_year_unknown_regime() {
+        assert!(exact_ad_year("不存在", "元嘉", 1).is_none());
+    }
+
+    fn find_death(events: &[Event]) -> (&str, DeathManner, Option<&str>) {
+        events
+            .iter()
+            .find_map(|e| match &e.kind {
+                EventKind::Death {
+                    person,
+                    manner,
+                    killer,
+                    ..
+                } => Some((person.as_str(), *manner, killer.as_deref())),
+                _ => None,
+            })
+            .expect("death event")
+    }
+
+    #[test]
+    fn test_scan_violent_death_with_killer() {
+        let scanner = EventScanner::new(&[]);
+        let (events, _) = scanner.scan_file(
+            "元嘉三年，張華為王進所殺。",
+            Book::SongShu,
+            Section::LieZhuan,
+            "t",
+        );
+        let (person, manner, killer) = find_death(&events);
+        assert_eq!(person, "張華");
+        assert_eq!(manner, DeathManner::Killed);
+        assert_eq!(killer, Some("王進"));
+    }
+
+    #[test]
+    fn test_scan_death_with_posthumous_honours() {
+        let scan = |text: &str| {
+            scan_kind(text, Book::SongShu, Section::LieZhuan, |e| match e.kind {
+                EventKind::Death {
+                    person,
+                    posthumous_title,
+                    posthumous_name,
+                    ..
+                } => Some((person, posthumous_title, posthumous_name)),
+                _ => None,
+            })
+        };
+        assert_eq!(
+            scan("元嘉三年，王進薨，贈司空，謚曰忠。"),
+            [("王進".into(), Some("司空".into()), Some("忠".into()))]
+        );
+        // a grant in the next sentence is not the death notice's
+        assert_eq!(
+            scan("元嘉三年，王進薨。明年，贈司空。"),
+            [("王進".into(), None, None)]
+        );
+        // the honour is on the death alone, not a second PosthumousGrant
+        let (events, _) = EventScanner::new(&[]).scan_file(
+            "王進薨，贈司空，謚曰忠。",
+            Book::SongShu,
+            Section::LieZhuan,
+            "t",
+        );
+        assert!(
+            !events
+                .iter()
+                .any(|e| matches!(e.kind, EventKind::PosthumousGrant { .. })),
+            "{events:?}"
+        );
+    }
+
+    #[test]
+    fn test_year_past_era_end_is_suspect() {
+        let scanner = EventScanner::new(&[]);
+        let (events, scopes) = scanner.scan_file(
+            "元嘉三十年，王進卒。元嘉四十三年，張華卒。",
+            Book::SongShu,
+            Section::LieZhuan,
+            "t",
+        );
+        let times: Vec<(u8, bool)> = events
+            .iter()
+            .filter_map(|e| e.time.as_ref())
+            .map(|t| (t.year, t.suspect))
+            .collect();
+        assert_eq!(times, [(30, false), (43, true)]);
+        assert_eq!(scopes.iter().filter(|s| s.time.suspect).count(), 1);
+    }
+
+    #[test]
+    fn test_scan_death_age() {
+        let scan = |text: &str| {
+            scan_kind(text, Book::SongShu, Section::LieZhuan, |e| match e.kind {
+                EventKind::Death { age_at_death, .. } => Some(age_at_death),
+                _ => None,
+            })
+        };
+        assert_eq!(scan("元嘉三年，王進卒，時年七十二。"), [Some(72)]);
+        assert_eq!(scan("王進薨於位，春秋六十，贈司空。"), [Some(60)]);
+        assert_eq!(scan("王進卒，時年一百二十。"), [Some(120)]);
+        // an age in the next sentence belongs to something else
+        assert_eq!(scan("王進卒。時年十五，張華舉秀才。"), [None]);
+    }
+
+    #[test]
+    fn test_scan_gaifeng_marks_reassignment() {
+        let scanner = EventScanner::new(&[]);
+        let (events, _) = scanner.scan_file(
+            "永明二年，改封武昌王。",
+            Book::NanQiShu,
+            Section::LieZhuan,
+            "t",
+        );
+        let e = events
+            .iter()
+            .find(|e| matches!(e.kind, EventKind::Enfeoffment { .. }))
+            .expect("enfeoffment event");
+        match &e.kind {
+            EventKind::Enfeoffment {
+                verb,
+                fief,
+                rank,
+                is_reassignment,
+                ..
+            } => {
+                assert_eq!(verb, "改封");
+                assert_eq!(fief, "武昌");
+                assert_eq!(rank, "王");
+                assert!(is_reassignment);
+            }
+            _ => unreachable!(),
+        }
+        assert!(e.time.is_some());
+    }
+
+    #[test]
+    fn test_scan_strips_name_prefix() {
+        let scanner = EventScanner::new(&[]);
+        let scan = |text: &str| {
+            let (events, _) = scanner.scan_file(text, Book::NanQiShu, Section::LieZhuan, "t");
+            events
+        };
+        let persons = |text: &str| {
+            scan(text)
+                .iter()
+                .map(|e| (e.kind.name(), e.person_name().map(str::to_string)))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            persons("公褚淵封南康郡公。"),
+            [("Enfeoffment", Some("褚淵".into()))]
+        );
+        assert_eq!(
+            persons("弟王進自稱帝，改元建興。"),
+            [("UsurpationEra", Some("王進".into()))]
+        );
+        assert_eq!(
+            persons("弟王進徙其民三千家於建康。"),
+            [("PopulationMove", Some("王進".into()))]
+        );
+        assert_eq!(find_dispatch(&scan("弟張華遣王進攻建康。")).0, Some("張華"));
+    }
+
+    #[test]
+    fn test_split_fief_county_rank() {
+        assert_eq!(split_fief("武昌縣侯"), Some(("武昌".into(), "縣侯".into())));
+        assert_eq!(split_fief("王"), None);
+    }
+
+    #[test]
+    fn test_scan_appointment_across_line_break() {
+        let scanner = EventScanner::new(&[]);
+        let source = "元嘉三年，文帝以王進為\n冠軍將軍、荊州刺史。";
+        let (events, scopes) = scanner.scan_file(source, Book::SongShu, Section::LieZhuan, "t");
+        let e = events
+            .iter()
+            .find(|e| matches!(e.kind, EventKind::Appointment { .. }))
+            .expect("appointment across line break");
+        match &e.kind {
+            EventKind::Appointment { new_title, .. } => {
+                assert_eq!(new_title, "冠軍將軍、荊州刺史")
+            }
+            _ => unreachable!(),
+        }
+        assert!(e.context.contains("荊州刺史"), "{}", e.context);
+        assert!(source[e.byte_offset..].starts_with("以王進"));
+        assert_eq!(scopes[0].span.byte_end, source.len());
+    }
+
+    fn find_usurpation(events: &[Event]) -> (&str, bool) {
+        events
+            .iter()
+            .find_map(|e| match &e.kind {
+                EventKind::UsurpationEra { era, known_era, .. } => Some((era.as_str(), *known_era)),
+                _ => None,
+            })
+            .expect("usurpation event")
+    }
+
+    #[test]
+    fn test_scan_usurpation_known_era() {
+        let scanner = EventScanner::new(&[]);
+        let (events, _) =
+            scanner.scan_file("勃勃僭號，改元龍昇。", Book::JinShu, Section::LieZhuan, "t");
+        assert_eq!(find_usurpation(&events), ("龍昇", true));
+    }
+
+    #[test]
+    fn test_scan_usurpation_unknown_era_flagged() {
+        let scanner = EventScanner::new(&[]);
+        let (events, _) = scanner.scan_file(
+            "王進自稱帝，改元天德。",
+            Book::SongShu,
+            Section::LieZhuan,
+            "t",
+        );
+        assert_eq!(find_usurpation(&events), ("天德", false));
+        assert_eq!(events[0].person_name(), Some("王進"));
+    }
+
+    /// Scan `text` as one file of `book`/`section`, keeping what `pick`
+    /// makes of each event; `pick` returns None for the kinds a test is
+    /// not about.
+    fn scan_kind<T>(
+        text: &str,
+        book: Book,
+        section: Section,
+        pick: impl FnMut(Event) -> Option<T>,
+    ) -> Vec<T> {
+        let (events, _) = EventScanner::new(&[]).scan_file(text, book, section, "t");
+        events.into_iter().filter_map(pick).collect()
+    }
+
+    fn appointments(events: &[Event]) -> Vec<(&str, &str)> {
+        events
+            .iter()
+            .filter_map(|e| match &e.kind {
+                EventKind::Appointment {
+                    person, new_title, ..
+                } => Some((person.as_str(), new_title.as_str())),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_subject_omitted_promotion_attributed_in_annals() {
+        let scanner = EventScanner::new(&[]);
+        let text = "義熙三年，遷侍中、車騎將軍。";
+        let file = "宋書/00_本紀/00_本紀第一　武帝上/02_武帝上.txt";
+
+        let (events, _) = scanner.scan_file(text, Book::SongShu, Section::BenJi, file);
+        assert_eq!(appointments(&events), vec![("武帝", "侍中、車騎將軍")]);
+
+        let (events, _) = scanner.scan_file(text, Book::SongShu, Section::LieZhuan, file);
+        assert!(appointments(&events).is_empty());
+    }
+
+    #[test]
+    fn test_scan_resolves_real_and_qiao_xuzhou() {
+        let (events, _) = EventScanner::new(&[]).scan_file(
+            "元嘉七年，以王進為徐州刺史。以張華為南徐州刺史。",
+            Book::SongShu,
+            Section::LieZhuan,
+            "t",
+        );
+        let ids: Vec<_> = events
+            .iter()
+            .filter_map(|e| match &e.kind {
+                EventKind::Appointment { place: Some(p), .. } => {
+                    Some((p.name.as_str(), p.resolved_id.as_deref()))
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            ids,
+            [("徐州", Some("徐州/彭城")), ("南徐州", Some("南徐州/京口"))]
+        );
+    }
+
+    #[test]
+    fn test_given_name_attributed_in_own_biography() {
+        let file = fixture::CHU_YUAN_FILE;
+        let chu_yuan = fixture::chu_yuan(&[]);
+        let scanner = EventScanner::new(&[chu_yuan]);
+        let text = "褚淵字彥回，河南陽翟人也。\n元徽二年，淵遷尚書令。";
+
+        let (events, _) = scanner.scan_file(text, Book::NanQiShu, Section::LieZhuan, file);
+        assert_eq!(appointments(&events), vec![("褚淵", "尚書令")]);
+        // offsets still point into the original text, at the given name,
+        // and the context is the text as written
+        assert_eq!(&text[events[0].byte_offset..][..3], "淵");
+        assert!(events[0].context.ends_with("元徽二年，淵遷尚書令。"));
+        assert_eq!(events[0].person_id.as_deref(), Some(file));
+
+        // another file's bare 淵 is not 褚淵
+        let (events, _) = scanner.scan_file(text, Book::NanQiShu, Section::LieZhuan, "t");
+        assert!(appointments(&events).is_empty());
+        // and a full name outside the biography is not tied to it either
+        let (events, _) =
+            scanner.scan_file("褚淵遷尚書令。", Book::NanQiShu, Section::LieZhuan, "t");
+        assert_eq!(appointments(&events), vec![("褚淵", "尚書令")]);
+        assert_eq!(events[0].person_id, None);
+    }
+
+    #[test]
+    fn test_subject_omitted_attributed_to_biography_owner() {
+        let file = fixture::CHU_YUAN_FILE;
+        let chu_yuan = fixture::chu_yuan(&[]);
+        let scanner = EventScanner::new(&[chu_yuan]);
+        let text = "褚淵字彥回，河南陽翟人也。\n元徽二年，遷尚書令。又除吳郡太守。";
+
+        let (events, _) = scanner.scan_file(text, Book::NanQiShu, Section::LieZhuan, file);
+        assert_eq!(
+            appointments(&events),
+            vec![("褚淵", "尚書令"), ("褚淵", "吳郡太守")]
+        );
+        assert!(events.iter().all(|e| e.person_id.as_deref() == Some(file)));
+
+        // a named appointment ends at the clause break; the 尋遷 after it
+        // has no subject of its own
+        let text = "褚淵字彥回。以王進為侍中；尋遷吳郡太守。";
+        let (events, _) = scanner.scan_file(text, Book::NanQiShu, Section::LieZhuan, file);
+        assert_eq!(
+            appointments(&events),
+            vec![("王進", "侍中"), ("褚淵", "吳郡太守")]
+        );
+    }
+
+    #[test]
+    fn test_renamed_subject_events_merge_under_one_name() {
+        let file = fixture::CHU_YUAN_FILE;
+        // aliases as `parser::add_rename_aliases` leaves them after 改名顯
+        let chu_yuan = fixture::chu_yuan(&["褚淵", "淵", "彥回", "褚顯", "顯"]);
+        let scanner = EventScanner::new(&[chu_yuan]);
+        let text = "褚淵字彥回，河南陽翟人也。\n淵遷侍中，後改名顯。顯遷尚書令。褚顯卒。";
+
+        let (events, _) = scanner.scan_file(text, Book::NanQiShu, Section::LieZhuan, file);
+        assert_eq!(
+            appointments(&events),
+            vec![("褚淵", "侍中"), ("褚淵", "尚書令")]
+        );
+        assert!(
+            events
+                .iter()
+                .all(|e| e.person_name() == Some("褚淵") && e.person_id.as_deref() == Some(file)),
+            "{events:?}"
+        );
+        assert!(
+            events
+                .iter()
+                .any(|e| matches!(e.kind, EventKind::Death { .. }))
+        );
+    }
+
+    fn find_dispatch(events: &[Event]) -> (Option<&str>, &str, &str) {
+        events
+            .iter()
+            .find_map(|e| match &e.kind {
+                EventKind::Dispatch {
+                    dispatcher,
+                    agent,
+                    purpose,
+                    ..
+                } => Some((dispatcher.as_deref(), agent.as_str(), purpose.as_str())),
+                _ => None,
+            })
+            .expect("dispatch event")
+    }
+
+    #[test]
+    fn test_scan_dispatch_attack() {
+        let scanner = EventScanner::new(&[]);
+        let (events, _) =
+            scanner.scan_file("張華遣王進攻建康。", Book::SongShu, Section::LieZhuan, "t");
+        assert_eq!(find_dispatch(&events), (Some("張華"), "王進", "攻建康"));
+    }
+
+    #[test]
+    fn test_scan_dispatch_envoy() {
+        let scanner = EventScanner::new(&[]);
+        let (events, _) =
+            scanner.scan_file("三年，遣張華使魏。", Book::SongShu, Section::LieZhuan, "t");
+        assert_eq!(find_dispatch(&events), (None, "張華", "使魏"));
+    }
+
+    fn find_tenure_end(events: &[Event]) -> (Option<&str>, &str, &str, Option<&str>) {
+        events
+            .iter()
+            .find_map(|e| match &e.kind {
+                EventKind::TenureEnd {
+                    person,
+                    verb,
+                    office_or_place,
+                    successor,
+                } => Some((
+                    person.as_deref(),
+                    verb.as_str(),
+                    office_or_place.as_str(),
+                    successor.as_deref(),
+                )),
+                _ => None,
+            })
+            .expect("tenure end event")
+    }
+
+    #[test]
+    fn test_scan_tenure_end_dismissal() {
+        let scanner = EventScanner::new(&[]);
+        let (events, _) = scanner.scan_file(
+            "十年，罷荊州，還為侍中。",
+            Book::SongShu,
+            Section::LieZhuan,
+            "t",
+        );
+        assert_eq!(find_tenure_end(&events), (None, "罷", "荊州", None));
+    }
+
+    #[test]
+    fn test_scan_tenure_end_replacement() {
+        let scanner = EventScanner::new(&[]);
+        let (events, _) =
+            scanner.scan_file("張華代王進為刺史。", Book::SongShu, Section::LieZhuan, "t");
+        assert_eq!(
+            find_tenure_end(&events),
+            (Some("王進"), "代", "刺史", Some("張華"))
+        );
+    }
+
+    #[test]
+    fn test_scan_tenure_end_recall_uses_last_post() {
+        let scanner = EventScanner::new(&[]);
+        let (events, _) = scanner.scan_file(
+            "以王進為荊州刺史。三年，王進徵還。",
+            Book::SongShu,
+            Section::LieZhuan,
+            "t",
+        );
+        assert_eq!(
+            find_tenure_end(&events),
+            (Some("王進"), "徵還", "荊州", None)
+        );
+    }
+
+    fn find_decline(events: &[Event]) -> (Option<&str>, &str, Option<&str>) {
+        events
+            .iter()
+            .find_map(|e| match &e.kind {
+                EventKind::Decline {
+                    person,
+                    verb,
+                    office,
+                } => Some((person.as_deref(), verb.as_str(), office.as_deref())),
+                _ => None,
+            })
+            .expect("decline event")
+    }
+
+    #[test]
+    fn test_scan_decline_takes_office_from_appointment() {
+        let scanner = EventScanner::new(&[]);
+        let (events, _) = scanner.scan_file(
+            "以王進為侍中，固辭不拜。",
+            Book::SongShu,
+            Section::LieZhuan,
+            "t",
+        );
+        assert_eq!(
+            find_decline(&events),
+            (Some("王進"), "固辭不拜", Some("侍中"))
+        );
+    }
+
+    #[test]
+    fn test_scan_decline_named_office() {
+        let scanner = EventScanner::new(&[]);
+        let (events, _) =
+            scanner.scan_file("王進讓侍中還第。", Book::SongShu, Section::LieZhuan, "t");
+        assert_eq!(find_decline(&events), (Some("王進"), "讓", Some("侍中")));
+    }
+
+    fn find_investiture(events: &[Event]) -> (&str, &str, Option<&str>) {
+        events
+            .iter()
+            .find_map(|e| match &e.kind {
+                EventKind::Investiture { person, role, by } => {
+                    Some((person.as_str(), role.as_str(), by.as_deref()))
+                }
+                _ => None,
+            })
+            .expect("investiture event")
+    }
+
+    #[test]
+    fn test_scan_investiture_named() {
+        let scanner = EventScanner::new(&[]);
+        let (events, _) =
+            scanner.scan_file("張華立王進為太子。", Book::SongShu, Section::LieZhuan, "t");
+        assert_eq!(find_investiture(&events), ("王進", "太子", Some("張華")));
+    }
+
+    #[test]
+    fn test_scan_investiture_kin_term() {
+        let scanner = EventScanner::new(&[]);
+        let (events, _) =
+            scanner.scan_file("王進乃立子為帝。", Book::SongShu, Section::LieZhuan, "t");
+        assert_eq!(find_investiture(&events), ("子", "帝", Some("王進")));
+    }
+
+    #[test]
+    fn test_scan_clan_references() {
+        let scanner = EventScanner::new(&[]);
+        let scan = |text: &str| {
+            let (events, _) = scanner.scan_file(text, Book::SongShu, Section::LieZhuan, "t");
+            events
+        };
+        // the clan is not a person
+        assert!(scan("王氏卒。").is_empty());
+        assert!(scan("以褚氏為侍中。").is_empty());
+        // but an empress is named by her clan
+        assert_eq!(
+            find_investiture(&scan("張華立妃王氏為皇后。")),
+            ("王氏", "皇后", Some("張華"))
+        );
+    }
+
+    #[test]
+    fn test_scan_conjoined_appointees() {
+        let scanner = EventScanner::new(&[]);
+        let scan = |text: &str| {
+            let (events, _) = scanner.scan_file(text, Book::SongShu, Section::LieZhuan, "t");
+            events
+        };
+        assert_eq!(
+            appointments(&scan("以王進及張華為侍中。")),
+            vec![("王進", "侍中"), ("張華", "侍中")]
+        );
+        assert_eq!(
+            appointments(&scan("以王進、張華與劉毅並為侍中。")),
+            vec![("王進", "侍中"), ("張華", "侍中"), ("劉毅", "侍中")]
+        );
+        // a name ending in 俱 keeps it, whether or not 並 follows
+        assert_eq!(
+            appointments(&scan("以張俱為侍中。")),
+            vec![("張俱", "侍中")]
+        );
+        assert_eq!(
+            appointments(&scan("以王進及張俱並為侍中。")),
+            vec![("王進", "侍中"), ("張俱", "侍中")]
+        );
+        // 與 joining battle allies is not an appointment
+        assert!(appointments(&scan("王進與張華攻建康。")).is_empty());
+    }
+
+    #[test]
+    fn test_scan_battle_person_target() {
+        let scanner = EventScanner::new(&[]);
+        let battle = |text: &str| {
+            let (events, _) = scanner.scan_file(text, Book::SongShu, Section::LieZhuan, "t");
+            events
+                .into_iter()
+                .find(|e| matches!(e.kind, EventKind::Battle { .. }))
+                .expect("battle")
+        };
+        let e = battle("張華攻王進，大破之。");
+        assert!(
+            matches!(&e.kind, EventKind::Battle { target_person: Some(p), target_place: None, .. } if p == "王進")
+        );
+        assert_eq!(e.participants(), ["張華", "王進"]);
+
+        let e = battle("張華攻壽陽城，拔之。");
+        assert!(matches!(
+            &e.kind,
+            EventKind::Battle {
+                target_person: None,
+                target_place: Some(_),
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_scan_battle_collective_target() {
+        let scanner = EventScanner::new(&[]);
+        let (events, _) =
+            scanner.scan_file("王進攻江南諸郡。", Book::SongShu, Section::LieZhuan, "t");
+        let place = events
+            .iter()
+            .find_map(|e| match &e.kind {
+                EventKind::Battle { target_place, .. } => target_place.clone(),
+                _ => None,
+            })
+            .expect("battle target place");
+        assert!(place.collective);
+        assert_eq!(place.region.as_deref(), Some("江南"));
+
+        assert_eq!(collective_region("三郡"), Some(None));
+        assert_eq!(collective_region("壽陽城"), None);
+    }
+
+    fn find_pacification(events: &[Event]) -> Option<(Option<&str>, &str)> {
+        events.iter().find_map(|e| match &e.kind {
+            EventKind::Pacification { person, place, .. } => {
+                Some((person.as_deref(), place.name.as_str()))
+            }
+            _ => None,
+        })
+    }
+
+    fn find_posthumous(events: &[Event]) -> Vec<(Option<&str>, &str, bool)> {
+        events
+            .iter()
+            .filter_map(|e| match &e.kind {
+                EventKind::PosthumousGrant {
+                    person,
+                    title,
+                    retroactive,
+                    ..
+                } => Some((person.as_deref(), title.as_str(), *retroactive)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_scan_posthumous_grants() {
+        let scanner = EventScanner::new(&[]);
+        let scan = |text: &str| {
+            let (events, _) = scanner.scan_file(text, Book::SongShu, Section::LieZhuan, "t");
+            events
+        };
+        assert_eq!(
+            find_posthumous(&scan("王進卒。追贈司空。")),
+            [(Some("王進"), "司空", true)]
+        );
+        // in the death notice's own sentence the 贈 is the death's honour
+        assert!(find_posthumous(&scan("王進薨，贈侍中。")).is_empty());
+        // but a second grant later in that sentence is its own
+        assert_eq!(
+            find_posthumous(&scan("王進薨，贈侍中，又贈褚淵為司空。")),
+            [(Some("褚淵"), "司空", false)]
+        );
+        // 追封 is not also a living enfeoffment
+        let events = scan("追封王進為武昌縣侯。");
+        assert_eq!(find_posthumous(&events), [(Some("王進"), "武昌縣侯", true)]);
+        assert!(
+            !events
+                .iter()
+                .any(|e| matches!(e.kind, EventKind::Enfeoffment { .. }))
+        );
+        assert_eq!(
+            find_posthumous(&scan("追贈王進侍中。")),
+            [(Some("王進"), "侍中", true)]
+        );
+        // 贈 as "give"
+        assert!(find_posthumous(&scan("贈以錢十萬。")).is_empty());
+    }
+
+    fn find_abdication(events: &[Event]) -> Option<(Option<&str>, Option<&str>)> {
+        events.iter().find_map(|e| match &e.kind {
+            EventKind::Abdication { person, to, .. } => Some((person.as_deref(), to.as_deref())),
+            _ => None,
+        })
+    }
+
+    #[test]
+    fn test_scan_abdication() {
+        let scanner = EventScanner::new(&[]);
+        let scan = |text: &str, section| {
+            let (events, _) = scanner.scan_file(text, Book::SongShu, section, "t");
+            events
+        };
+        assert_eq!(
+            find_abdication(&scan("順帝禪位於齊王。", Section::LieZhuan)),
+            Some((Some("順帝"), Some("齊王")))
+        );
+        // 王 as a surname
+        assert_eq!(
+            find_abdication(&scan("順帝禪位於王進。", Section::LieZhuan)),
+            Some((Some("順帝"), Some("王進")))
+        );
+        assert_eq!(
+            find_abdication(&scan("恭帝遜位，居秣陵宮。", Section::LieZhuan)),
+            Some((Some("恭帝"), None))
+        );
+        // an official resigning, and the receiving side
+        assert_eq!(
+            find_abdication(&scan("以疾遜位。", Section::LieZhuan)),
+            None
+        );
+        assert_eq!(
+            find_abdication(&scan("魏受禪于漢。", Section::LieZhuan)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_scan_diplomacy() {
+        let scan = |text: &str| {
+            scan_kind(text, Book::WeiShu, Section::BenJi, |e| match e.kind {
+                EventKind::Diplomacy {
+                    sender,
+                    target,
+                    kind,
+                } => Some((sender, target, kind)),
+                _ => None,
+            })
+        };
+        assert_eq!(
+            scan("三月，高麗國遣使朝貢。"),
+            [(Some("高麗國".into()), None, "朝貢".into())]
+        );
+        assert_eq!(scan("太和三年，遣使朝貢。"), [(None, None, "朝貢".into())]);
+        assert_eq!(
+            scan("蕭衍遣使求救於魏。"),
+            [(Some("蕭衍".into()), Some("魏".into()), "求救".into())]
+        );
+        // A state is the sender, not a person
+        let persons = |text: &str| {
+            scan_kind(text, Book::WeiShu, Section::BenJi, |e| {
+                e.person_name().map(str::to_string)
+            })
+        };
+        assert!(persons("三月，高麗國遣使朝貢。").is_empty());
+        assert_eq!(persons("蕭衍遣使求救於魏。"), ["蕭衍"]);
+    }
+
+    #[test]
+    fn test_scan_omen() {
+        let scan = |text: &str| {
+            scan_kind(text, Book::WeiShu, Section::BenJi, |e| match e.kind {
+                EventKind::Omen { phenomenon, place } => Some((
+                    phenomenon,
+                    place.map(|p| p.name),
+                    e.time.map(|t| (t.era, t.year)),
+                )),
+                _ => None,
+            })
+        };
+        assert_eq!(
+            scan("太和三年春正月癸酉朔，日有蝕之。"),
+            [("日有蝕之".into(), None, Some(("太和".into(), 3)))]
+        );
+        assert_eq!(
+            scan("太和五年二月，雍州地震。"),
+            [("地震".into(), Some("雍州".into()), Some(("太和".into(), 5)))]
+        );
+        assert_eq!(
+            scan("太和六年，京師大水。"),
+            [("大水".into(), Some("京師".into()), Some(("太和".into(), 6)))]
+        );
+        assert_eq!(
+            scan("太和八年，京師旱。"),
+            [("旱".into(), Some("京師".into()), Some(("太和".into(), 8)))]
+        );
+        // 水旱 is a stock phrase, not a drought
+        assert!(scan("太和七年，詔曰：水旱不時。").is_empty());
+        // 新羅 titles, a rite for drought, daily rations
+        assert!(scan("其官有子賁旱支、齊旱支、謁旱支。").is_empty());
+        assert!(scan("爲旱而祭天地。").is_empty());
+        assert!(scan("日食一升飰。").is_empty());
+        assert!(scan("足三日食否。").is_empty());
+    }
+
+    #[test]
+    fn test_scan_rebellion() {
+        let scan = |text: &str| {
+            scan_kind(text, Book::SongShu, Section::LieZhuan, |e| match e.kind {
+                EventKind::Rebellion {
+                    person,
+                    verb,
+                    place,
+                } => Some((
+                    person,
+                    verb,
+                    place.map(|p| p.name),
+                    e.time.map(|t| (t.era, t.year)),
+                )),
+                _ => None,
+            })
+        };
+        assert_eq!(
+            scan("元嘉三年，王進據壽陽反。"),
+            [(
+                "王進".into(),
+                "據壽陽反".into(),
+                Some("壽陽".into()),
+                Some(("元嘉".into(), 3))
+            )]
+        );
+        assert_eq!(
+            scan("元嘉五年，張華舉兵反。"),
+            [(
+                "張華".into(),
+                "舉兵反".into(),
+                None,
+                Some(("元嘉".into(), 5))
+            )]
+        );
+        // 反覆 is fickleness, not revolt
+        assert!(scan("王進反覆無常。").is_empty());
+        // nor is 反 written for 返, "to return"
+        assert!(scan("王進反命，帝甚悅。").is_empty());
+        assert!(scan("使還，王進反國。").is_empty());
+    }
+
+    #[test]
+    fn test_scan_population_move() {
+        let scan = |text: &str| {
+            let (events, _) =
+                EventScanner::new(&[]).scan_file(text, Book::WeiShu, Section::LieZhuan, "t");
+            events
+        };
+        let events = scan("王進徙豪族三千戶於京師。");
+        let moves: Vec<_> = events
+            .iter()
+            .filter_map(|e| match &e.kind {
+                EventKind::PopulationMove {
+                    person,
+                    group,
+                    count,
+                    unit,
+                    to,
+                    ..
+                } => Some((
+                    person.as_deref(),
+                    group.as_deref(),
+                    *count,
+                    unit.as_deref(),
+                    to.as_deref(),
+                )),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            moves,
+            [(
+                Some("王進"),
+                Some("豪族"),
+                Some(3000),
+                Some("戶"),
+                Some("京師")
+            )]
+        );
+        assert!(
+            !events
+                .iter()
+                .any(|e| matches!(e.kind, EventKind::Appointment { .. })),
+            "{events:?}"
+        );
+
+        // an official transfer stays a promotion
+        let events = scan("王進遷尚書令。");
+        assert!(
+            events
+                .iter()
+                .all(|e| !matches!(e.kind, EventKind::PopulationMove { .. }))
+        );
+        assert!(events.iter().any(|e| matches!(&e.kind,
+            EventKind::Appointment { new_title, .. } if new_title == "尚書令")));
 
-fn collect_extra_surnames(persons: &[Person]) -> Vec<String> {
-    let mut surnames = std::collections::HashSet::new();
-    for p in persons {
-        match &p.kind {
-            PersonKind::Official { surname, .. } | PersonKind::Ruler { surname, .. } => {
-                surnames.insert(surname.clone());
-            }
-            PersonKind::Emperor {
-                surname: Some(s), ..
-            } => {
-                surnames.insert(s.clone());
-            }
-            _ => {}
+        assert_eq!(
+            population_move("其民萬餘家於代"),
+            Some((Some("其民"), Some((10_000, "家")), Some("代")))
+        );
+        assert_eq!(
+            population_move("冀州民以實京師"),
+            Some((Some("冀州民"), None, Some("京師")))
+        );
+        assert_eq!(
+            population_move("李祥等數千戶于敷陸"),
+            Some((Some("李祥等"), None, Some("敷陸")))
+        );
+        assert_eq!(population_move("配涼州為民"), None);
+        assert_eq!(population_move("居會稽"), None);
+    }
+
+    #[test]
+    fn test_scan_pacification_regions() {
+        let scanner = EventScanner::new(&[]);
+        let scan = |text: &str| {
+            let (events, _) = scanner.scan_file(text, Book::JinShu, Section::LieZhuan, "t");
+            events
+        };
+        assert_eq!(
+            find_pacification(&scan("王進平關中，還鎮。")),
+            Some((Some("王進"), "關中"))
+        );
+        assert_eq!(
+            find_pacification(&scan("三年，遂定河北。")),
+            Some((None, "河北"))
+        );
+        // 安定 is a commandery and 平城 a city, not 定/平 + place
+        assert_eq!(find_pacification(&scan("安定人也。")), None);
+        assert_eq!(find_pacification(&scan("還平城。")), None);
+        // place names in titles and county lists
+        assert_eq!(find_pacification(&scan("，平昌二郡太守。")), None);
+        assert_eq!(find_pacification(&scan("濟南、平原十縣。")), None);
+    }
+
+    #[test]
+    fn test_provenance_only_when_enabled() {
+        let text = "元嘉三年，文帝以王進為侍中。";
+        let scan = |scanner: EventScanner| {
+            let (events, _) = scanner.scan_file(text, Book::SongShu, Section::LieZhuan, "t");
+            events
+                .into_iter()
+                .find(|e| matches!(e.kind, EventKind::Appointment { .. }))
+                .expect("appointment")
+                .extracted_by
+        };
+        assert_eq!(scan(EventScanner::new(&[])), None);
+        assert_eq!(
+            scan(EventScanner::new(&[]).with_provenance(true)),
+            Some("re_appointment")
+        );
+    }
+
+    #[test]
+    fn test_appointment_gap_widens_title_prefix() {
+        let text = "以使持節散騎常侍都督揚州諸軍事領軍將軍王進為尚書令。";
+        let scan = |scanner: EventScanner| {
+            scanner
+                .scan_file(text, Book::SongShu, Section::LieZhuan, "t")
+                .0
+        };
+        // 18 chars of titles before the name: past the default gap
+        assert!(appointments(&scan(EventScanner::new(&[]))).is_empty());
+        let events = scan(EventScanner::new(&[]).with_appointment_gap(20));
+        assert_eq!(appointments(&events), vec![("王進", "尚書令")]);
+    }
+
+    #[test]
+    fn test_promotion_gap_below_two_is_raised() {
+        let (events, _) = EventScanner::new(&[]).with_promotion_gap(1).scan_file(
+            "王進遷侍中。",
+            Book::SongShu,
+            Section::LieZhuan,
+            "t",
+        );
+        assert_eq!(appointments(&events), vec![("王進", "侍中")]);
+    }
+
+    #[test]
+    fn test_context_radius_per_kind() {
+        let text = "元嘉三年秋，魏軍大至，諸將皆懼。王進攻壽陽，城中震恐，旬日而拔，斬首萬餘級，盡獲其輜重。\n明年卒。";
+        let battle_context = |scanner: EventScanner| {
+            let (events, _) = scanner.scan_file(text, Book::SongShu, Section::LieZhuan, "t");
+            events
+                .into_iter()
+                .find(|e| matches!(e.kind, EventKind::Battle { .. }))
+                .expect("battle")
+                .context
+        };
+        let narrow = battle_context(EventScanner::new(&[]).with_context_radius(5));
+        let wide = battle_context(
+            EventScanner::new(&[])
+                .with_context_radius(5)
+                .with_kind_context_radius("Battle", 20),
+        );
+        assert!(narrow.contains("王進攻"), "{narrow}");
+        assert!(wide.chars().count() > narrow.chars().count());
+        assert!(wide.contains("斬首"), "{wide}");
+        // the window never crosses into the next line
+        assert!(!wide.contains("明年"), "{wide}");
+    }
+
+    #[test]
+    fn test_long_dudu_title_kept_in_context() {
+        let title = "使持節都督荊湘雍梁益寧南北秦八州諸軍事征西將軍荊州刺史";
+        let text = format!("元嘉三年，以王進為{title}，鎮江陵。");
+        let appointment_context = |scanner: EventScanner| {
+            let (events, _) = scanner.scan_file(&text, Book::SongShu, Section::LieZhuan, "t");
+            events
+                .into_iter()
+                .find(|e| matches!(e.kind, EventKind::Appointment { .. }))
+                .expect("appointment")
+                .context
+        };
+        let context = appointment_context(EventScanner::new(&[]));
+        assert!(context.contains(title), "{context}");
+        // the old flat radius cut it short
+        let flat = appointment_context(
+            EventScanner::new(&[]).with_kind_context_radius("Appointment", DEFAULT_CONTEXT_RADIUS),
+        );
+        assert!(!flat.contains(title), "{flat}");
+    }
+
+    #[test]
+    fn test_base_context_radius_scales_appointments_and_deaths() {
+        let config = |radius| ContextConfig {
+            radius,
+            ..ContextConfig::default()
+        };
+        assert_eq!(config(30).radius("Appointment"), 40);
+        assert_eq!(config(30).radius("Death"), 20);
+        assert_eq!(config(100).radius("Battle"), 100);
+        assert_eq!(config(90).radius("Appointment"), 120);
+        assert_eq!(config(90).radius("Death"), 60);
+        // an explicit per-kind radius still wins
+        let mut fixed = config(90);
+        fixed.radius_by_kind.insert("Death".into(), 10);
+        assert_eq!(fixed.radius("Death"), 10);
+    }
+
+    #[test]
+    fn test_scanners_share_cached_base_regexes() {
+        assert!(std::ptr::eq(regime::era_regex(), regime::era_regex()));
+        assert!(std::ptr::eq(title_regex(), title_regex()));
+
+        let first = EventScanner::new(&[]);
+        for _ in 0..5 {
+            let next = EventScanner::new(&[]);
+            assert!(std::ptr::eq(first.re_time.as_str(), next.re_time.as_str()));
         }
     }
-    surnames.into_iter().collect()
-}
 
-/// Check whether a string looks like a plausible administrative place name.
-/// Used to filter context-extracted place names (before 刺史/太守/內史).
-/// In Six Dynasties texts, administrative place names are 2-3 characters
-/// (e.g., 荊州, 揚州, 南兗州). Longer matches from context windows are
-/// almost always junk-prefixed (e.g., "攻暐洛州" where only "洛州" is real).
-fn is_plausible_place(name: &str) -> bool {
-    let char_count = name.chars().count();
-    // Reject very short or very long names
-    if !(2..=3).contains(&char_count) {
-        return false;
+    #[test]
+    fn test_era_only_time_ref() {
+        let scanner = EventScanner::new(&[]);
+        let text = "太和中朝議，以為不可。太和三年，遷都。";
+        let refs = scanner
+            .scan_file_with_era_refs(text, Book::WeiShu, Section::LieZhuan, "t")
+            .2;
+        assert_eq!(refs.len(), 1);
+        let t = &refs[0].time;
+        assert_eq!((t.era.as_str(), t.regime.as_str()), ("太和", "北魏"));
+        assert_eq!((t.year, t.precision), (0, TimePrecision::Era));
+        assert_eq!(t.raw, "太和中");
+        assert_eq!(exact_ad_year(&t.regime, &t.era, t.year), None);
+        // The scope stops at the dated reference that follows
+        assert_eq!(refs[0].span.byte_end, text.find("太和三年").unwrap());
+
+        // An era name inside a word or without a part-of-era suffix is left alone
+        for text in ["上御太和殿", "於是太和中外", "天下太和中興"] {
+            assert!(
+                scanner
+                    .scan_file_with_era_refs(text, Book::WeiShu, Section::LieZhuan, "t")
+                    .2
+                    .is_empty(),
+                "{text}"
+            );
+        }
     }
-    // Reject bracket/annotation artifacts
-    if name.contains('[') || name.contains(']') {
-        return false;
+
+    #[test]
+    fn test_parse_ganzhi_cycle() {
+        assert_eq!(parse_ganzhi_cycle("甲子"), Some(1));
+        assert_eq!(parse_ganzhi_cycle("丁卯"), Some(4));
+        assert_eq!(parse_ganzhi_cycle("甲戌"), Some(11));
+        assert_eq!(parse_ganzhi_cycle("癸亥"), Some(60));
+        assert_eq!(parse_ganzhi_cycle("甲丑"), None);
+        assert_eq!(parse_ganzhi_cycle("甲"), None);
+        // 元嘉元年 (424) was a 甲子 year
+        assert_eq!(ad_year_cycle(424), 1);
     }
-    // Reject names starting with characters that cannot begin a place name
-    let first = name.chars().next().unwrap();
-    let bad_starts: &[char] = &[
-        '殺', '攻', '伐', '克', '陷', '討', '破', '逐', '執', // military verbs
-        '使', '令', '遣', '命', '除', '拜', '遷', '轉', '授', // appointment verbs
-        '乃', '又', '則', '其', '先', '亦', '再', '俄', '仍', // adverbs/connectives
-        '兄', '弟', '父', '母', '叔', // kinship terms
-        '偽', '僞', '故', '舊', '前', '後', '害', '盜', // modifiers/verbs
-        '加', '領', '兼', '行', '代', '署', '出', '入', '功', // official action words
-        '是', '走', '率', '擒', '獲', '斬', '在', '及', // misc verbs
-        '與', '隨', '自', '累', '左', '右', '號', '詔', '贈', // misc
-        '遙', '重', '衆', '勒', '從', '結', '更', '如', '乘', // misc
-        '時', '方', '永', '爲', '歷', '曆', '瑗', '苗', '宋', // temporal/surnames/misc
-    ];
-    !bad_starts.contains(&first)
-}
 
-fn extract_context(text: &str, byte_offset: usize, char_radius: usize) -> String {
-    let chars: Vec<char> = text.chars().collect();
-    let mut byte_pos = 0;
-    let mut char_idx = 0;
-    for (i, ch) in chars.iter().enumerate() {
-        if byte_pos >= byte_offset {
-            char_idx = i;
-            break;
+    #[test]
+    fn test_day_of_month_from_shuo() {
+        assert_eq!(day_of_month("甲子", "甲子"), Some(1));
+        assert_eq!(day_of_month("甲子", "丁卯"), Some(4));
+        // 丙寅 (3) comes before 癸亥 (60) in the cycle: next run of sixty
+        assert_eq!(day_of_month("癸亥", "丙寅"), Some(4));
+        // more than 30 days on is another month
+        assert_eq!(day_of_month("甲子", "甲午"), None);
+
+        // an annal gives the era-year once, then only months and days
+        let scanner = EventScanner::new(&[]);
+        let text = "元嘉三年春正月甲子朔，日有蝕之。丁卯，王進卒。二月，大水。己巳，張華卒。";
+        let (events, scopes) = scanner.scan_file(text, Book::SongShu, Section::BenJi, "t");
+        let origins: Vec<TimeOrigin> = scopes.iter().map(|s| s.time.origin).collect();
+        assert_eq!(
+            origins,
+            [
+                TimeOrigin::Era,
+                TimeOrigin::DayMarker,
+                TimeOrigin::DayMarker,
+                TimeOrigin::DayMarker
+            ]
+        );
+        let death = |name: &str| {
+            events
+                .iter()
+                .find(|e| e.person_name() == Some(name))
+                .and_then(|e| e.time.as_ref())
+                .map(|t| (t.year, t.month, t.day_ganzhi.clone(), t.day))
+        };
+        assert_eq!(
+            death("王進"),
+            Some((3, Some(1), Some("丁卯".into()), Some(4)))
+        );
+        // no 朔 recorded for 二月: the month carries, the day does not
+        assert_eq!(death("張華"), Some((3, Some(2), Some("己巳".into()), None)));
+
+        // outside the annals a bare marker is not a date
+        let (events, scopes) = scanner.scan_file(text, Book::SongShu, Section::LieZhuan, "t");
+        assert_eq!(scopes.len(), 1);
+        assert!(
+            events
+                .iter()
+                .all(|e| e.time.as_ref().unwrap().month == Some(1))
+        );
+    }
+
+    #[test]
+    fn test_ganzhi_year_resolved_against_era() {
+        let scanner = EventScanner::new(&[]);
+        let text = "元嘉三年，歲在丙寅，王進卒。後歲在庚午，張華卒。";
+        let (events, scopes) = scanner.scan_file(text, Book::SongShu, Section::LieZhuan, "t");
+        let death_year = |name: &str| {
+            events
+                .iter()
+                .find(|e| e.person_name() == Some(name))
+                .and_then(|e| e.time.as_ref())
+                .map(|t| (t.era.clone(), t.year, t.year_ganzhi.clone()))
+        };
+        // 丙寅 is 元嘉三年 itself: confirmed, not a second reference
+        assert_eq!(
+            death_year("王進"),
+            Some(("元嘉".into(), 3, Some("丙寅".into())))
+        );
+        // 庚午 is 430, 元嘉七年
+        assert_eq!(
+            death_year("張華"),
+            Some(("元嘉".into(), 7, Some("庚午".into())))
+        );
+        assert_eq!(scopes.len(), 2);
+
+        // no era to read it against: kept with the era refs, unresolved
+        let refs = scanner
+            .scan_file_with_era_refs("歲在甲子，王進卒。", Book::SongShu, Section::LieZhuan, "t")
+            .2;
+        assert_eq!(refs.len(), 1);
+        assert_eq!(refs[0].time.precision, TimePrecision::Sexagenary);
+        assert_eq!(refs[0].time.year_ganzhi.as_deref(), Some("甲子"));
+
+        // 景平 (423-424) never reached a 丙寅 year: no scope, but an era ref
+        let (_, scopes, refs) = scanner.scan_file_with_era_refs(
+            "景平元年，王進卒。歲在丙寅，張華卒。",
+            Book::SongShu,
+            Section::LieZhuan,
+            "t",
+        );
+        assert_eq!(scopes.len(), 1);
+        let unresolved: Vec<_> = refs
+            .iter()
+            .map(|s| (s.time.precision, s.time.raw.as_str()))
+            .collect();
+        assert_eq!(unresolved, [(TimePrecision::Sexagenary, "歲在丙寅")]);
+    }
+
+    #[test]
+    fn test_order_key_breaks_same_offset_ties_by_kind() {
+        let (events, _) = EventScanner::new(&[]).scan_file(
+            "元嘉三年，王進卒。",
+            Book::SongShu,
+            Section::LieZhuan,
+            "t",
+        );
+        let death = events
+            .iter()
+            .find(|e| matches!(e.kind, EventKind::Death { .. }))
+            .expect("death event");
+        let grant = Event {
+            kind: EventKind::PosthumousGrant {
+                person: Some("王進".into()),
+                verb: "贈".into(),
+                title: "司空".into(),
+                retroactive: false,
+            },
+            ..death.clone()
+        };
+        for mut pair in [vec![&grant, death], vec![death, &grant]] {
+            pair.sort_by_key(|e| e.order_key());
+            let kinds: Vec<_> = pair.iter().map(|e| e.kind.name()).collect();
+            assert_eq!(kinds, ["Death", "PosthumousGrant"]);
         }
-        byte_pos += ch.len_utf8();
     }
 
-    let start = char_idx.saturating_sub(char_radius);
-    let end = (char_idx + char_radius).min(chars.len());
+    #[test]
+    fn test_merge_window_collapses_repeat_match() {
+        let text = "以王進為侍中，又為侍中。";
+        let (events, _) =
+            EventScanner::new(&[]).scan_file(text, Book::SongShu, Section::LieZhuan, "t");
+        let first = events
+            .iter()
+            .find(|e| matches!(e.kind, EventKind::Appointment { .. }))
+            .expect("appointment")
+            .clone();
+        // the same appointment matched again 5 chars on
+        let repeat = Event {
+            byte_offset: first.byte_offset + "以王進為侍".len(),
+            ..first.clone()
+        };
+        let other = Event {
+            kind: EventKind::Appointment {
+                person: "張華".into(),
+                new_title: "侍中".into(),
+                place: None,
+            },
+            ..repeat.clone()
+        };
+        let merged = |window| {
+            let mut events = vec![first.clone(), repeat.clone(), other.clone()];
+            merge_adjacent(&mut events, text, window);
+            events.iter().map(|e| e.byte_offset).collect::<Vec<_>>()
+        };
+        assert_eq!(merged(0).len(), 3);
+        assert_eq!(merged(4).len(), 3);
+        // 張華 is another person and stays
+        assert_eq!(merged(5), [first.byte_offset, other.byte_offset]);
+    }
 
-    let window: String = chars[start..end].iter().collect();
-    window
-        .lines()
-        .find(|l| !l.is_empty())
-        .unwrap_or(&window)
-        .to_string()
-}
+    #[test]
+    fn test_kind_zh_labels() {
+        let kinds = [
+            (
+                EventKind::Appointment {
+                    person: "王進".into(),
+                    new_title: "侍中".into(),
+                    place: None,
+                },
+                "任命",
+            ),
+            (
+                EventKind::Battle {
+                    person: "王進".into(),
+                    verb: "攻".into(),
+                    target: "建康".into(),
+                    target_place: None,
+                    target_person: None,
+                    troops: None,
+                    units: Vec::new(),
+                },
+                "戰事",
+            ),
+            (
+                EventKind::Death {
+                    person: "王進".into(),
+                    verb: "卒".into(),
+                    manner: DeathManner::Natural,
+                    killer: None,
+                    posthumous_title: None,
+                    posthumous_name: None,
+                    age_at_death: None,
+                },
+                "死亡",
+            ),
+            (
+                EventKind::Enfeoffment {
+                    person: None,
+                    verb: "封".into(),
+                    fief: "武昌".into(),
+                    rank: "王".into(),
+                    is_reassignment: false,
+                },
+                "封爵",
+            ),
+            (
+                EventKind::Defeat {
+                    person: "王進".into(),
+                    verb: "敗".into(),
+                    victor: "魏".into(),
+                },
+                "敗績",
+            ),
+            (
+                EventKind::Dispatch {
+                    dispatcher: None,
+                    agent: "張華".into(),
+                    purpose: "使魏".into(),
+                    troops: None,
+                    units: Vec::new(),
+                },
+                "派遣",
+            ),
+            (
+                EventKind::TenureEnd {
+                    person: None,
+                    verb: "罷".into(),
+                    office_or_place: "荊州".into(),
+                    successor: None,
+                },
+                "去職",
+            ),
+            (
+                EventKind::Decline {
+                    person: None,
+                    verb: "固辭".into(),
+                    office: None,
+                },
+                "辭讓",
+            ),
+            (
+                EventKind::Investiture {
+                    person: "王進".into(),
+                    role: "太子".into(),
+                    by: None,
+                },
+                "冊立",
+            ),
+            (
+                EventKind::Pacification {
+                    person: None,
+                    verb: "平".into(),
+                    place: pacified_place("關中", "").unwrap(),
+                },
+                "平定",
+            ),
+            (
+                EventKind::PosthumousGrant {
+                    person: Some("王進".into()),
+                    verb: "追贈".into(),
+                    title: "司空".into(),
+                    retroactive: true,
+                },
+                "贈官",
+            ),
+            (
+                EventKind::Abdication {
+                    person: Some("順帝".into()),
+                    verb: "禪位".into(),
+                    to: Some("齊王".into()),
+                },
+                "禪位",
+            ),
+            (
+                EventKind::UsurpationEra {
+                    person: None,
+                    era: "龍昇".into(),
+                    known_era: true,
+                },
+                "僭號",
+            ),
+            (
+                EventKind::PopulationMove {
+                    person: None,
+                    verb: "徙".into(),
+                    group: Some("豪族".into()),
+                    count: Some(3000),
+                    unit: Some("戶".into()),
+                    to: Some("京師".into()),
+                },
+                "徙民",
+            ),
+            (
+                EventKind::Diplomacy {
+                    sender: Some("高麗國".into()),
+                    target: None,
+                    kind: "朝貢".into(),
+                },
+                "遣使",
+            ),
+            (
+                EventKind::Omen {
+                    phenomenon: "地震".into(),
+                    place: None,
+                },
+                "災異",
+            ),
+            (
+                EventKind::Rebellion {
+                    person: "王進".into(),
+                    verb: "反".into(),
+                    place: None,
+                },
+                "叛亂",
+            ),
+            (
+                EventKind::Surrender {
+                    person: "王進".into(),
+                    verb: "降".into(),
+                    to: Some("魏".into()),
+                },
+                "降附",
+            ),
+            (
+                EventKind::Marriage {
+                    person: "王進".into(),
+                    verb: "尚".into(),
+                    spouse: Some("公主".into()),
+                },
+                "婚姻",
+            ),
+        ];
+        for (kind, label) in &kinds {
+            assert_eq!(kind.kind_zh(), *label, "{}", kind.name());
+            assert_eq!(
+                EventKind::NAMES[usize::from(kind.discriminant())],
+                kind.name()
+            );
+        }
+        assert_eq!(kinds.len(), EventKind::NAMES.len());
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[test]
+    fn test_parse_cn_count() {
+        assert_eq!(parse_cn_count("三萬"), Some(30_000));
+        assert_eq!(parse_cn_count("十萬"), Some(100_000));
+        assert_eq!(parse_cn_count("二萬五千"), Some(25_000));
+        assert_eq!(parse_cn_count("五百"), Some(500));
+        assert_eq!(parse_cn_count("數萬"), None);
+    }
 
     #[test]
-    fn test_exact_ad_year_liu_song() {
-        // 元嘉 started in AD 424
-        assert_eq!(exact_ad_year("劉宋", "元嘉", 1), Some(424));
-        assert_eq!(exact_ad_year("劉宋", "元嘉", 30), Some(453));
+    fn test_parse_cn_number() {
+        assert_eq!(parse_cn_number("元"), Some(1));
+        assert_eq!(parse_cn_number("十二"), Some(12));
+        assert_eq!(parse_cn_number("四十三"), Some(43));
+        assert_eq!(parse_cn_number("七十二"), Some(72));
+        assert_eq!(parse_cn_number("一百"), None);
     }
 
     #[test]
-    fn test_exact_ad_year_northern_wei() {
-        // 太和 started in AD 477
-        assert_eq!(exact_ad_year("北魏", "太和", 1), Some(477));
-        assert_eq!(exact_ad_year("北魏", "太和", 23), Some(499));
+    fn test_scan_dispatch_troops() {
+        let troops = scan_kind(
+            "遣王進將兵三萬攻建康。",
+            Book::SongShu,
+            Section::LieZhuan,
+            |e| match e.kind {
+                EventKind::Dispatch { troops, .. } => Some(troops),
+                _ => None,
+            },
+        );
+        assert_eq!(troops, [Some(30_000)]);
     }
 
     #[test]
-    fn test_exact_ad_year_cross_regime_ordering() {
-        // 劉宋/元嘉 (AD 424) should be BEFORE 北魏/太和 (AD 477)
-        let song = exact_ad_year("劉宋", "元嘉", 1).unwrap();
-        let wei = exact_ad_year("北魏", "太和", 1).unwrap();
-        assert!(
-            song < wei,
-            "劉宋/元嘉({song}) should be before 北魏/太和({wei})"
+    fn test_scan_battle_troops() {
+        let troops = scan_kind(
+            "王進攻建康，步騎十萬。",
+            Book::SongShu,
+            Section::LieZhuan,
+            |e| match e.kind {
+                EventKind::Battle { troops, .. } => Some(troops),
+                _ => None,
+            },
         );
+        assert_eq!(troops, [Some(100_000)]);
     }
 
     #[test]
-    fn test_exact_ad_year_dynasty_succession() {
-        // 義熙14年 = AD 418, 元嘉1年 = AD 424 — gap of 6 years (劉宋永初 in between)
-        let yixi = exact_ad_year("東晉", "義熙", 14).unwrap();
-        let yuanjia = exact_ad_year("劉宋", "元嘉", 1).unwrap();
-        assert_eq!(yixi, 418);
-        assert_eq!(yuanjia, 424);
-        let gap = yuanjia.abs_diff(yixi);
-        assert!(gap <= 10, "義熙14→元嘉1 gap should be small, got {gap}");
+    fn test_scan_dispatch_units() {
+        let dispatch = |text: &str| {
+            scan_kind(text, Book::SongShu, Section::LieZhuan, |e| match e.kind {
+                EventKind::Dispatch { troops, units, .. } => Some((troops, units)),
+                _ => None,
+            })
+            .into_iter()
+            .next()
+        };
+        assert_eq!(
+            dispatch("遣王進將部曲五千攻建康。"),
+            Some((Some(5_000), vec!["部曲".to_string()]))
+        );
+        assert_eq!(
+            dispatch("遣王進率五軍攻壽陽。"),
+            Some((None, vec!["五軍".to_string()]))
+        );
+        assert_eq!(
+            dispatch("遣王進率三營攻壽陽，破其六營。"),
+            Some((None, vec!["三營".to_string(), "六營".to_string()]))
+        );
+        // 將軍 and the stock 三軍 are not units
+        assert_eq!(
+            dispatch("遣王進攻建康，三軍皆懼，假輔國將軍。"),
+            Some((None, Vec::new()))
+        );
     }
 
     #[test]
-    fn test_exact_ad_year_unknown_era() {
-        assert!(exact_ad_year("劉宋", "不存在", 1).is_none());
+    fn test_scan_surrender() {
+        let scan = |text: &str| {
+            scan_kind(text, Book::SongShu, Section::LieZhuan, |e| match e.kind {
+                EventKind::Surrender { person, verb, to } => Some((person, verb, to)),
+                _ => None,
+            })
+        };
+        assert_eq!(
+            scan("王進以城降魏。"),
+            [("王進".into(), "以城降".into(), Some("魏".into()))]
+        );
+        assert_eq!(
+            scan("張華率眾歸降於宋。"),
+            [("張華".into(), "率眾歸降".into(), Some("宋".into()))]
+        );
+        // 降為 is a demotion
+        assert!(scan("王進降為侍中。").is_empty());
     }
 
     #[test]
-    fn test_exact_ad_year_unknown_regime() {
-        assert!(exact_ad_year("不存在", "元嘉", 1).is_none());
+    fn test_scan_marriage() {
+        let scan = |text: &str| {
+            scan_kind(text, Book::SongShu, Section::LieZhuan, |e| match e.kind {
+                EventKind::Marriage {
+                    person,
+                    verb,
+                    spouse,
+                } => Some((person, verb, spouse)),
+                _ => None,
+            })
+        };
+        assert_eq!(
+            scan("王進尚高祖女吳興長公主拜駙馬都尉。"),
+            [("王進".into(), "尚".into(), Some("高祖女吳興長公主".into()))]
+        );
+        assert_eq!(
+            scan("張華娶王氏女為妻。"),
+            [("張華".into(), "娶".into(), Some("王氏女".into()))]
+        );
+        assert_eq!(
+            scan("王進妻以宗女。"),
+            [("王進".into(), "妻以".into(), Some("宗女".into()))]
+        );
+        // 尚書 is an office, not a marriage
+        assert!(scan("王進尚書令。").is_empty());
+        // the clause before the verb is not a name
+        assert!(scan("百姓忽聞其尚主。").is_empty());
+        assert!(scan("宋末選尚公主。").is_empty());
+        assert!(scan("齊代娶王氏女。").is_empty());
+        assert!(scan("高祖先娶王氏女。").is_empty());
+        // 子尚 is a name, and 山陰主 is not his bride
+        assert!(scan("豫章王子尚及山陰主。").is_empty());
+        // 之 ends many given names
+        assert_eq!(
+            scan("初，褚湛之尚始安公主，薨。"),
+            [("褚湛之".into(), "尚".into(), Some("始安公主".into()))]
+        );
+        // 妻以 with no kin noun: his wife was put to death
+        assert!(scan("袁慆妻以妒忌賜死。").is_empty());
+    }
+
+    #[test]
+    fn test_zaiji_rejects_mid_word_name() {
+        let scanner = EventScanner::new(&[]);
+        let text = "石季龍將攻上邽，洪又請降。";
+        let battles = |section| {
+            let (events, _) = scanner.scan_file(text, Book::JinShu, section, "t");
+            events
+                .iter()
+                .filter(|e| matches!(e.kind, EventKind::Battle { .. }))
+                .count()
+        };
+        assert_eq!(battles(Section::LieZhuan), 1);
+        assert_eq!(battles(Section::ZaiJi), 0);
+    }
+
+    #[test]
+    fn test_direct_appointment_with_name() {
+        let scanner = EventScanner::new(&[]);
+        let (events, _) = scanner.scan_file(
+            "元嘉三年，拜王進侍中。",
+            Book::SongShu,
+            Section::LieZhuan,
+            "t",
+        );
+        assert_eq!(appointments(&events), vec![("王進", "侍中")]);
+    }
+
+    #[test]
+    fn test_direct_appointment_subject_omitted() {
+        let scanner = EventScanner::new(&[]);
+        let file = "宋書/00_本紀/00_本紀第一　武帝上/02_武帝上.txt";
+        let text = "義熙元年，除冠軍將軍。";
+        let (events, _) = scanner.scan_file(text, Book::SongShu, Section::BenJi, file);
+        assert_eq!(appointments(&events), vec![("武帝", "冠軍將軍")]);
+
+        // No subject to attach to outside the annals, and an office that
+        // starts with a surname char is not read as a name
+        let (events, _) = scanner.scan_file("除黃門侍郎。", Book::SongShu, Section::LieZhuan, "t");
+        assert!(appointments(&events).is_empty());
+    }
+
+    #[test]
+    fn test_explicit_promotion_in_biography() {
+        let scanner = EventScanner::new(&[]);
+        let (events, _) = scanner.scan_file(
+            "明年，王進遷中書侍郎。",
+            Book::SongShu,
+            Section::LieZhuan,
+            "t",
+        );
+        assert_eq!(appointments(&events), vec![("王進", "中書侍郎")]);
+    }
+
+    #[test]
+    fn test_scan_passive_defeat() {
+        let scanner = EventScanner::new(&[]);
+        let (events, _) = scanner.scan_file(
+            "王進為魏所敗，退保壽陽。",
+            Book::SongShu,
+            Section::LieZhuan,
+            "t",
+        );
+        let defeat = events
+            .iter()
+            .find_map(|e| match &e.kind {
+                EventKind::Defeat {
+                    person,
+                    verb,
+                    victor,
+                } => Some((person.as_str(), verb.as_str(), victor.as_str())),
+                _ => None,
+            })
+            .expect("defeat event");
+        assert_eq!(defeat, ("王進", "敗", "魏"));
+    }
+
+    #[test]
+    fn test_scan_passive_killing() {
+        let scanner = EventScanner::new(&[]);
+        let (events, _) =
+            scanner.scan_file("王進為賊所殺。", Book::SongShu, Section::LieZhuan, "t");
+        assert_eq!(
+            find_death(&events),
+            ("王進", DeathManner::Killed, Some("賊"))
+        );
+        assert!(
+            !events
+                .iter()
+                .any(|e| matches!(e.kind, EventKind::Defeat { .. }))
+        );
+    }
+
+    #[test]
+    fn test_scan_violent_death_yuhai() {
+        let scanner = EventScanner::new(&[]);
+        let (events, _) = scanner.scan_file(
+            "張華遇害，時年五十。",
+            Book::SongShu,
+            Section::LieZhuan,
+            "t",
+        );
+        let (person, manner, killer) = find_death(&events);
+        assert_eq!(person, "張華");
+        assert_eq!(manner, DeathManner::Killed);
+        assert_eq!(killer, None);
     }
 }
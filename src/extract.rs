@@ -1,6 +1,7 @@
 use std::collections::HashMap;
-use std::fs;
 
+use crate::gazetteer;
+use crate::scanner::read_text;
 use crate::types::*;
 
 /// Statistics about how a person is referred to in their own biography.
@@ -14,7 +15,7 @@ pub struct RefStats {
 
 /// Count how often each alias of a person appears in their biography text.
 pub fn count_refs_in_biography(person: &Person) -> RefStats {
-    let content = match fs::read_to_string(&person.source.file_path) {
+    let content = match read_text(&person.source.file_path) {
         Ok(c) => c,
         Err(_) => return RefStats::default(),
     };
@@ -53,6 +54,15 @@ pub struct PersonSummary {
     pub courtesy_name: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub origin: Option<String>,
+    /// (latitude, longitude) of the origin's commandery seat, from
+    /// `gazetteer::geocode`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub origin_coords: Option<(f64, f64)>,
+    /// 本籍 and 僑居 of an émigré family, see `PersonKind::Official`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ancestral_origin: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub current_residence: Option<String>,
     pub file: String,
 }
 
@@ -99,6 +109,15 @@ impl PersonSummary {
             }
         };
 
+        let (ancestral_origin, current_residence) = match &person.kind {
+            PersonKind::Official {
+                ancestral_origin,
+                current_residence,
+                ..
+            } => (ancestral_origin.clone(), current_residence.clone()),
+            _ => (None, None),
+        };
+
         let section = match person.source.section {
             Section::BenJi => "本紀",
             Section::LieZhuan => "列傳",
@@ -115,7 +134,10 @@ impl PersonSummary {
             aliases: person.aliases.clone(),
             ref_stats,
             courtesy_name,
+            origin_coords: origin.as_deref().and_then(gazetteer::geocode),
             origin,
+            ancestral_origin,
+            current_residence,
             file: person.source.file_path.display().to_string(),
         }
     }
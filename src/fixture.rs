@@ -0,0 +1,147 @@
+//! Synthetic corpus for end-to-end tests.
+//!
+//! Writes a few crafted files in the `{書名}/{NN_section}/{NN_卷名}/{NN_人名}.txt`
+//! layout `scanner::scan_corpus` expects, so a test can run discovery,
+//! biography parsing and event extraction together instead of one regex
+//! at a time.
+
+use std::path::Path;
+
+use tempfile::TempDir;
+
+use crate::types::{Book, CourtesyName, Person, PersonKind, Section, Source};
+
+/// One corpus file: path relative to the corpus root, and its text.
+pub struct FixtureFile {
+    pub path: &'static str,
+    pub text: &'static str,
+}
+
+/// A 宋書 with one annals juan and one biography juan.
+pub const SONG_CORPUS: &[FixtureFile] = &[
+    FixtureFile {
+        path: "宋書/00_本紀/00_本紀第一　武帝上/02_武帝上.txt",
+        text: "高祖武皇帝諱裕，字德輿，小名寄奴，彭城縣綏輿里人。\n\
+               永初元年六月，即皇帝位。三年五月，帝崩。\n",
+    },
+    FixtureFile {
+        path: "宋書/02_列傳/02_列傳第三　傅亮/03_傅亮.txt",
+        text: "傅亮字季友，北地靈州人也。\n\
+               永初元年，以傅亮為尚書僕射。\n\
+               元嘉三年，傅亮伏誅。\n",
+    },
+];
+
+/// 褚淵's biography file in 南齊書.
+pub const CHU_YUAN_FILE: &str = "南齊書/02_列傳/04_列傳第四　褚淵/01_褚淵.txt";
+
+/// 褚淵 (字彥回), as parsed from `CHU_YUAN_FILE`, known by `aliases`.
+pub fn chu_yuan(aliases: &[&str]) -> Person {
+    Person {
+        kind: PersonKind::Official {
+            surname: "褚".into(),
+            given_name: "淵".into(),
+            courtesy_name: CourtesyName::Recorded("彥回".into()),
+            origin: Some("河南陽翟".into()),
+            ancestral_origin: None,
+            current_residence: None,
+        },
+        source: Source {
+            book: Book::NanQiShu,
+            section: Section::LieZhuan,
+            juan: "列傳第四　褚淵".into(),
+            file_path: CHU_YUAN_FILE.into(),
+        },
+        aliases: aliases.iter().map(|a| a.to_string()).collect(),
+    }
+}
+
+/// Write `files` under a fresh temporary corpus root. The directory is
+/// removed when the returned guard drops.
+pub fn write_corpus(files: &[FixtureFile]) -> TempDir {
+    let root = tempfile::tempdir().expect("temp corpus root");
+    for f in files {
+        write_file(root.path(), f);
+    }
+    root
+}
+
+fn write_file(root: &Path, f: &FixtureFile) {
+    let path = root.join(f.path);
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(&path, f.text).unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::{EventKind, EventScanner, Timeline};
+    use crate::{parser, scanner};
+
+    #[test]
+    fn test_pipeline_on_fixture_corpus() {
+        let root = write_corpus(SONG_CORPUS);
+
+        let bio_files = scanner::scan_corpus(root.path());
+        assert_eq!(bio_files.len(), 2);
+
+        let persons: Vec<_> = bio_files
+            .iter()
+            .filter_map(|bio| parser::parse_biography(bio).unwrap())
+            .collect();
+        assert!(
+            persons.iter().any(|p| p.display_name() == "傅亮"),
+            "parsed: {:?}",
+            persons.iter().map(|p| p.display_name()).collect::<Vec<_>>()
+        );
+
+        let (events, index, stats) = EventScanner::new(&persons).scan_corpus(&bio_files);
+        let appointment = events
+            .iter()
+            .find(|e| {
+                matches!(&e.kind, EventKind::Appointment { person, new_title, .. }
+                    if person == "傅亮" && new_title == "尚書僕射")
+            })
+            .expect("appointment of 傅亮");
+        let time = appointment.time.as_ref().expect("dated appointment");
+        assert_eq!((time.era.as_str(), time.year), ("永初", 1));
+        assert!(stats.deaths >= 1);
+
+        let timeline = Timeline::from_scopes(&index.scopes);
+        assert!(timeline.regimes.iter().any(|r| r.regime == "劉宋"));
+    }
+
+    #[test]
+    fn test_scan_corpus_keeps_file_order() {
+        let root = write_corpus(SONG_CORPUS);
+        let bio_files = scanner::scan_corpus(root.path());
+        let persons: Vec<_> = bio_files
+            .iter()
+            .filter_map(|bio| parser::parse_biography(bio).unwrap())
+            .collect();
+        let scanner = EventScanner::new(&persons);
+
+        let (events, index, stats) = scanner.scan_corpus(&bio_files);
+        let files: Vec<String> = bio_files
+            .iter()
+            .map(|b| b.path.display().to_string())
+            .collect();
+        let order: Vec<usize> = events
+            .iter()
+            .map(|e| files.iter().position(|f| *f == e.source_file).unwrap())
+            .collect();
+        assert!(order.is_sorted(), "{order:?}");
+        // scope indices were rebased onto the merged index
+        for e in events.iter().filter(|e| e.scope_index.is_some()) {
+            let scope = &index.scopes[e.scope_index.unwrap()];
+            assert_eq!(scope.span.file, e.source_file);
+        }
+
+        let (again, _, again_stats) = scanner.scan_corpus(&bio_files);
+        let offsets = |es: &[crate::event::Event]| -> Vec<usize> {
+            es.iter().map(|e| e.byte_offset).collect()
+        };
+        assert_eq!(offsets(&events), offsets(&again));
+        assert_eq!(stats.top_places, again_stats.top_places);
+    }
+}
@@ -0,0 +1,227 @@
+//! Commandery gazetteer for geocoding native places (籍貫).
+//!
+//! Biographies give origin as commandery + county ("吳興武康", "琅邪臨沂").
+//! Only the commandery is looked up: coordinates are those of its seat,
+//! approximate, as (latitude, longitude) in decimal degrees. Good enough
+//! for a map of where officials came from, not for county-level work.
+//!
+//! Prefectures (州) are keyed differently: the same name stood for
+//! different places under different regimes and in different decades
+//! (徐州 at 彭城 or at 鍾離; the 僑 南徐州 at 京口), so they resolve by
+//! regime and AD year to a `name/seat` identity.
+
+/// (commandery, latitude, longitude). 國 and 郡 spellings of the same
+/// unit (譙國/譙郡) are listed separately, as the texts use both.
+static COMMANDERIES: &[(&str, f64, f64)] = &[
+    // ── South of the Huai ──
+    ("丹陽", 32.06, 118.78),
+    ("吳郡", 31.30, 120.62),
+    ("吳興", 30.87, 120.09),
+    ("會稽", 30.00, 120.58),
+    ("義興", 31.36, 119.82),
+    ("晉陵", 31.78, 119.95),
+    ("南蘭陵", 31.78, 119.95), // 僑 commandery at 武進
+    ("東陽", 29.08, 119.65),
+    ("廣陵", 32.39, 119.42),
+    ("廬江", 31.25, 117.29),
+    ("豫章", 28.68, 115.86),
+    ("長沙", 28.23, 112.94),
+    ("武陵", 29.03, 111.69),
+    ("武昌", 30.40, 114.89),
+    ("江夏", 31.26, 113.69),
+    ("竟陵", 31.17, 112.59),
+    ("南郡", 30.35, 112.19),
+    ("襄陽", 32.01, 112.12),
+    ("義陽", 32.13, 114.07),
+    ("蜀郡", 30.66, 104.07),
+    ("巴西", 31.58, 105.97),
+    // ── Huai and Central Plain ──
+    ("彭城", 34.26, 117.19),
+    ("下邳", 34.33, 117.96),
+    ("東海", 34.61, 118.37),
+    ("蘭陵", 34.85, 117.85),
+    ("琅邪", 35.10, 118.35),
+    ("東莞", 35.58, 118.83),
+    ("沛國", 33.92, 116.77),
+    ("沛郡", 33.92, 116.77),
+    ("譙國", 33.84, 115.78),
+    ("譙郡", 33.84, 115.78),
+    ("汝南", 32.96, 114.63),
+    ("新蔡", 32.75, 114.98),
+    ("汝陰", 32.89, 115.81),
+    ("陳郡", 33.73, 114.88),
+    ("陳國", 33.73, 114.88),
+    ("梁國", 34.41, 115.65),
+    ("潁川", 34.15, 113.46),
+    ("陳留", 34.60, 114.55),
+    ("濟陽", 34.82, 114.82),
+    ("濟陰", 35.07, 115.57),
+    ("高平", 35.11, 116.21),
+    ("南陽", 32.99, 112.53),
+    ("新野", 32.52, 112.36),
+    ("順陽", 32.98, 111.49),
+    ("滎陽", 34.79, 113.38),
+    ("河南", 34.62, 112.45),
+    ("河內", 35.09, 112.95),
+    ("弘農", 34.52, 110.89),
+    // ── Shandong and Hebei ──
+    ("魯郡", 35.60, 116.99),
+    ("魯國", 35.60, 116.99),
+    ("泰山", 36.21, 117.20),
+    ("太山", 36.21, 117.20),
+    ("東平", 35.94, 116.47),
+    ("濟北", 36.55, 116.73),
+    ("東郡", 35.77, 115.04),
+    ("北海", 36.70, 119.10),
+    ("平昌", 36.48, 119.22),
+    ("東萊", 37.18, 119.94),
+    ("樂安", 37.15, 118.13),
+    ("平原", 37.16, 116.43),
+    ("清河", 37.06, 115.67),
+    ("陽平", 36.28, 115.15),
+    ("魏郡", 36.33, 114.62),
+    ("廣平", 36.83, 114.88),
+    ("鉅鹿", 37.22, 115.04),
+    ("趙郡", 37.75, 114.78),
+    ("長樂", 37.57, 115.57),
+    ("勃海", 38.04, 116.70),
+    ("渤海", 38.04, 116.70),
+    ("河間", 38.44, 116.10),
+    ("博陵", 38.23, 115.52),
+    ("高陽", 38.70, 115.78),
+    ("中山", 38.52, 114.99),
+    ("范陽", 39.49, 115.97),
+    ("燕國", 39.90, 116.40),
+    ("上谷", 40.41, 115.50),
+    // ── Shanxi, Guanzhong and the northwest ──
+    ("代", 40.09, 113.30), // 代人: the 平城 region
+    ("代郡", 39.84, 114.57),
+    ("雁門", 39.07, 112.96),
+    ("新興", 38.42, 112.73),
+    ("太原", 37.87, 112.55),
+    ("上黨", 36.19, 113.12),
+    ("平陽", 36.08, 111.52),
+    ("河東", 35.14, 111.22),
+    ("京兆", 34.26, 108.94),
+    ("馮翊", 34.80, 109.94),
+    ("扶風", 34.30, 108.49),
+    ("北地", 34.91, 108.98),
+    ("安定", 35.33, 107.37),
+    ("天水", 34.58, 105.72),
+    ("略陽", 34.86, 105.67),
+    ("南安", 34.98, 104.87),
+    ("隴西", 35.00, 104.63),
+    ("金城", 36.06, 103.83),
+    ("敦煌", 40.14, 94.66),
+    ("昌黎", 41.55, 120.45),
+];
+
+/// Coordinates of the commandery an origin string starts with, taking the
+/// longest match so 南蘭陵 is not read as 南 + 蘭陵 and 代郡 not as 代.
+/// None when the origin names no known commandery.
+pub fn geocode(origin: &str) -> Option<(f64, f64)> {
+    COMMANDERIES
+        .iter()
+        .filter(|(name, _, _)| origin.starts_with(name))
+        .max_by_key(|(name, _, _)| name.len())
+        .map(|&(_, lat, lon)| (lat, lon))
+}
+
+/// Southern regimes, for prefecture rows that held across the south.
+const SOUTH: &[&str] = &["東晉", "劉宋", "南齊", "梁", "陳"];
+
+/// One identity of a prefecture name: which regimes used it for that
+/// seat, over which AD years (inclusive), and the resulting id.
+struct Prefecture {
+    name: &'static str,
+    regimes: &'static [&'static str],
+    years: std::ops::RangeInclusive<u16>,
+    id: &'static str,
+}
+
+const fn pref(
+    name: &'static str,
+    regimes: &'static [&'static str],
+    years: std::ops::RangeInclusive<u16>,
+    id: &'static str,
+) -> Prefecture {
+    Prefecture {
+        name,
+        regimes,
+        years,
+        id,
+    }
+}
+
+/// Time-variant seats of homonymous prefectures. 劉宋 lost the lands
+/// north of the Huai in 466–469, after which its 徐州, 青州 and 冀州 were
+/// re-established further south while 北魏 kept the old names at the old
+/// seats. Years not covered are left unresolved.
+static PREFECTURES: &[Prefecture] = &[
+    pref("徐州", &["劉宋"], 420..=468, "徐州/彭城"),
+    pref("徐州", &["劉宋", "南齊", "梁"], 469..=556, "徐州/鍾離"),
+    pref("徐州", &["北魏"], 469..=534, "徐州/彭城"),
+    pref("南徐州", SOUTH, 421..=589, "南徐州/京口"),
+    pref("兗州", &["劉宋"], 420..=466, "兗州/瑕丘"),
+    pref("兗州", &["北魏"], 469..=534, "兗州/瑕丘"),
+    pref("南兗州", SOUTH, 422..=589, "南兗州/廣陵"),
+    pref("青州", &["劉宋"], 420..=468, "青州/東陽"),
+    pref("青州", &["劉宋", "南齊"], 469..=501, "青州/鬱洲"),
+    pref("青州", &["北魏"], 469..=534, "青州/東陽"),
+    pref("冀州", &["劉宋"], 420..=468, "冀州/歷城"),
+    pref("冀州", &["北魏"], 398..=534, "冀州/信都"),
+    pref("豫州", &["劉宋", "南齊"], 420..=500, "豫州/壽陽"),
+    pref("豫州", &["北魏"], 467..=534, "豫州/懸瓠"),
+    pref("荊州", SOUTH, 317..=554, "荊州/江陵"),
+    pref("荊州", &["北魏"], 495..=534, "荊州/穰城"),
+    pref("雍州", &["劉宋", "南齊", "梁"], 420..=548, "雍州/襄陽"),
+    pref("雍州", &["北魏"], 431..=534, "雍州/長安"),
+    pref("司州", &["劉宋", "南齊", "梁"], 467..=548, "司州/義陽"),
+    pref("司州", &["北魏"], 398..=493, "司州/平城"),
+    pref("司州", &["北魏"], 494..=534, "司州/洛陽"),
+];
+
+/// The specific prefecture a name stood for under `regime` (Chinese
+/// name, as in `TimeRef::regime`) in AD `year`. None when the table has
+/// no row for it, or rows that disagree.
+pub fn resolve_prefecture(name: &str, regime: &str, year: u16) -> Option<&'static str> {
+    let mut ids = PREFECTURES
+        .iter()
+        .filter(|p| p.name == name && p.regimes.contains(&regime) && p.years.contains(&year))
+        .map(|p| p.id);
+    let id = ids.next()?;
+    ids.all(|other| other == id).then_some(id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_geocode_commandery_prefix() {
+        assert_eq!(geocode("吳興武康"), Some((30.87, 120.09)));
+        assert_eq!(geocode("南蘭陵蘭陵"), geocode("晉陵"));
+        assert_ne!(geocode("代郡平城"), geocode("代"));
+    }
+
+    #[test]
+    fn test_resolve_real_and_qiao_xuzhou() {
+        // 元嘉 (劉宋, 430): 徐州 still at 彭城; 南徐州 the 僑 unit at 京口
+        assert_eq!(resolve_prefecture("徐州", "劉宋", 430), Some("徐州/彭城"));
+        assert_eq!(
+            resolve_prefecture("南徐州", "劉宋", 430),
+            Some("南徐州/京口")
+        );
+        // after 469 the southern 徐州 moved to 鍾離; 北魏 held 彭城
+        assert_eq!(resolve_prefecture("徐州", "南齊", 490), Some("徐州/鍾離"));
+        assert_eq!(resolve_prefecture("徐州", "北魏", 490), Some("徐州/彭城"));
+        // 東晉 徐州 moved too often to pin down
+        assert_eq!(resolve_prefecture("徐州", "東晉", 400), None);
+    }
+
+    #[test]
+    fn test_geocode_unknown_origin() {
+        assert_eq!(geocode("不知何許"), None);
+        assert_eq!(geocode(""), None);
+    }
+}
@@ -1,12 +1,14 @@
 use std::collections::{HashMap, HashSet};
 use std::fs;
+use std::path::Path;
 
+use aho_corasick::{AhoCorasick, MatchKind};
 use regex::Regex;
 use serde::Serialize;
 
-use crate::scanner::BiographyFile;
-use crate::surname::{build_name_regex, split_name};
-use crate::titles::{STANDALONE_TITLES, TITLE_SUFFIXES, build_title_regex};
+use crate::scanner::{BiographyFile, read_text};
+use crate::surname::{COMPOUND_SURNAMES, build_name_regex, is_known_surname, split_name};
+use crate::titles::{STANDALONE_TITLES, TITLE_SUFFIXES, title_regex};
 use crate::types::Person;
 
 // ── Types ────────────────────────────────────────────────────────────
@@ -22,6 +24,9 @@ pub enum MentionPattern {
     CourtesyIntro,
     /// 問/謂{name}曰 — speech attribution
     Speech,
+    /// The exact name or alias of a person with their own biography,
+    /// found anywhere (see `InTextScanner::with_known_names`)
+    KnownAlias,
 }
 
 impl MentionPattern {
@@ -31,6 +36,7 @@ impl MentionPattern {
             Self::TitleName => "官銜+名",
             Self::CourtesyIntro => "X字Y",
             Self::Speech => "問/謂X曰",
+            Self::KnownAlias => "已知名",
         }
     }
 }
@@ -45,6 +51,8 @@ pub struct InTextMention {
     /// Short context window around the match
     pub context: String,
     pub source_file: String,
+    /// Byte offset of the name in the scanned text
+    pub byte_offset: usize,
 }
 
 /// Aggregated info about a person found via in-text mentions.
@@ -121,6 +129,55 @@ const BLACKLIST: &[&str] = &[
     "梁之眾",
 ];
 
+/// Honorifics and kin terms that precede names in running text
+/// (公褚淵, 兄王該, 弟王進). 王 is left out: as the commonest surname,
+/// "王XY" is far more often a three-char name than 王 + name.
+const NAME_PREFIXES: &[char] = &['公', '卿', '君', '兄', '弟', '叔', '姪', '從', '族'];
+
+/// If `text` (running text from a match start) begins with an honorific or
+/// kin prefix followed by a valid name, return that name: "弟王進為" → 王進.
+/// Tries three chars before two, like the name regex. A compound surname
+/// at the start (公孫淵) is a name, not a prefix.
+/// Public so event.rs can apply the same trim.
+pub fn strip_name_prefix(text: &str) -> Option<&str> {
+    let first = text.chars().next()?;
+    if !NAME_PREFIXES.contains(&first) || COMPOUND_SURNAMES.iter().any(|c| text.starts_with(c)) {
+        return None;
+    }
+    let rest = &text[first.len_utf8()..];
+    let ends: Vec<usize> = rest
+        .char_indices()
+        .map(|(i, c)| i + c.len_utf8())
+        .take(3)
+        .collect();
+    ends.iter().skip(1).rev().find_map(|&end| {
+        let cand = &rest[..end];
+        let (surname, given) = split_name(cand)?;
+        (is_known_surname(&surname)
+            && (1..=2).contains(&given.chars().count())
+            && !is_false_positive_name(cand))
+        .then_some(cand)
+    })
+}
+
+/// Load a user blacklist: one name per line, blank lines and `#` comments skipped.
+pub fn load_blacklist(path: &Path) -> Result<Vec<String>, String> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("cannot read blacklist {}: {e}", path.display()))?;
+    Ok(content
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        .map(str::to_string)
+        .collect())
+}
+
+/// Whether `name` is a surname + 氏 (王氏, 司馬氏): the clan, or a woman
+/// known only by her clan, rather than a man's surname + given name.
+pub fn is_clan_reference(name: &str) -> bool {
+    name.strip_suffix('氏').is_some_and(is_known_surname)
+}
+
 /// Check if the captured name is a false positive.
 /// Public so event.rs can reuse the same filter.
 pub fn is_false_positive_name(name: &str) -> bool {
@@ -129,6 +186,11 @@ pub fn is_false_positive_name(name: &str) -> bool {
         return true;
     }
 
+    // 王氏, 褚氏: a clan, not an individual
+    if is_clan_reference(name) {
+        return true;
+    }
+
     // If the name ends with a title suffix, it's a title chain, not a person.
     // e.g. "左僕射" (ends with 僕射), "黃門侍郎" would be caught differently.
     for suffix in TITLE_SUFFIXES {
@@ -227,15 +289,21 @@ pub struct InTextScanner {
     re_speech: Regex,
     /// Set of names from persons who have their own biography file
     known_names: HashSet<String>,
+    /// User-supplied names to reject, on top of the built-in `BLACKLIST`
+    extra_blacklist: HashSet<String>,
+    /// Known display names and aliases, with the display name each one
+    /// stands for (see `with_known_names`)
+    known_matcher: Option<(AhoCorasick, Vec<String>)>,
 }
 
 impl InTextScanner {
-    /// Build a new scanner. `known_persons` are the already-parsed biography subjects.
-    pub fn new(known_persons: &[Person]) -> Self {
+    /// Build a new scanner. `known_persons` are the already-parsed biography subjects;
+    /// `extra_blacklist` holds corpus-specific false positives (see `load_blacklist`).
+    pub fn new(known_persons: &[Person], extra_blacklist: &[String]) -> Self {
         // Collect extra surnames from known persons
         let extra_surnames = Self::collect_extra_surnames(known_persons);
         let name_re = build_name_regex(&extra_surnames);
-        let title_re = build_title_regex();
+        let title_re = title_regex();
 
         // Pattern 1: 以 ... name ... 為
         let re_appointment =
@@ -269,9 +337,48 @@ impl InTextScanner {
             re_courtesy,
             re_speech,
             known_names,
+            extra_blacklist: extra_blacklist
+                .iter()
+                .filter(|n| !n.is_empty())
+                .cloned()
+                .collect(),
+            known_matcher: None,
         }
     }
 
+    /// Also report every exact occurrence of a known person's display
+    /// name or alias (2+ chars) as a `KnownAlias` mention under their
+    /// display name. This catches given names the surname-anchored regexes
+    /// cannot (courtesy names, three-char given names). An alias shared by
+    /// several persons is left out, as it cannot say which one is meant.
+    pub fn with_known_names(mut self, known_persons: &[Person]) -> Self {
+        let mut owners: HashMap<String, HashSet<String>> = HashMap::new();
+        for p in known_persons {
+            let display = p.display_name();
+            for name in p.aliases.iter().chain([&display]) {
+                if name.chars().count() >= 2 {
+                    owners
+                        .entry(name.clone())
+                        .or_default()
+                        .insert(display.clone());
+                }
+            }
+        }
+        let mut pairs: Vec<(String, String)> = owners
+            .into_iter()
+            .filter(|(_, o)| o.len() == 1)
+            .map(|(name, o)| (name, o.into_iter().next().unwrap()))
+            .collect();
+        pairs.sort();
+        let (patterns, displays): (Vec<String>, Vec<String>) = pairs.into_iter().unzip();
+        let matcher = AhoCorasick::builder()
+            .match_kind(MatchKind::LeftmostLongest)
+            .build(&patterns)
+            .expect("known name automaton");
+        self.known_matcher = Some((matcher, displays));
+        self
+    }
+
     fn collect_extra_surnames(persons: &[Person]) -> Vec<String> {
         let mut surnames = HashSet::new();
         for p in persons {
@@ -318,9 +425,53 @@ impl InTextScanner {
             }
         }
 
+        if let Some((matcher, displays)) = &self.known_matcher {
+            let regex_hits: HashSet<usize> = mentions.iter().map(|m| m.byte_offset).collect();
+            for m in matcher.find_iter(content) {
+                if regex_hits.contains(&m.start()) {
+                    continue;
+                }
+                let display = &displays[m.pattern().as_usize()];
+                let alias = &content[m.range()];
+                if let Some(mention) =
+                    self.known_mention(alias, display, content, m.start(), source_file)
+                {
+                    mentions.push(mention);
+                }
+            }
+        }
+
         mentions
     }
 
+    /// A `KnownAlias` mention of `alias` at `byte_offset`, recorded under
+    /// the display name it stands for.
+    fn known_mention(
+        &self,
+        alias: &str,
+        display: &str,
+        full_text: &str,
+        byte_offset: usize,
+        source_file: &str,
+    ) -> Option<InTextMention> {
+        if [alias, display]
+            .iter()
+            .any(|n| is_false_positive_name(n) || self.extra_blacklist.contains(*n))
+        {
+            return None;
+        }
+        let (surname, given) = split_name(display)?;
+        Some(InTextMention {
+            name: display.to_string(),
+            surname,
+            given,
+            pattern: MentionPattern::KnownAlias,
+            context: extract_context(full_text, byte_offset, 20),
+            source_file: source_file.to_string(),
+            byte_offset,
+        })
+    }
+
     /// Validate and construct a mention from a matched name string.
     fn make_mention(
         &self,
@@ -330,8 +481,14 @@ impl InTextScanner {
         byte_offset: usize,
         source_file: &str,
     ) -> Option<InTextMention> {
+        // "弟王進": a kin/honorific char read as a surname glues onto the name
+        let (matched, byte_offset) = match strip_name_prefix(&full_text[byte_offset..]) {
+            Some(name) => (name, full_text[byte_offset..].find(name)? + byte_offset),
+            None => (matched, byte_offset),
+        };
+
         // False positive filter
-        if is_false_positive_name(matched) {
+        if is_false_positive_name(matched) || self.extra_blacklist.contains(matched) {
             return None;
         }
 
@@ -353,6 +510,7 @@ impl InTextScanner {
             pattern,
             context,
             source_file: source_file.to_string(),
+            byte_offset,
         })
     }
 
@@ -369,7 +527,7 @@ impl InTextScanner {
         let mut agg: HashMap<String, PersonAgg> = HashMap::new();
 
         for bio in bio_files {
-            let content = match fs::read_to_string(&bio.path) {
+            let content = match read_text(&bio.path) {
                 Ok(c) => c,
                 Err(_) => continue,
             };
@@ -418,7 +576,7 @@ impl InTextScanner {
             .collect();
 
         // Sort by mention count descending
-        results.sort_by(|a, b| b.mention_count.cmp(&a.mention_count));
+        results.sort_by_key(|p| std::cmp::Reverse(p.mention_count));
         results
     }
 }
@@ -444,3 +602,89 @@ fn extract_context(text: &str, byte_offset: usize, char_radius: usize) -> String
 
     line.to_string()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture;
+
+    #[test]
+    fn test_strip_kin_prefix() {
+        assert_eq!(strip_name_prefix("兄王該曰"), Some("王該"));
+        assert_eq!(strip_name_prefix("弟王進為"), Some("王進"));
+    }
+
+    #[test]
+    fn test_strip_honorific_prefix() {
+        assert_eq!(strip_name_prefix("公褚淵"), Some("褚淵"));
+        // Compound surname, not 公 + 孫淵
+        assert_eq!(strip_name_prefix("公孫淵"), None);
+        assert_eq!(strip_name_prefix("王進"), None);
+    }
+
+    #[test]
+    fn test_clan_reference_not_a_person() {
+        assert!(is_false_positive_name("王氏"));
+        assert!(is_false_positive_name("褚氏"));
+        assert!(is_false_positive_name("司馬氏"));
+        assert!(!is_false_positive_name("王進"));
+
+        let scanner = InTextScanner::new(&[], &[]);
+        let names: Vec<String> = scanner
+            .scan_text("以王氏為侍中。帝謂褚氏曰", "t")
+            .into_iter()
+            .map(|m| m.name)
+            .collect();
+        assert!(names.is_empty(), "{names:?}");
+    }
+
+    #[test]
+    fn test_blacklist_file_filters_name() {
+        let path = tempfile::NamedTempFile::new().unwrap().into_temp_path();
+        fs::write(&path, "# junk observed in output\n\n王進\n").unwrap();
+        let extra = load_blacklist(&path).unwrap();
+        assert_eq!(extra, vec!["王進".to_string()]);
+
+        let text = "以王進為侍中";
+        let plain = InTextScanner::new(&[], &[]);
+        assert!(plain.scan_text(text, "t").iter().any(|m| m.name == "王進"));
+
+        let filtered = InTextScanner::new(&[], &extra);
+        assert!(
+            !filtered
+                .scan_text(text, "t")
+                .iter()
+                .any(|m| m.name == "王進")
+        );
+    }
+
+    #[test]
+    fn test_known_names_found_by_alias() {
+        let chu_yuan = fixture::chu_yuan(&["褚淵", "淵", "彥回"]);
+        let text = "彥回辭曰。以褚淵為侍中。";
+        let persons = [chu_yuan];
+
+        let plain = InTextScanner::new(&persons, &[]);
+        let found: Vec<_> = plain.scan_text(text, "t");
+        assert_eq!(found.len(), 1);
+
+        let scanner = InTextScanner::new(&persons, &[]).with_known_names(&persons);
+        let found: Vec<(String, MentionPattern, usize)> = scanner
+            .scan_text(text, "t")
+            .into_iter()
+            .map(|m| (m.name, m.pattern, m.byte_offset))
+            .collect();
+        // 褚淵 after 以 is already the appointment hit; 彥回 is new
+        assert_eq!(
+            found,
+            [
+                (
+                    "褚淵".into(),
+                    MentionPattern::Appointment,
+                    text.find("褚淵").unwrap()
+                ),
+                ("褚淵".into(), MentionPattern::KnownAlias, 0),
+            ]
+        );
+    }
+}
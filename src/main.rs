@@ -1,5 +1,9 @@
+mod anomaly;
 mod event;
 mod extract;
+#[cfg(test)]
+mod fixture;
+mod gazetteer;
 mod intext;
 mod parser;
 mod regime;
@@ -30,14 +34,19 @@ struct Cli {
 enum Command {
     /// Run full corpus extraction → output/*.json
     Extract {
-        /// Path to corpus root directory
-        #[arg(default_value = ".")]
-        corpus: PathBuf,
+        /// Corpus root directories, repeated or comma-separated; files
+        /// from all roots are merged
+        #[arg(default_value = ".", value_delimiter = ',')]
+        corpus: Vec<PathBuf>,
+        #[command(flatten)]
+        options: ExtractOptions,
     },
     /// Query time periods from cached output
     Query {
-        /// Time query, e.g. "太和三年", "太和元年-太和六年", "@東晉"
+        /// Time query, e.g. "太和三年", "太和元年-太和六年", "@東晉", "元嘉 永明"
         query: Vec<String>,
+        #[command(flatten)]
+        output: OutputOptions,
     },
     /// Print the full era-year timeline inventory
     Timeline,
@@ -50,20 +59,233 @@ enum Command {
     Locate {
         /// Time query, e.g. "太和三年", "元嘉", "@東晉"
         query: Vec<String>,
+        #[command(flatten)]
+        output: OutputOptions,
+    },
+    /// List the persons recorded at a place, optionally within a period
+    Who {
+        /// Place names, e.g. "荊州"; an event at any of them counts
+        #[arg(required = true)]
+        place: Vec<String>,
+        /// Time query, e.g. "元嘉", "太和元年-太和六年", "@東晉"
+        #[arg(long)]
+        when: Option<String>,
+        #[command(flatten)]
+        output: OutputOptions,
+    },
+    /// Show where each dynasty's dated material ends and its successor's begins
+    Transitions,
+    /// Export every dated reference that carries a month or ganzhi day
+    Calendar,
+    /// Summarize what happened in an era: counts, key events, top actors
+    Era {
+        /// Time query, e.g. "元嘉", "太和元年-太和六年", "@東晉"
+        query: Vec<String>,
+    },
+    /// List in-text persons who have no biography of their own
+    Unknown,
+    /// Cross-check in-text mentions against the persons events name
+    Reconcile {
+        /// Mentions needed to report a person as passive
+        #[arg(long, default_value_t = 10)]
+        min_mentions: usize,
+        /// Most events a passive person may have
+        #[arg(long, default_value_t = 1)]
+        max_events: usize,
+    },
+    /// Rank persons by how many others they share a dated passage with
+    Central {
+        /// Number of persons to output
+        #[arg(long, default_value_t = 30)]
+        top: usize,
+    },
+    /// Rank offices by how often they are conferred, with distinct holders
+    Titles,
+    /// Show one person's chronological career arc
+    Person {
+        /// Person name as it appears in events, e.g. "褚淵"
+        name: Vec<String>,
+        /// Also print the text of the person's own biography
+        #[arg(long)]
+        full: bool,
+        /// Only events dated in or after this AD year
+        #[arg(long, value_name = "AD")]
+        since: Option<u16>,
+        /// Only events dated in or before this AD year
+        #[arg(long, value_name = "AD")]
+        until: Option<u16>,
+        #[command(flatten)]
+        output: OutputOptions,
+    },
+    /// Flatten events.json into a CSV or TSV table for spreadsheets
+    Export {
+        /// csv or tsv
+        #[arg(long, default_value = "csv")]
+        format: String,
+        /// File to write
+        out: PathBuf,
     },
 }
 
+/// How query-type commands print their result on stdout.
+#[derive(clap::Args)]
+struct OutputOptions {
+    /// pretty or compact JSON, or ndjson: one record per line
+    #[arg(long, value_enum, default_value_t = OutputFormat::Pretty)]
+    format: OutputFormat,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum OutputFormat {
+    Pretty,
+    Compact,
+    Ndjson,
+}
+
+/// Tuning flags for the `extract` command.
+#[derive(clap::Args)]
+struct ExtractOptions {
+    /// Extra in-text name blacklist (one name per line)
+    #[arg(long)]
+    blacklist: Option<PathBuf>,
+    /// Directory the JSON files are written to; the query commands
+    /// always read output/
+    #[arg(long, default_value = OUTPUT_DIR)]
+    out: PathBuf,
+    /// Also count every exact occurrence of a known person's name or
+    /// alias as an in-text mention, not only the regex patterns
+    #[arg(long)]
+    match_known_names: bool,
+    /// Record which scanner regex produced each event (`extracted_by`)
+    #[arg(long)]
+    debug_provenance: bool,
+    /// Chars of source text kept on each side of an event match;
+    /// appointments get 4/3 of it and deaths 2/3
+    #[arg(long, default_value_t = event::DEFAULT_CONTEXT_RADIUS)]
+    context_radius: usize,
+    /// Per-kind context radius, e.g. `--kind-context-radius Battle=80`,
+    /// used as is in place of the one scaled from --context-radius
+    #[arg(long, value_name = "KIND=N", value_parser = parse_kind_radius)]
+    kind_context_radius: Vec<(String, usize)>,
+    /// Max chars between 以 and the appointee's name, at most 40; wider
+    /// finds longer title strings at the cost of more false appointments
+    #[arg(
+        long,
+        default_value_t = event::DEFAULT_APPOINTMENT_GAP,
+        value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(0..=40)
+    )]
+    appt_gap: usize,
+    /// Max chars of office after 遷/轉 in a promotion; at least 2
+    #[arg(
+        long,
+        default_value_t = event::DEFAULT_PROMOTION_GAP,
+        value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(2..)
+    )]
+    promo_gap: usize,
+    /// Collapse same-person, same-kind events at most this many chars
+    /// apart into one; 0 keeps every match
+    #[arg(long, default_value_t = event::DEFAULT_MERGE_WINDOW)]
+    merge_window: usize,
+    /// Also write events.jsonl: one compact event per line,
+    /// high-confidence events first
+    #[arg(long)]
+    jsonl: bool,
+    /// Also write a gzipped copy (`*.json.gz`) of each output file
+    #[arg(long)]
+    gzip: bool,
+    /// Run the whole pipeline and print statistics, but write nothing
+    /// to output/
+    #[arg(long)]
+    dry_run: bool,
+    /// Only report output files and errors, no statistics or samples
+    #[arg(long, short, conflicts_with = "verbose")]
+    quiet: bool,
+    /// Longer statistics and sample lists
+    #[arg(long, short)]
+    verbose: bool,
+}
+
+impl ExtractOptions {
+    fn verbosity(&self) -> Verbosity {
+        match (self.quiet, self.verbose) {
+            (true, _) => Verbosity::Quiet,
+            (_, true) => Verbosity::Verbose,
+            _ => Verbosity::Normal,
+        }
+    }
+}
+
+fn parse_kind_radius(raw: &str) -> Result<(String, usize), String> {
+    let (kind, radius) = raw
+        .split_once('=')
+        .ok_or_else(|| format!("expected KIND=N, got '{raw}'"))?;
+    if !event::EventKind::NAMES.contains(&kind) {
+        return Err(format!(
+            "unknown event kind '{kind}'; expected one of {}",
+            event::EventKind::NAMES.join(", ")
+        ));
+    }
+    let radius = radius
+        .parse()
+        .map_err(|_| format!("invalid radius '{radius}' for {kind}"))?;
+    Ok((kind.to_string(), radius))
+}
+
+impl Default for ExtractOptions {
+    fn default() -> Self {
+        Self {
+            blacklist: None,
+            out: PathBuf::from(OUTPUT_DIR),
+            match_known_names: false,
+            debug_provenance: false,
+            context_radius: event::DEFAULT_CONTEXT_RADIUS,
+            kind_context_radius: Vec::new(),
+            appt_gap: event::DEFAULT_APPOINTMENT_GAP,
+            promo_gap: event::DEFAULT_PROMOTION_GAP,
+            merge_window: event::DEFAULT_MERGE_WINDOW,
+            jsonl: false,
+            gzip: false,
+            dry_run: false,
+            quiet: false,
+            verbose: false,
+        }
+    }
+}
+
 fn main() {
     let cli = Cli::parse();
 
     match cli.command {
-        Some(Command::Extract { corpus }) => run_extract(&corpus),
-        Some(Command::Query { query }) => run_query(&query),
+        Some(Command::Extract { corpus, options }) => run_extract(&corpus, &options),
+        Some(Command::Query { query, output }) => run_query(&query, output.format),
         Some(Command::Timeline) => run_timeline(),
         Some(Command::Text { query }) => run_text(&query),
-        Some(Command::Locate { query }) => run_locate(&query),
+        Some(Command::Locate { query, output }) => run_locate(&query, output.format),
+        Some(Command::Who {
+            place,
+            when,
+            output,
+        }) => run_who(&place, when.as_deref(), output.format),
+        Some(Command::Transitions) => run_transitions(),
+        Some(Command::Calendar) => run_calendar(),
+        Some(Command::Era { query }) => run_era(&query),
+        Some(Command::Unknown) => run_unknown(),
+        Some(Command::Reconcile {
+            min_mentions,
+            max_events,
+        }) => run_reconcile(min_mentions, max_events),
+        Some(Command::Central { top }) => run_central(top),
+        Some(Command::Titles) => run_titles(),
+        Some(Command::Person {
+            name,
+            full,
+            since,
+            until,
+            output,
+        }) => run_person(&name, full, since, until, output.format),
+        Some(Command::Export { format, out }) => run_export(&format, &out),
         // Default: extract from current directory
-        None => run_extract(Path::new(".")),
+        None => run_extract(&[PathBuf::from(".")], &ExtractOptions::default()),
     }
 }
 
@@ -71,24 +293,264 @@ fn main() {
 //  OUTPUT FILE HELPERS
 // ═══════════════════════════════════════════════════════════════════════
 
-fn output_path(name: &str) -> PathBuf {
-    Path::new(OUTPUT_DIR).join(name)
-}
-
-fn write_json<T: serde::Serialize>(name: &str, data: &T) {
-    let path = output_path(name);
+/// Write JSON via a temp file + rename so readers never see a half-written
+/// file. Returns the path written.
+fn write_json<T: serde::Serialize>(dir: &Path, name: &str, data: &T) -> PathBuf {
+    let path = dir.join(name);
     let json = serde_json::to_string_pretty(data).expect("JSON serialization failed");
-    std::fs::write(&path, &json).unwrap_or_else(|e| panic!("cannot write {}: {e}", path.display()));
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, &json).unwrap_or_else(|e| panic!("cannot write {}: {e}", tmp.display()));
+    std::fs::rename(&tmp, &path)
+        .unwrap_or_else(|e| panic!("cannot rename into {}: {e}", path.display()));
     eprintln!("  {} ({} bytes)", path.display(), json.len());
+    path
+}
+
+/// Write one compact JSON value per line (JSON Lines), via a temp file +
+/// rename like `write_json`, for consumers that stream records.
+fn write_jsonl<'a, T: serde::Serialize + 'a>(
+    dir: &Path,
+    name: &str,
+    items: impl IntoIterator<Item = &'a T>,
+) -> PathBuf {
+    use std::io::Write;
+    let path = dir.join(name);
+    let tmp = path.with_extension("jsonl.tmp");
+    let result = std::fs::File::create(&tmp).and_then(|f| {
+        let mut out = std::io::BufWriter::new(f);
+        for item in items {
+            serde_json::to_writer(&mut out, item)?;
+            out.write_all(b"\n")?;
+        }
+        out.flush()
+    });
+    result.unwrap_or_else(|e| panic!("cannot write {}: {e}", tmp.display()));
+    std::fs::rename(&tmp, &path)
+        .unwrap_or_else(|e| panic!("cannot rename into {}: {e}", path.display()));
+    let len = std::fs::metadata(&path).map_or(0, |m| m.len());
+    eprintln!("  {} ({} bytes)", path.display(), len);
+    path
+}
+
+/// Writes a JSON object field by field, arrays one element at a time,
+/// straight to `out`: peak memory is one element rather than the whole
+/// document as a `String`. The bytes match `serde_json::to_string_pretty`
+/// (or `to_string` when not `pretty`) of the equivalent struct.
+struct JsonObjectWriter<W: std::io::Write> {
+    out: W,
+    pretty: bool,
+    fields: usize,
+}
+
+impl<W: std::io::Write> JsonObjectWriter<W> {
+    fn new(mut out: W, pretty: bool) -> std::io::Result<Self> {
+        out.write_all(b"{")?;
+        Ok(JsonObjectWriter {
+            out,
+            pretty,
+            fields: 0,
+        })
+    }
+
+    fn field<T: serde::Serialize + ?Sized>(&mut self, key: &str, value: &T) -> std::io::Result<()> {
+        self.key(key)?;
+        self.value(value, 1)
+    }
+
+    fn array<T: serde::Serialize>(
+        &mut self,
+        key: &str,
+        items: impl IntoIterator<Item = T>,
+    ) -> std::io::Result<()> {
+        self.key(key)?;
+        self.out.write_all(b"[")?;
+        let mut count = 0;
+        for item in items {
+            if count > 0 {
+                self.out.write_all(b",")?;
+            }
+            if self.pretty {
+                self.out.write_all(b"\n    ")?;
+            }
+            self.value(&item, 2)?;
+            count += 1;
+        }
+        if self.pretty && count > 0 {
+            self.out.write_all(b"\n  ")?;
+        }
+        self.out.write_all(b"]")
+    }
+
+    fn finish(mut self) -> std::io::Result<W> {
+        if self.pretty && self.fields > 0 {
+            self.out.write_all(b"\n")?;
+        }
+        self.out.write_all(b"}")?;
+        Ok(self.out)
+    }
+
+    fn key(&mut self, key: &str) -> std::io::Result<()> {
+        if self.fields > 0 {
+            self.out.write_all(b",")?;
+        }
+        if self.pretty {
+            self.out.write_all(b"\n  ")?;
+        }
+        serde_json::to_writer(&mut self.out, key)?;
+        self.out.write_all(if self.pretty { b": " } else { b":" })?;
+        self.fields += 1;
+        Ok(())
+    }
+
+    /// One value at nesting `depth`. Pretty output is rendered per value
+    /// and re-indented; JSON strings escape newlines, so every raw newline
+    /// is a line break.
+    fn value<T: serde::Serialize + ?Sized>(
+        &mut self,
+        value: &T,
+        depth: usize,
+    ) -> std::io::Result<()> {
+        if !self.pretty {
+            return Ok(serde_json::to_writer(&mut self.out, value)?);
+        }
+        let rendered = serde_json::to_vec_pretty(value)?;
+        let indent = "  ".repeat(depth);
+        for (i, line) in rendered.split(|&b| b == b'\n').enumerate() {
+            if i > 0 {
+                self.out.write_all(b"\n")?;
+                self.out.write_all(indent.as_bytes())?;
+            }
+            self.out.write_all(line)?;
+        }
+        Ok(())
+    }
+}
+
+/// Stream a JSON object into the output directory with `JsonObjectWriter`,
+/// via a temp file + rename like `write_json`. For the largest outputs.
+fn write_json_streamed(
+    dir: &Path,
+    name: &str,
+    write: impl FnOnce(&mut JsonObjectWriter<std::io::BufWriter<std::fs::File>>) -> std::io::Result<()>,
+) -> PathBuf {
+    use std::io::Write;
+    let path = dir.join(name);
+    let tmp = path.with_extension("json.tmp");
+    let result = std::fs::File::create(&tmp).and_then(|f| {
+        let mut writer = JsonObjectWriter::new(std::io::BufWriter::new(f), true)?;
+        write(&mut writer)?;
+        writer.finish()?.flush()
+    });
+    result.unwrap_or_else(|e| panic!("cannot write {}: {e}", tmp.display()));
+    std::fs::rename(&tmp, &path)
+        .unwrap_or_else(|e| panic!("cannot rename into {}: {e}", path.display()));
+    let len = std::fs::metadata(&path).map_or(0, |m| m.len());
+    eprintln!("  {} ({} bytes)", path.display(), len);
+    path
+}
+
+/// Write a gzip copy of `path` next to it (`events.json` →
+/// `events.json.gz`), for static hosting with `Content-Encoding: gzip`.
+/// Streams the file through the encoder rather than reading it whole.
+fn gzip_file(path: &Path) -> std::io::Result<PathBuf> {
+    let gz_path = PathBuf::from(format!("{}.gz", path.display()));
+    let tmp = PathBuf::from(format!("{}.gz.tmp", path.display()));
+    let mut encoder =
+        flate2::write::GzEncoder::new(std::fs::File::create(&tmp)?, flate2::Compression::best());
+    std::io::copy(&mut std::fs::File::open(path)?, &mut encoder)?;
+    encoder.finish()?;
+    std::fs::rename(&tmp, &gz_path)?;
+    Ok(gz_path)
+}
+
+/// Exclusive lock on an output directory, held for the duration of an
+/// extraction run. The lockfile is removed when the guard is dropped.
+struct OutputLock {
+    path: PathBuf,
+}
+
+impl OutputLock {
+    const FILE_NAME: &'static str = ".lock";
+
+    fn acquire(dir: &Path) -> Result<Self, String> {
+        let path = dir.join(Self::FILE_NAME);
+        match std::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+        {
+            Ok(mut f) => {
+                use std::io::Write;
+                let _ = writeln!(f, "{}", std::process::id());
+                Ok(OutputLock { path })
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => Err(format!(
+                "{} is locked by another extraction run ({}); remove it if that run is gone",
+                dir.display(),
+                path.display()
+            )),
+            Err(e) => Err(format!("cannot create {}: {e}", path.display())),
+        }
+    }
+}
+
+impl Drop for OutputLock {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// Layout version of persons.json, events.json and timeline.json. Bump it
+/// when a change would break older readers; added fields with serde
+/// defaults do not need a bump, since unknown fields are ignored.
+const SCHEMA_VERSION: u32 = 1;
+
+/// The `schema_version` field of an output file. Files written before it
+/// was introduced have none and count as version 1.
+#[derive(serde::Deserialize)]
+struct SchemaProbe {
+    #[serde(default = "first_schema_version")]
+    schema_version: u32,
+}
+
+fn first_schema_version() -> u32 {
+    1
+}
+
+/// What to tell the user when `file` has schema `found` instead of
+/// `SCHEMA_VERSION`, or None when they agree.
+fn schema_mismatch(file: &str, found: u32) -> Option<String> {
+    match found.cmp(&SCHEMA_VERSION) {
+        std::cmp::Ordering::Equal => None,
+        std::cmp::Ordering::Less => Some(format!(
+            "{file} has schema version {found}, older than this binary's {SCHEMA_VERSION}. \
+             Re-run extraction."
+        )),
+        std::cmp::Ordering::Greater => Some(format!(
+            "{file} has schema version {found}, newer than this binary's {SCHEMA_VERSION}. \
+             Upgrade person_extract."
+        )),
+    }
 }
 
 fn read_json<T: serde::de::DeserializeOwned>(name: &str) -> T {
-    let path = output_path(name);
+    read_json_in(Path::new(OUTPUT_DIR), name)
+}
+
+fn read_json_in<T: serde::de::DeserializeOwned>(dir: &Path, name: &str) -> T {
+    let path = dir.join(name);
     let json = std::fs::read_to_string(&path).unwrap_or_else(|e| {
         eprintln!("Cannot read {}: {e}", path.display());
         eprintln!("Run extraction first (without --query) to generate the index.");
         std::process::exit(1);
     });
+    // Arrays (locations.json) carry no version
+    if let Ok(probe) = serde_json::from_str::<SchemaProbe>(&json)
+        && let Some(msg) = schema_mismatch(&path.display().to_string(), probe.schema_version)
+    {
+        eprintln!("{msg}");
+        std::process::exit(1);
+    }
     serde_json::from_str(&json).unwrap_or_else(|e| {
         eprintln!("Cannot parse {}: {e}", path.display());
         eprintln!("The JSON may be from an older format. Re-run extraction.");
@@ -96,6 +558,43 @@ fn read_json<T: serde::de::DeserializeOwned>(name: &str) -> T {
     })
 }
 
+/// Render a command's result for stdout. A whole document is a single
+/// record, so ndjson prints it on one line like compact.
+fn render_json<T: serde::Serialize + ?Sized>(data: &T, format: OutputFormat) -> String {
+    match format {
+        OutputFormat::Pretty => serde_json::to_string_pretty(data),
+        OutputFormat::Compact | OutputFormat::Ndjson => serde_json::to_string(data),
+    }
+    .expect("JSON serialization")
+}
+
+/// One compact JSON record per line.
+fn render_ndjson<R: serde::Serialize>(records: &[R]) -> String {
+    records
+        .iter()
+        .map(|r| serde_json::to_string(r).expect("JSON serialization"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn print_json<T: serde::Serialize + ?Sized>(data: &T, format: OutputFormat) {
+    println!("{}", render_json(data, format));
+}
+
+/// Print a query-type command's result: the whole document, or under
+/// `--format ndjson` its `records` (events, entries) one per line.
+fn print_output<T: serde::Serialize, R: serde::Serialize>(
+    data: &T,
+    records: &[R],
+    format: OutputFormat,
+) {
+    match format {
+        OutputFormat::Ndjson if records.is_empty() => {}
+        OutputFormat::Ndjson => println!("{}", render_ndjson(records)),
+        _ => print_json(data, format),
+    }
+}
+
 // ═══════════════════════════════════════════════════════════════════════
 //  TIMELINE MODE: print the era-year inventory to stdout
 // ═══════════════════════════════════════════════════════════════════════
@@ -103,7 +602,6 @@ fn read_json<T: serde::de::DeserializeOwned>(name: &str) -> T {
 #[derive(serde::Deserialize)]
 struct TimelineFile {
     timeline: event::Timeline,
-    #[allow(dead_code)]
     time_index: event::TimeIndex,
     #[allow(dead_code)]
     stats: event::EventStats,
@@ -179,54 +677,101 @@ fn run_timeline() {
 #[derive(serde::Deserialize)]
 struct EventsFile {
     events: Vec<event::Event>,
-    #[allow(dead_code)]
     unstructured_events: Vec<event::Event>,
 }
 
-fn run_query(query_args: &[String]) {
-    let raw = query_args.join(" ");
+/// Extracted output loaded back for querying. The query, locate, who and
+/// person commands are thin wrappers that load one, call its method and
+/// print the typed result as JSON.
+struct Corpus {
+    /// Empty unless loaded with `CorpusFiles::timeline`
+    time_index: event::TimeIndex,
+    /// High-confidence events
+    events: Vec<event::Event>,
+    unstructured_events: Vec<event::Event>,
+    /// Empty unless loaded with `CorpusFiles::persons`
+    persons: Vec<PersonSummary>,
+}
 
-    let timeline_data: TimelineFile = read_json("timeline.json");
-    let events_file: EventsFile = read_json("events.json");
-    let events = events_file.events;
+/// The output files a command reads besides events.json, so a missing
+/// file only stops the commands that use it.
+#[derive(Debug, Clone, Copy, Default)]
+struct CorpusFiles {
+    /// timeline.json, for its time index
+    timeline: bool,
+    persons: bool,
+}
 
-    // Parse query: "太和", "太和三年", "太和元年-太和六年", "太和1-5"
-    let parsed = parse_time_query(&raw);
+impl Corpus {
+    /// Read events.json and the `files` asked for from the output
+    /// directory, exiting with a hint when extraction has not been run.
+    fn load(files: CorpusFiles) -> Self {
+        Self::load_from(Path::new(OUTPUT_DIR), files)
+    }
 
-    let matching_scopes = match &parsed {
-        TimeQuery::Single { era, year } => timeline_data.time_index.query(era, *year),
-        TimeQuery::Range {
-            era,
-            year_from,
-            year_to,
-        } => timeline_data
-            .time_index
-            .query_range(era, *year_from, *year_to),
-        TimeQuery::Regime { regime } => timeline_data.time_index.query_regime(regime),
-        TimeQuery::AdYear { year } => timeline_data
-            .time_index
-            .scopes
-            .iter()
-            .filter(|s| {
-                event::exact_ad_year(&s.time.regime, &s.time.era, s.time.year) == Some(*year)
-            })
-            .collect(),
-        TimeQuery::AdRange { from, to } => timeline_data
-            .time_index
-            .scopes
+    /// `load` from another directory, e.g. one written with `extract --out`.
+    fn load_from(dir: &Path, files: CorpusFiles) -> Self {
+        let events_file: EventsFile = read_json_in(dir, "events.json");
+        let time_index = if files.timeline {
+            read_json_in::<TimelineFile>(dir, "timeline.json").time_index
+        } else {
+            event::TimeIndex {
+                scopes: Vec::new(),
+                era_refs: Vec::new(),
+            }
+        };
+        let persons = if files.persons {
+            read_json_in::<PersonsFile>(dir, "persons.json").persons
+        } else {
+            Vec::new()
+        };
+        Corpus {
+            time_index,
+            events: events_file.events,
+            unstructured_events: events_file.unstructured_events,
+            persons,
+        }
+    }
+
+    /// High-confidence and unstructured events together.
+    fn all_events(&self) -> impl Iterator<Item = &event::Event> {
+        self.events.iter().chain(&self.unstructured_events)
+    }
+
+    /// Time scopes and high-confidence events matching a time expression.
+    fn query(&self, raw: &str) -> QueryResult<'_> {
+        // Several era tokens ("元嘉 永明") → one group per era
+        if let Some(queries) = parse_multi_era_query(raw) {
+            return QueryResult::MultiEra {
+                query: raw.to_string(),
+                groups: query_era_groups(&self.time_index, &self.events, &queries),
+            };
+        }
+
+        // Parse query: "太和", "太和三年", "太和元年-太和六年", "太和1-5"
+        let parsed = parse_time_query(raw);
+        let scopes = scopes_for_query(&self.time_index, &parsed);
+        let events: Vec<&event::Event> = self
+            .events
             .iter()
-            .filter(|s| {
-                event::exact_ad_year(&s.time.regime, &s.time.era, s.time.year)
-                    .is_some_and(|y| y >= *from && y <= *to)
+            .filter(|e| {
+                e.time
+                    .as_ref()
+                    .is_some_and(|t| time_matches_query(t, &parsed))
             })
-            .collect(),
-    };
+            .collect();
+        QueryResult::Single {
+            query: raw.to_string(),
+            scope_count: scopes.len(),
+            event_count: events.len(),
+            scopes,
+            events,
+        }
+    }
 
-    if matching_scopes.is_empty() {
-        eprintln!("No time scopes found for: {raw}");
-        eprintln!("  parsed as: {parsed:?}");
-        // Show available eras
-        let mut eras: Vec<&str> = timeline_data
+    /// Era names with at least one time scope, sorted.
+    fn available_eras(&self) -> Vec<&str> {
+        let mut eras: Vec<&str> = self
             .time_index
             .scopes
             .iter()
@@ -234,49 +779,141 @@ fn run_query(query_args: &[String]) {
             .collect();
         eras.sort();
         eras.dedup();
-        eprintln!("  available eras: {}", eras.join(", "));
-        return;
+        eras
     }
+}
 
-    // Filter events: find events whose time matches the query
-    let matching_events: Vec<&event::Event> = events
-        .iter()
-        .filter(|e| {
-            if let Some(t) = &e.time {
-                time_matches_query(t, &parsed)
-            } else {
-                false
-            }
-        })
-        .collect();
-
-    eprintln!(
-        "Found {} time scope(s), {} event(s) for: {}",
-        matching_scopes.len(),
-        matching_events.len(),
-        raw
-    );
-
-    // Output to stdout
-    #[derive(serde::Serialize)]
-    struct QueryResult<'a> {
+/// Result of `Corpus::query`, serialized as the query command's output.
+#[derive(serde::Serialize)]
+#[serde(untagged)]
+enum QueryResult<'a> {
+    /// Several era tokens: one group per era
+    MultiEra {
+        query: String,
+        groups: Vec<EraGroup<'a>>,
+    },
+    /// One time expression
+    Single {
         query: String,
         scope_count: usize,
         event_count: usize,
         scopes: Vec<&'a event::TimeScope>,
         events: Vec<&'a event::Event>,
+    },
+}
+
+impl<'a> QueryResult<'a> {
+    /// Matched events across all groups, the records of `--format ndjson`.
+    fn events(&self) -> Vec<&'a event::Event> {
+        match self {
+            QueryResult::MultiEra { groups, .. } => groups
+                .iter()
+                .flat_map(|g| g.events.iter().copied())
+                .collect(),
+            QueryResult::Single { events, .. } => events.clone(),
+        }
     }
+}
 
-    let result = QueryResult {
-        query: raw,
-        scope_count: matching_scopes.len(),
-        event_count: matching_events.len(),
-        scopes: matching_scopes,
-        events: matching_events,
-    };
+fn run_query(query_args: &[String], format: OutputFormat) {
+    let raw = query_args.join(" ");
+    let corpus = Corpus::load(CorpusFiles {
+        timeline: true,
+        ..CorpusFiles::default()
+    });
+    let result = corpus.query(&raw);
+
+    match &result {
+        QueryResult::MultiEra { groups, .. } => eprintln!(
+            "Found {} era group(s), {} event(s) for: {}",
+            groups.len(),
+            groups.iter().map(|g| g.event_count).sum::<usize>(),
+            raw
+        ),
+        QueryResult::Single { scope_count: 0, .. } => {
+            eprintln!("No time scopes found for: {raw}");
+            eprintln!("  parsed as: {:?}", parse_time_query(&raw));
+            eprintln!("  available eras: {}", corpus.available_eras().join(", "));
+            return;
+        }
+        QueryResult::Single {
+            scope_count,
+            event_count,
+            ..
+        } => eprintln!("Found {scope_count} time scope(s), {event_count} event(s) for: {raw}"),
+    }
+
+    print_output(&result, &result.events(), format);
+}
+
+/// Results for one era token of a multi-era query.
+#[derive(serde::Serialize)]
+struct EraGroup<'a> {
+    era: String,
+    scope_count: usize,
+    event_count: usize,
+    scopes: Vec<&'a event::TimeScope>,
+    events: Vec<&'a event::Event>,
+}
+
+/// Evaluate each (token, query) pair independently, one group per token.
+fn query_era_groups<'a>(
+    index: &'a event::TimeIndex,
+    events: &'a [event::Event],
+    queries: &[(String, TimeQuery)],
+) -> Vec<EraGroup<'a>> {
+    queries
+        .iter()
+        .map(|(token, parsed)| {
+            let scopes = scopes_for_query(index, parsed);
+            let events: Vec<&event::Event> = events
+                .iter()
+                .filter(|e| {
+                    e.time
+                        .as_ref()
+                        .is_some_and(|t| time_matches_query(t, parsed))
+                })
+                .collect();
+            EraGroup {
+                era: token.clone(),
+                scope_count: scopes.len(),
+                event_count: events.len(),
+                scopes,
+                events,
+            }
+        })
+        .collect()
+}
 
-    let json = serde_json::to_string_pretty(&result).expect("JSON serialization");
-    println!("{json}");
+/// Select the time scopes matching a parsed query.
+fn scopes_for_query<'a>(
+    index: &'a event::TimeIndex,
+    parsed: &TimeQuery,
+) -> Vec<&'a event::TimeScope> {
+    match parsed {
+        TimeQuery::Single { era, year } => index.query(era, *year),
+        TimeQuery::Range {
+            era,
+            year_from,
+            year_to,
+        } => index.query_range(era, *year_from, *year_to),
+        TimeQuery::Regime { regime } => index.query_regime(regime),
+        TimeQuery::AdYear { year } => index
+            .scopes
+            .iter()
+            .filter(|s| {
+                event::exact_ad_year(&s.time.regime, &s.time.era, s.time.year) == Some(*year)
+            })
+            .collect(),
+        TimeQuery::AdRange { from, to } => index
+            .scopes
+            .iter()
+            .filter(|s| {
+                event::exact_ad_year(&s.time.regime, &s.time.era, s.time.year)
+                    .is_some_and(|y| y >= *from && y <= *to)
+            })
+            .collect(),
+    }
 }
 
 // ── Query parsing ───────────────────────────────────────────────────
@@ -360,6 +997,33 @@ fn parse_time_query(raw: &str) -> TimeQuery {
     TimeQuery::Single { era, year }
 }
 
+/// Split a whitespace-separated query into per-era queries, e.g. "元嘉 永明".
+/// Returns None unless there are 2+ distinct tokens that each name a known
+/// era, so single-era and range queries keep their usual parsing.
+fn parse_multi_era_query(raw: &str) -> Option<Vec<(String, TimeQuery)>> {
+    let mut tokens: Vec<&str> = raw.split_whitespace().collect();
+    tokens.dedup();
+    if tokens.len() < 2 {
+        return None;
+    }
+    let mut queries = Vec::new();
+    for token in tokens {
+        if queries.iter().any(|(t, _)| t == token) {
+            continue;
+        }
+        let parsed = parse_time_query(token);
+        let era = match &parsed {
+            TimeQuery::Single { era, .. } | TimeQuery::Range { era, .. } => era,
+            _ => return None,
+        };
+        if !regime::ERA_NAMES.iter().any(|e| e.name == era) {
+            return None;
+        }
+        queries.push((token.to_string(), parsed));
+    }
+    (queries.len() >= 2).then_some(queries)
+}
+
 /// Parse "太和三年" → ("太和", Some(3)), "太和" → ("太和", None), "5" → ("", Some(5))
 fn parse_era_year(raw: &str) -> (String, Option<u8>) {
     let raw = raw.trim().trim_end_matches('年');
@@ -380,22 +1044,33 @@ fn parse_era_year(raw: &str) -> (String, Option<u8>) {
         }
     }
 
+    // A bare era whose last char reads as a number: "建元" is not 建 year 1
+    if regime::is_known_era(raw) {
+        return (raw.to_string(), None);
+    }
+
     // Chinese number suffix: "太和三", "太和十二", "太和元"
     let chars: Vec<char> = raw.chars().collect();
-    for suffix_len in (1..=3).rev() {
-        if chars.len() <= suffix_len {
-            continue;
-        }
-        let suffix: String = chars[chars.len() - suffix_len..].iter().collect();
-        if let Some(y) = parse_cn_year(&suffix) {
+    let splits: Vec<(String, u8)> = (1..=3)
+        .rev()
+        .filter(|&suffix_len| chars.len() > suffix_len)
+        .filter_map(|suffix_len| {
+            let suffix: String = chars[chars.len() - suffix_len..].iter().collect();
             let era: String = chars[..chars.len() - suffix_len].iter().collect();
-            if !era.is_empty() {
-                return (era, Some(y));
-            }
-        }
-    }
+            parse_cn_year(&suffix).map(|y| (era, y))
+        })
+        .collect();
 
-    (raw.to_string(), None)
+    // Prefer the longest known era, as `regime::build_era_regex` does:
+    // "中大通三" is 中大通 year 3, "建元元" is 建元 year 1
+    let known = splits
+        .iter()
+        .filter(|(era, _)| regime::is_known_era(era))
+        .max_by_key(|(era, _)| era.chars().count());
+    match known.or(splits.first()) {
+        Some((era, y)) => (era.clone(), Some(*y)),
+        None => (raw.to_string(), None),
+    }
 }
 
 fn parse_cn_year(s: &str) -> Option<u8> {
@@ -441,34 +1116,7 @@ fn run_text(query_args: &[String]) {
     let timeline_data: TimelineFile = read_json("timeline.json");
     let parsed = parse_time_query(&raw);
 
-    let matching_scopes = match &parsed {
-        TimeQuery::Single { era, year } => timeline_data.time_index.query(era, *year),
-        TimeQuery::Range {
-            era,
-            year_from,
-            year_to,
-        } => timeline_data
-            .time_index
-            .query_range(era, *year_from, *year_to),
-        TimeQuery::Regime { regime } => timeline_data.time_index.query_regime(regime),
-        TimeQuery::AdYear { year } => timeline_data
-            .time_index
-            .scopes
-            .iter()
-            .filter(|s| {
-                event::exact_ad_year(&s.time.regime, &s.time.era, s.time.year) == Some(*year)
-            })
-            .collect(),
-        TimeQuery::AdRange { from, to } => timeline_data
-            .time_index
-            .scopes
-            .iter()
-            .filter(|s| {
-                event::exact_ad_year(&s.time.regime, &s.time.era, s.time.year)
-                    .is_some_and(|y| y >= *from && y <= *to)
-            })
-            .collect(),
-    };
+    let matching_scopes = scopes_for_query(&timeline_data.time_index, &parsed);
 
     if matching_scopes.is_empty() {
         eprintln!("No time scopes found for: {raw}");
@@ -488,7 +1136,7 @@ fn run_text(query_args: &[String]) {
     }
 
     for (file, scopes) in &by_file {
-        let content = match std::fs::read_to_string(file) {
+        let content = match scanner::read_text(Path::new(file)) {
             Ok(c) => c,
             Err(e) => {
                 eprintln!("Cannot read {}: {}", file, e);
@@ -549,187 +1197,232 @@ fn is_stale(last_seen_ad: u16, query_ad: u16) -> bool {
     query_ad.saturating_sub(last_seen_ad) > STALENESS_THRESHOLD_YEARS
 }
 
-fn run_locate(query_args: &[String]) {
-    let raw = query_args.join(" ");
-    let events_file: EventsFile = read_json("events.json");
+impl Corpus {
+    /// Where each person was as of the end of a time expression's period:
+    /// the latest place from an appointment, battle or context, keyed by
+    /// person. Uses all events (high-confidence + unstructured). None when
+    /// no dated event falls in the period.
+    fn locate(&self, raw: &str) -> Option<std::collections::HashMap<String, PersonLocation>> {
+        let parsed = parse_time_query(raw);
+
+        // Pre-compute person frequency across the entire corpus (not just the query window)
+        let person_freq: std::collections::HashMap<&str, usize> = {
+            let mut freq: std::collections::HashMap<&str, usize> = std::collections::HashMap::new();
+            for e in self.all_events() {
+                if let Some(p) = e.person_name() {
+                    *freq.entry(p).or_insert(0) += 1;
+                }
+            }
+            freq
+        };
 
-    // Use all events (high-confidence + unstructured) for locate
-    let mut all_events = events_file.events;
-    all_events.extend(events_file.unstructured_events);
+        // Only process events that have time references
+        let mut timed_events: Vec<&event::Event> =
+            self.all_events().filter(|e| e.time.is_some()).collect();
 
-    let parsed = parse_time_query(&raw);
+        // Sort all events chronologically by approximate AD year
+        timed_events.sort_by_key(|e| (time_sort_key(e.time.as_ref().unwrap()), e.order_key()));
 
-    // Pre-compute person frequency across the entire corpus (not just the query window)
-    let person_freq: std::collections::HashMap<&str, usize> = {
-        let mut freq: std::collections::HashMap<&str, usize> = std::collections::HashMap::new();
-        for e in &all_events {
-            *freq.entry(e.person_name()).or_insert(0) += 1;
+        // Determine the query time range for filtering output
+        let query_max_key: u16 = timed_events
+            .iter()
+            .filter(|e| time_matches_query(e.time.as_ref().unwrap(), &parsed))
+            .map(|e| time_sort_key(e.time.as_ref().unwrap()))
+            .max()?;
+
+        // Walk events in chronological order, building per-person state
+        struct PersonState {
+            location: Option<LocRecord>,
+            last_seen: u16,
+            dead_at: Option<u16>,
         }
-        freq
-    };
-
-    // Only process events that have time references
-    let mut timed_events: Vec<&event::Event> =
-        all_events.iter().filter(|e| e.time.is_some()).collect();
 
-    // Sort all events chronologically by approximate AD year
-    timed_events.sort_by_key(|e| time_sort_key(e.time.as_ref().unwrap()));
-
-    // Determine the query time range for filtering output
-    let query_max_key: Option<u16> = timed_events
-        .iter()
-        .filter(|e| time_matches_query(e.time.as_ref().unwrap(), &parsed))
-        .map(|e| time_sort_key(e.time.as_ref().unwrap()))
-        .max();
-
-    let query_max_key = match query_max_key {
-        Some(k) => k,
-        None => {
-            eprintln!("No events found for: {raw}");
-            return;
+        struct LocRecord {
+            place: String,
+            resolved_id: Option<String>,
+            role: Option<String>,
+            command_over: Vec<String>,
+            as_of: String,
+            ad_year: u16,
+            context: String,
         }
-    };
 
-    // Walk events in chronological order, building per-person state
-    struct PersonState {
-        location: Option<LocRecord>,
-        last_seen: u16,
-        dead_at: Option<u16>,
-    }
+        let mut state: std::collections::HashMap<String, PersonState> =
+            std::collections::HashMap::new();
 
-    struct LocRecord {
-        place: String,
-        role: Option<String>,
-        as_of: String,
-        ad_year: u16,
-        context: String,
-    }
-
-    let mut state: std::collections::HashMap<String, PersonState> =
-        std::collections::HashMap::new();
-
-    for e in &timed_events {
-        let t = e.time.as_ref().unwrap();
-        let key = time_sort_key(t);
+        for e in &timed_events {
+            let t = e.time.as_ref().unwrap();
+            let key = time_sort_key(t);
 
-        // Stop processing events beyond the query cutoff
-        if key > query_max_key {
-            break;
-        }
+            // Stop processing events beyond the query cutoff
+            if key > query_max_key {
+                break;
+            }
 
-        let time_label = format!("{}/{}{}年 (AD{})", t.regime, t.era, t.year, key);
+            let time_label = format!("{}/{}{}年 (AD{})", t.regime, t.era, t.year, key);
 
-        // Extract person name from event
-        let person = match &e.kind {
-            event::EventKind::Appointment { person, .. }
-            | event::EventKind::Battle { person, .. }
-            | event::EventKind::Death { person, .. } => person.clone(),
-        };
+            // Extract person name from event
+            let Some(person) = e.person_name().map(str::to_string) else {
+                continue;
+            };
 
-        let ps = state.entry(person).or_insert(PersonState {
-            location: None,
-            last_seen: key,
-            dead_at: None,
-        });
+            let ps = state.entry(person).or_insert(PersonState {
+                location: None,
+                last_seen: key,
+                dead_at: None,
+            });
 
-        // Update last seen
-        ps.last_seen = key;
+            // Update last seen
+            ps.last_seen = key;
 
-        // Update location from structured place fields
-        let mut has_structured_place = false;
-        match &e.kind {
-            event::EventKind::Appointment {
-                place: Some(place),
-                new_title,
-                ..
-            } => {
-                has_structured_place = true;
-                ps.location = Some(LocRecord {
-                    place: place.name.clone(),
-                    role: Some(new_title.clone()),
-                    as_of: time_label.clone(),
-                    ad_year: key,
-                    context: e.context.clone(),
-                });
+            // 都督X諸軍事: the provinces commanded, beyond the seat
+            let command_over = match &e.kind {
+                event::EventKind::Appointment { new_title, .. } => {
+                    appointment_command(&e.context, new_title)
+                }
+                _ => Vec::new(),
+            };
+
+            // Update location from structured place fields
+            let mut has_structured_place = false;
+            match &e.kind {
+                event::EventKind::Appointment {
+                    place: Some(place),
+                    new_title,
+                    ..
+                } => {
+                    has_structured_place = true;
+                    ps.location = Some(LocRecord {
+                        place: place.name.clone(),
+                        resolved_id: place.resolved_id.clone(),
+                        role: Some(new_title.clone()),
+                        command_over: command_over.clone(),
+                        as_of: time_label.clone(),
+                        ad_year: key,
+                        context: e.context.clone(),
+                    });
+                }
+                event::EventKind::Battle {
+                    target_place: Some(place),
+                    ..
+                } => {
+                    has_structured_place = true;
+                    ps.location = Some(LocRecord {
+                        place: place.name.clone(),
+                        resolved_id: place.resolved_id.clone(),
+                        role: None,
+                        command_over: Vec::new(),
+                        as_of: time_label.clone(),
+                        ad_year: key,
+                        context: e.context.clone(),
+                    });
+                }
+                event::EventKind::Death { .. } => {
+                    ps.dead_at = Some(key);
+                }
+                // Left the post: the last known location no longer holds
+                event::EventKind::TenureEnd {
+                    office_or_place, ..
+                } => {
+                    has_structured_place = true;
+                    if ps
+                        .location
+                        .as_ref()
+                        .is_some_and(|l| office_or_place.contains(&l.place))
+                    {
+                        ps.location = None;
+                    }
+                }
+                _ => {}
             }
-            event::EventKind::Battle {
-                target_place: Some(place),
-                ..
-            } => {
-                has_structured_place = true;
+
+            // Fall back to context locations when no structured place was set
+            if !has_structured_place && let Some(loc) = e.locations.first() {
                 ps.location = Some(LocRecord {
-                    place: place.name.clone(),
-                    role: None,
+                    place: loc.name.clone(),
+                    resolved_id: loc.resolved_id.clone(),
+                    role: loc.role_suffix.clone(),
+                    command_over,
                     as_of: time_label.clone(),
                     ad_year: key,
                     context: e.context.clone(),
                 });
             }
-            event::EventKind::Death { .. } => {
-                ps.dead_at = Some(key);
-            }
-            _ => {}
         }
 
-        // Fall back to context locations when no structured place was set
-        if !has_structured_place && let Some(loc) = e.locations.first() {
-            ps.location = Some(LocRecord {
-                place: loc.name.clone(),
-                role: loc.role_suffix.clone(),
-                as_of: time_label.clone(),
-                ad_year: key,
-                context: e.context.clone(),
-            });
-        }
-    }
+        // Build output: filter to persons with known location, not dead, not stale
+        let mut result: std::collections::HashMap<String, PersonLocation> =
+            std::collections::HashMap::new();
 
-    // Build output: filter to persons with known location, not dead, not stale
-    let mut result: std::collections::HashMap<String, PersonLocation> =
-        std::collections::HashMap::new();
+        for (person, ps) in &state {
+            // Skip persons appearing only once across entire corpus (likely false positives)
+            if person_freq.get(person.as_str()).copied().unwrap_or(0) < 2 {
+                continue;
+            }
 
-    for (person, ps) in &state {
-        // Skip persons appearing only once across entire corpus (likely false positives)
-        if person_freq.get(person.as_str()).copied().unwrap_or(0) < 2 {
-            continue;
-        }
+            // Skip dead persons
+            if let Some(dead_at) = ps.dead_at
+                && dead_at <= query_max_key
+            {
+                continue;
+            }
 
-        // Skip dead persons
-        if let Some(dead_at) = ps.dead_at
-            && dead_at <= query_max_key
-        {
-            continue;
-        }
+            // Skip persons with no known location
+            let loc = match &ps.location {
+                Some(l) => l,
+                None => continue,
+            };
 
-        // Skip persons with no known location
-        let loc = match &ps.location {
-            Some(l) => l,
-            None => continue,
-        };
+            // Skip stale persons
+            if is_stale(ps.last_seen, query_max_key) {
+                continue;
+            }
 
-        // Skip stale persons
-        if is_stale(ps.last_seen, query_max_key) {
-            continue;
+            let status = if loc.ad_year == query_max_key {
+                "current"
+            } else {
+                "last_known"
+            };
+
+            result.insert(
+                person.clone(),
+                PersonLocation {
+                    location: loc.place.clone(),
+                    resolved_id: loc.resolved_id.clone(),
+                    role: loc.role.clone(),
+                    command_over: loc.command_over.clone(),
+                    as_of: loc.as_of.clone(),
+                    as_of_ad: loc.ad_year,
+                    status: status.to_string(),
+                    context: loc.context.clone(),
+                },
+            );
         }
 
-        let status = if loc.ad_year == query_max_key {
-            "current"
-        } else {
-            "last_known"
-        };
-
-        result.insert(
-            person.clone(),
-            PersonLocation {
-                location: loc.place.clone(),
-                role: loc.role.clone(),
-                as_of: loc.as_of.clone(),
-                as_of_ad: loc.ad_year,
-                status: status.to_string(),
-                context: loc.context.clone(),
-            },
-        );
+        Some(result)
     }
+}
 
+/// Provinces commanded by the appointment to `new_title`, read from the
+/// sentence of `context` it sits in: the title itself is often cut short
+/// (使持節、都督南北兗青徐冀), so the whole sentence is parsed.
+fn appointment_command(context: &str, new_title: &str) -> Vec<String> {
+    let Some(at) = context.find(new_title) else {
+        return Vec::new();
+    };
+    let start = context[..at].rfind('。').map_or(0, |i| i + '。'.len_utf8());
+    let end = context[at..].find('。').map_or(context.len(), |i| at + i);
+    titles::command_provinces(&context[start..end])
+}
+
+fn run_locate(query_args: &[String], format: OutputFormat) {
+    let raw = query_args.join(" ");
+    let corpus = Corpus::load(CorpusFiles::default());
+
+    let Some(result) = corpus.locate(&raw) else {
+        eprintln!("No events found for: {raw}");
+        return;
+    };
     if result.is_empty() {
         eprintln!("No person-location mappings found for: {raw}");
         return;
@@ -741,15 +1434,33 @@ fn run_locate(query_args: &[String]) {
         raw
     );
 
-    let json = serde_json::to_string_pretty(&result).expect("JSON serialization");
-    println!("{json}");
+    // ndjson: one line per person, name inlined
+    #[derive(serde::Serialize)]
+    struct Located<'a> {
+        person: &'a str,
+        #[serde(flatten)]
+        location: &'a PersonLocation,
+    }
+    let mut records: Vec<Located> = result
+        .iter()
+        .map(|(person, location)| Located { person, location })
+        .collect();
+    records.sort_by_key(|r| r.person);
+    print_output(&result, &records, format);
 }
 
 #[derive(serde::Serialize)]
 struct PersonLocation {
     location: String,
+    /// Which homonymous prefecture `location` is, when known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    resolved_id: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     role: Option<String>,
+    /// Provinces under a 都督X諸軍事 command held with the post: the
+    /// person's sphere, where `location` is only the seat
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    command_over: Vec<String>,
     as_of: String,
     as_of_ad: u16,
     status: String,
@@ -757,54 +1468,1163 @@ struct PersonLocation {
 }
 
 // ═══════════════════════════════════════════════════════════════════════
-//  EXTRACT MODE: full corpus processing → output/*.json
+//  WHO MODE: persons recorded at a place
 // ═══════════════════════════════════════════════════════════════════════
 
-fn run_extract(root: &Path) {
-    eprintln!("Scanning corpus at: {}", root.display());
+/// A person's presence at a place: their latest role there and every AD
+/// year they are recorded there.
+#[derive(Debug, serde::Serialize)]
+struct PlacePresence<'a> {
+    person: &'a str,
+    /// The place name the latest event matched
+    place: &'a str,
+    /// Office held there (new title of an appointment, or the role suffix
+    /// of a context place), from the latest event
+    #[serde(skip_serializing_if = "Option::is_none")]
+    role: Option<&'a str>,
+    /// `EventKind::name` of the latest event
+    kind: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    as_of_ad: Option<u16>,
+    ad_years: Vec<u16>,
+    event_count: usize,
+    context: &'a str,
+}
 
-    // Phase 1: discover all biography files
-    let bio_files = scanner::scan_corpus(root);
-    eprintln!("Found {} biography/annals files", bio_files.len());
+impl Corpus {
+    /// Persons whose events name any of `places`, in the event itself
+    /// (appointment seat, battle target ...) or its context `locations`.
+    /// With `when`, only events dated in that period count. One record
+    /// per person, keeping their latest role; sorted by that year, then
+    /// name.
+    fn who<'a>(&'a self, places: &'a [String], when: Option<&str>) -> Vec<PlacePresence<'a>> {
+        let parsed = when.map(parse_time_query);
+        let mut matched: Vec<(&event::Event, &str)> = self
+            .all_events()
+            .filter(|e| match (&parsed, &e.time) {
+                (None, _) => true,
+                (Some(q), Some(t)) => time_matches_query(t, q),
+                (Some(_), None) => false,
+            })
+            .filter_map(|e| {
+                let names = e.all_location_names();
+                let place = places.iter().find(|p| names.contains(&p.as_str()))?;
+                Some((e, place.as_str()))
+            })
+            .collect();
+        // Chronological, undated first, so the last event seen is the latest
+        matched.sort_by_key(|(e, _)| (e.time.as_ref().map(time_sort_key), e.order_key()));
 
-    // Phase 2: parse person info from each file
-    let mut persons = Vec::new();
-    let mut failed = Vec::new();
+        let mut by_person: Vec<PlacePresence> = Vec::new();
+        for (e, place) in matched {
+            let Some(person) = e.person_name() else {
+                continue;
+            };
+            let role = match &e.kind {
+                event::EventKind::Appointment { new_title, .. } => Some(new_title.as_str()),
+                _ => e
+                    .locations
+                    .iter()
+                    .find(|l| l.name == place)
+                    .and_then(|l| l.role_suffix.as_deref()),
+            };
+            let ad = e
+                .time
+                .as_ref()
+                .and_then(|t| event::exact_ad_year(&t.regime, &t.era, t.year));
+            let latest = PlacePresence {
+                person,
+                place,
+                role,
+                kind: e.kind.name(),
+                as_of_ad: ad,
+                ad_years: Vec::new(),
+                event_count: 0,
+                context: &e.context,
+            };
+            let i = match by_person.iter().position(|p| p.person == person) {
+                Some(i) => {
+                    let earlier = std::mem::replace(&mut by_person[i], latest);
+                    by_person[i].ad_years = earlier.ad_years;
+                    by_person[i].event_count = earlier.event_count;
+                    i
+                }
+                None => {
+                    by_person.push(latest);
+                    by_person.len() - 1
+                }
+            };
+            let entry = &mut by_person[i];
+            entry.event_count += 1;
+            if let Some(ad) = ad
+                && !entry.ad_years.contains(&ad)
+            {
+                entry.ad_years.push(ad);
+            }
+        }
+        by_person.sort_by(|a, b| a.as_of_ad.cmp(&b.as_of_ad).then(a.person.cmp(b.person)));
+        by_person
+    }
+}
 
-    for bio in &bio_files {
-        match parser::parse_biography(bio) {
-            Some(person) => persons.push(person),
-            None => failed.push(bio.path.display().to_string()),
+fn run_who(places: &[String], when: Option<&str>, format: OutputFormat) {
+    let corpus = Corpus::load(CorpusFiles::default());
+    let result = corpus.who(places, when);
+    let label = places.join("、");
+    match when {
+        Some(w) => eprintln!("{} persons at {label} in {w}", result.len()),
+        None => eprintln!("{} persons at {label}", result.len()),
+    }
+    print_output(&result, &result, format);
+}
+
+// ═══════════════════════════════════════════════════════════════════════
+//  TRANSITIONS MODE: dynastic handovers in AD terms
+// ═══════════════════════════════════════════════════════════════════════
+
+/// One predecessor→successor handover as seen in the corpus.
+struct Transition {
+    from: &'static str,
+    to: &'static str,
+    from_last_ad: u16,
+    to_first_ad: u16,
+    /// AD years with dated material under both regimes
+    overlap: Vec<u16>,
+}
+
+/// AD years observed per regime in the timeline.
+fn regime_ad_years(timeline: &event::Timeline) -> std::collections::HashMap<&str, Vec<u16>> {
+    let mut years: std::collections::HashMap<&str, Vec<u16>> = std::collections::HashMap::new();
+    for rt in &timeline.regimes {
+        let ads = years.entry(rt.regime.as_str()).or_default();
+        for et in &rt.eras {
+            for tp in &et.years {
+                if let Some(ad) = event::exact_ad_year(&rt.regime, &tp.era, tp.year) {
+                    ads.push(ad);
+                }
+            }
+        }
+        ads.sort_unstable();
+        ads.dedup();
+    }
+    years
+}
+
+/// Pair each observed regime with its observed successor (`Regime::successor`).
+fn dynastic_transitions(timeline: &event::Timeline) -> Vec<Transition> {
+    let years = regime_ad_years(timeline);
+    let mut regimes: Vec<regime::Regime> = Vec::new();
+    for e in regime::ERA_NAMES {
+        if !regimes.contains(&e.regime) {
+            regimes.push(e.regime);
         }
     }
+    regimes.sort_by_key(|r| r.start_ad_year());
 
+    regimes
+        .iter()
+        .filter_map(|r| {
+            let next = r.successor()?;
+            let from = years.get(r.as_chinese()).filter(|v| !v.is_empty())?;
+            let to = years.get(next.as_chinese()).filter(|v| !v.is_empty())?;
+            Some(Transition {
+                from: r.as_chinese(),
+                to: next.as_chinese(),
+                from_last_ad: *from.last()?,
+                to_first_ad: *to.first()?,
+                overlap: from.iter().filter(|y| to.contains(y)).copied().collect(),
+            })
+        })
+        .collect()
+}
+
+fn run_transitions() {
+    let data: TimelineFile = read_json("timeline.json");
+    let transitions = dynastic_transitions(&data.timeline);
+    for t in &transitions {
+        let overlap = if t.overlap.is_empty() {
+            String::new()
+        } else {
+            let ads: Vec<String> = t.overlap.iter().map(|y| format!("AD{y}")).collect();
+            format!("  ⚠ both dated in {}", ads.join(", "))
+        };
+        println!(
+            "{} last AD{} → {} first AD{}{}",
+            t.from, t.from_last_ad, t.to, t.to_first_ad, overlap
+        );
+    }
+    eprintln!("\n{} transitions", transitions.len());
+}
+
+// ═══════════════════════════════════════════════════════════════════════
+//  CALENDAR MODE: month/day-bearing time references for calendar work
+// ═══════════════════════════════════════════════════════════════════════
+
+#[derive(serde::Serialize)]
+struct CalendarEntry<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ad_year: Option<u16>,
+    #[serde(flatten)]
+    time: &'a event::TimeRef,
+    source_file: &'a str,
+}
+
+/// Time references with a month or ganzhi day, in AD order (undated
+/// eras last), then month, leap month after its regular month, and
+/// source position.
+fn calendar_entries(index: &event::TimeIndex) -> Vec<CalendarEntry<'_>> {
+    let mut entries: Vec<CalendarEntry> = index
+        .scopes
+        .iter()
+        .filter(|s| s.time.month.is_some() || s.time.day_ganzhi.is_some())
+        .map(|s| CalendarEntry {
+            ad_year: event::exact_ad_year(&s.time.regime, &s.time.era, s.time.year),
+            time: &s.time,
+            source_file: &s.span.file,
+        })
+        .collect();
+    entries.sort_by_key(|e| {
+        (
+            e.ad_year.unwrap_or(u16::MAX),
+            e.time.month,
+            e.time.is_leap,
+            e.time.day_num,
+            e.source_file,
+            e.time.byte_offset,
+        )
+    });
+    entries
+}
+
+fn run_calendar() {
+    let data: TimelineFile = read_json("timeline.json");
+    let entries = calendar_entries(&data.time_index);
+    let with_day = entries
+        .iter()
+        .filter(|e| e.time.day_ganzhi.is_some())
+        .count();
     eprintln!(
-        "Parsed {} persons ({} files could not be parsed)",
-        persons.len(),
-        failed.len()
+        "{} dated references with a month or day ({} with a ganzhi day)",
+        entries.len(),
+        with_day
     );
+    print_json(&entries, OutputFormat::Pretty);
+}
 
-    // Phase 3: compute reference stats and build summaries
-    let summaries: Vec<PersonSummary> = persons.iter().map(PersonSummary::from_person).collect();
+// ═══════════════════════════════════════════════════════════════════════
+//  ERA MODE: structured summary of everything in a time period
+// ═══════════════════════════════════════════════════════════════════════
+
+/// How many persons/places to list in an era summary.
+const ERA_TOP_N: usize = 10;
+
+#[derive(serde::Serialize)]
+struct EraSummary {
+    query: String,
+    event_count: usize,
+    /// Regime/era labels covered, in chronological order
+    eras: Vec<String>,
+    /// Event count per kind (Appointment, Battle, …)
+    kind_counts: std::collections::BTreeMap<&'static str, usize>,
+    appointments: Vec<EraItem>,
+    deaths: Vec<EraItem>,
+    battles: Vec<EraItem>,
+    top_persons: Vec<(String, usize)>,
+    top_places: Vec<(String, usize)>,
+}
+
+/// One event line in an era summary: who, what, when.
+#[derive(serde::Serialize)]
+struct EraItem {
+    person: String,
+    detail: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    time: Option<String>,
+}
+
+fn top_n(counts: std::collections::HashMap<String, usize>) -> Vec<(String, usize)> {
+    let mut v: Vec<(String, usize)> = counts.into_iter().collect();
+    v.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    v.truncate(ERA_TOP_N);
+    v
+}
 
-    // ── Print statistics ───────────────────────────────────────────
-    eprintln!("\n══════════════════════════════════════════");
-    eprintln!("  CORPUS STATISTICS");
-    eprintln!("══════════════════════════════════════════");
+/// Aggregate already-matched events into an `EraSummary`.
+fn summarize_era(query: &str, events: &[&event::Event]) -> EraSummary {
+    let mut kind_counts = std::collections::BTreeMap::new();
+    let mut eras: Vec<(u16, String)> = Vec::new();
+    let mut persons = std::collections::HashMap::new();
+    let mut places = std::collections::HashMap::new();
+    let (mut appointments, mut deaths, mut battles) = (Vec::new(), Vec::new(), Vec::new());
+
+    for e in events {
+        *kind_counts.entry(e.kind.name()).or_insert(0) += 1;
+        if let Some(p) = e.person_name() {
+            *persons.entry(p.to_string()).or_insert(0) += 1;
+        }
+        for place in e.all_location_names() {
+            *places.entry(place.to_string()).or_insert(0) += 1;
+        }
+        let time = e.time.as_ref().map(|t| {
+            let label = format!("{}/{}", t.regime, t.era);
+            if !eras.iter().any(|(_, l)| *l == label) {
+                eras.push((time_sort_key(t), label));
+            }
+            format!("{}/{}{}年", t.regime, t.era, t.year)
+        });
+
+        let person = e.person_name().unwrap_or_default().to_string();
+        match &e.kind {
+            event::EventKind::Appointment { new_title, .. } => appointments.push(EraItem {
+                person,
+                detail: new_title.clone(),
+                time,
+            }),
+            event::EventKind::Death { verb, .. } => deaths.push(EraItem {
+                person,
+                detail: verb.clone(),
+                time,
+            }),
+            event::EventKind::Battle { verb, target, .. } => battles.push(EraItem {
+                person,
+                detail: format!("{verb}{target}"),
+                time,
+            }),
+            _ => {}
+        }
+    }
+    eras.sort();
+
+    EraSummary {
+        query: query.to_string(),
+        event_count: events.len(),
+        eras: eras.into_iter().map(|(_, l)| l).collect(),
+        kind_counts,
+        appointments,
+        deaths,
+        battles,
+        top_persons: top_n(persons),
+        top_places: top_n(places),
+    }
+}
+
+fn run_era(query_args: &[String]) {
+    let raw = query_args.join(" ");
+    let events_file: EventsFile = read_json("events.json");
+    let parsed = parse_time_query(&raw);
+
+    let matched: Vec<&event::Event> = events_file
+        .events
+        .iter()
+        .filter(|e| {
+            e.time
+                .as_ref()
+                .is_some_and(|t| time_matches_query(t, &parsed))
+        })
+        .collect();
+
+    if matched.is_empty() {
+        eprintln!("No events found for: {raw}");
+        eprintln!("  parsed as: {parsed:?}");
+        return;
+    }
+
+    let summary = summarize_era(&raw, &matched);
+    eprintln!(
+        "{}: {} events across {} era(s)",
+        raw,
+        summary.event_count,
+        summary.eras.len()
+    );
+    print_json(&summary, OutputFormat::Pretty);
+}
+
+// ═══════════════════════════════════════════════════════════════════════
+//  UNKNOWN MODE: in-text figures without their own biography
+// ═══════════════════════════════════════════════════════════════════════
+
+/// Deserialization wrapper for the parts of persons.json we read back.
+#[derive(serde::Deserialize)]
+struct PersonsFile {
+    #[serde(default)]
+    persons: Vec<extract::PersonSummary>,
+    in_text_mentions: Vec<intext::InTextPerson>,
+    #[serde(default)]
+    event_persons: Vec<EventPersonEntry>,
+}
+
+/// How many events name a person, as listed in persons.json.
+#[derive(serde::Serialize, serde::Deserialize)]
+struct EventPersonEntry {
+    name: String,
+    event_count: usize,
+}
+
+/// Biographies a name other than the canonical one could refer to, e.g.
+/// the courtesy name 彥回 → 褚淵. One entry per display name.
+fn alias_candidates<'a>(
+    persons: &'a [extract::PersonSummary],
+    query: &str,
+) -> Vec<&'a extract::PersonSummary> {
+    let mut found: Vec<&extract::PersonSummary> = Vec::new();
+    for p in persons {
+        if p.display_name != query
+            && p.aliases.iter().any(|a| a == query)
+            && !found.iter().any(|f| f.display_name == p.display_name)
+        {
+            found.push(p);
+        }
+    }
+    found
+}
+
+/// In-text persons without a biography, most-mentioned first.
+fn unknown_persons(mentions: &[intext::InTextPerson]) -> Vec<&intext::InTextPerson> {
+    let mut unknown: Vec<&intext::InTextPerson> =
+        mentions.iter().filter(|p| !p.has_own_biography).collect();
+    unknown.sort_by(|a, b| {
+        b.mention_count
+            .cmp(&a.mention_count)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    unknown
+}
+
+fn run_unknown() {
+    let persons: PersonsFile = read_json("persons.json");
+    let unknown = unknown_persons(&persons.in_text_mentions);
+    eprintln!(
+        "{} of {} in-text persons have no own biography",
+        unknown.len(),
+        persons.in_text_mentions.len()
+    );
+    print_json(&unknown, OutputFormat::Pretty);
+}
+
+// ═══════════════════════════════════════════════════════════════════════
+//  RECONCILE MODE: in-text mentions against event persons
+// ═══════════════════════════════════════════════════════════════════════
+
+/// One name with its counts on both sides of the join.
+#[derive(serde::Serialize, Debug)]
+struct ReconciledPerson<'a> {
+    name: &'a str,
+    mention_count: usize,
+    event_count: usize,
+}
+
+/// In-text mentions and event persons joined by name.
+#[derive(serde::Serialize, Debug)]
+struct Reconciliation<'a> {
+    /// Often mentioned but seldom acting in an event, most-mentioned first
+    passive: Vec<ReconciledPerson<'a>>,
+    /// Named by events but never found by the mention scan, most events
+    /// first: likely extraction artifacts
+    unmentioned: Vec<ReconciledPerson<'a>>,
+}
+
+/// Join mentions and event persons by name. A person is passive with at
+/// least `min_mentions` mentions and at most `max_events` events.
+fn reconcile<'a>(
+    mentions: &'a [intext::InTextPerson],
+    event_persons: &'a [EventPersonEntry],
+    min_mentions: usize,
+    max_events: usize,
+) -> Reconciliation<'a> {
+    let events: std::collections::HashMap<&str, usize> = event_persons
+        .iter()
+        .map(|p| (p.name.as_str(), p.event_count))
+        .collect();
+    let mentioned: std::collections::HashMap<&str, usize> = mentions
+        .iter()
+        .map(|p| (p.name.as_str(), p.mention_count))
+        .collect();
+
+    let mut passive: Vec<ReconciledPerson> = mentions
+        .iter()
+        .map(|p| ReconciledPerson {
+            name: &p.name,
+            mention_count: p.mention_count,
+            event_count: events.get(p.name.as_str()).copied().unwrap_or(0),
+        })
+        .filter(|p| p.mention_count >= min_mentions && p.event_count <= max_events)
+        .collect();
+    passive.sort_by(|a, b| {
+        b.mention_count
+            .cmp(&a.mention_count)
+            .then_with(|| a.name.cmp(b.name))
+    });
+
+    let mut unmentioned: Vec<ReconciledPerson> = event_persons
+        .iter()
+        .filter(|p| !mentioned.contains_key(p.name.as_str()))
+        .map(|p| ReconciledPerson {
+            name: &p.name,
+            mention_count: 0,
+            event_count: p.event_count,
+        })
+        .collect();
+    unmentioned.sort_by(|a, b| {
+        b.event_count
+            .cmp(&a.event_count)
+            .then_with(|| a.name.cmp(b.name))
+    });
+
+    Reconciliation {
+        passive,
+        unmentioned,
+    }
+}
+
+fn run_reconcile(min_mentions: usize, max_events: usize) {
+    let persons: PersonsFile = read_json("persons.json");
+    let report = reconcile(
+        &persons.in_text_mentions,
+        &persons.event_persons,
+        min_mentions,
+        max_events,
+    );
+    eprintln!(
+        "{} passive persons, {} of {} event persons never mentioned",
+        report.passive.len(),
+        report.unmentioned.len(),
+        persons.event_persons.len()
+    );
+    print_json(&report, OutputFormat::Pretty);
+}
+
+// ═══════════════════════════════════════════════════════════════════════
+//  CENTRAL MODE: degree centrality in the person co-occurrence graph
+// ═══════════════════════════════════════════════════════════════════════
+
+/// One person's place in the co-occurrence graph.
+#[derive(serde::Serialize, Debug)]
+struct Centrality {
+    name: String,
+    /// Distinct persons co-occurring with this one
+    degree: usize,
+    /// Total co-occurrences, counting each shared passage once per pair
+    weighted_degree: usize,
+}
+
+/// Person co-occurrence graph: two persons are linked once for every time
+/// scope (the passage governed by one dated reference, see
+/// `Event::scope_index`) in which both have events. Keys are ordered pairs.
+fn cooccurrence(events: &[event::Event]) -> std::collections::BTreeMap<(&str, &str), usize> {
+    let mut by_scope: std::collections::BTreeMap<usize, Vec<&str>> =
+        std::collections::BTreeMap::new();
+    for e in events {
+        let Some(scope) = e.scope_index else {
+            continue;
+        };
+        let persons = by_scope.entry(scope).or_default();
+        for p in e.participants() {
+            if !persons.contains(&p) {
+                persons.push(p);
+            }
+        }
+    }
+    let mut edges = std::collections::BTreeMap::new();
+    for persons in by_scope.values() {
+        for (i, a) in persons.iter().enumerate() {
+            for b in &persons[i + 1..] {
+                let key = if a < b { (*a, *b) } else { (*b, *a) };
+                *edges.entry(key).or_insert(0) += 1;
+            }
+        }
+    }
+    edges
+}
+
+/// Persons ranked by degree, then weighted degree, then name.
+fn degree_centrality(events: &[event::Event]) -> Vec<Centrality> {
+    let mut nodes: std::collections::HashMap<&str, (usize, usize)> =
+        std::collections::HashMap::new();
+    for ((a, b), w) in cooccurrence(events) {
+        for p in [a, b] {
+            let n = nodes.entry(p).or_default();
+            n.0 += 1;
+            n.1 += w;
+        }
+    }
+    let mut ranked: Vec<Centrality> = nodes
+        .into_iter()
+        .map(|(name, (degree, weighted_degree))| Centrality {
+            name: name.to_string(),
+            degree,
+            weighted_degree,
+        })
+        .collect();
+    ranked.sort_by(|a, b| {
+        b.degree
+            .cmp(&a.degree)
+            .then_with(|| b.weighted_degree.cmp(&a.weighted_degree))
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    ranked
+}
+
+fn run_central(top: usize) {
+    let events_file: EventsFile = read_json("events.json");
+    let mut all_events = events_file.events;
+    all_events.extend(events_file.unstructured_events);
+
+    let mut ranked = degree_centrality(&all_events);
+    eprintln!("{} persons with at least one co-occurrence", ranked.len());
+    ranked.truncate(top);
+    print_json(&ranked, OutputFormat::Pretty);
+}
+
+// ═══════════════════════════════════════════════════════════════════════
+//  TITLES MODE: office frequencies across all appointments
+// ═══════════════════════════════════════════════════════════════════════
+
+/// How often one class of office (see `titles::office_class`) is conferred.
+#[derive(serde::Serialize, Debug)]
+struct TitleCount {
+    title: &'static str,
+    /// Appointments conferring it, a multi-office appointment
+    /// (龍驤將軍、秦州刺史) counting once for each office
+    appointments: usize,
+    /// Distinct persons appointed to it
+    holders: usize,
+}
+
+/// Offices ranked by appointments, then holders, then title.
+fn title_frequencies<'a>(events: impl IntoIterator<Item = &'a event::Event>) -> Vec<TitleCount> {
+    let mut by_title: std::collections::HashMap<
+        &'static str,
+        (usize, std::collections::HashSet<&str>),
+    > = std::collections::HashMap::new();
+    for e in events {
+        let event::EventKind::Appointment {
+            person, new_title, ..
+        } = &e.kind
+        else {
+            continue;
+        };
+        for office in new_title.split('、') {
+            if let Some(class) = titles::office_class(office) {
+                let (count, holders) = by_title.entry(class).or_default();
+                *count += 1;
+                holders.insert(person);
+            }
+        }
+    }
+    let mut ranked: Vec<TitleCount> = by_title
+        .into_iter()
+        .map(|(title, (appointments, holders))| TitleCount {
+            title,
+            appointments,
+            holders: holders.len(),
+        })
+        .collect();
+    ranked.sort_by(|a, b| {
+        b.appointments
+            .cmp(&a.appointments)
+            .then_with(|| b.holders.cmp(&a.holders))
+            .then_with(|| a.title.cmp(b.title))
+    });
+    ranked
+}
+
+fn run_titles() {
+    let events_file: EventsFile = read_json("events.json");
+    let ranked = title_frequencies(
+        events_file
+            .events
+            .iter()
+            .chain(&events_file.unstructured_events),
+    );
+    eprintln!(
+        "{} offices across {} appointments",
+        ranked.len(),
+        ranked.iter().map(|t| t.appointments).sum::<usize>()
+    );
+    print_json(&ranked, OutputFormat::Pretty);
+}
+
+// ═══════════════════════════════════════════════════════════════════════
+//  PERSON MODE: one person's events as a chronological career arc
+// ═══════════════════════════════════════════════════════════════════════
+
+/// One regime's share of a person's timed events.
+#[derive(Debug, serde::Serialize)]
+struct RegimeShare {
+    regime: String,
+    event_count: usize,
+    share: f32,
+}
+
+#[derive(serde::Serialize)]
+struct PersonTimeline<'a> {
+    name: String,
+    event_count: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    primary_regime: Option<String>,
+    regimes: Vec<RegimeShare>,
+    entries: Vec<Entry<'a>>,
+    /// Own biography text, with `--full`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    biography: Option<String>,
+}
+
+#[derive(serde::Serialize)]
+struct Entry<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ad_year: Option<u16>,
+    /// Approximate ISO date, see `TimeRef::to_iso_approx`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    iso_date: Option<String>,
+    #[serde(flatten)]
+    event: &'a event::Event,
+}
+
+/// Weigh a person's timed events by regime. Persons who served across a
+/// dynastic transition get one share per regime; the primary regime is
+/// the one with the most events (earlier regime wins ties).
+fn regime_affiliation(events: &[&event::Event]) -> (Option<String>, Vec<RegimeShare>) {
+    let mut counts: Vec<(String, usize)> = Vec::new();
+    for t in events.iter().filter_map(|e| e.time.as_ref()) {
+        match counts.iter_mut().find(|(r, _)| *r == t.regime) {
+            Some((_, c)) => *c += 1,
+            None => counts.push((t.regime.clone(), 1)),
+        }
+    }
+    let total: usize = counts.iter().map(|(_, c)| c).sum();
+
+    let start_year = |regime: &str| {
+        regime::ERA_NAMES
+            .iter()
+            .find(|e| e.regime.as_chinese() == regime)
+            .map(|e| e.regime.start_ad_year())
+            .unwrap_or(u16::MAX)
+    };
+    counts.sort_by_key(|(r, c)| (std::cmp::Reverse(*c), start_year(r)));
+
+    let shares: Vec<RegimeShare> = counts
+        .into_iter()
+        .map(|(regime, event_count)| RegimeShare {
+            regime,
+            event_count,
+            share: event_count as f32 / total as f32,
+        })
+        .collect();
+    (shares.first().map(|s| s.regime.clone()), shares)
+}
+
+/// Text of `name`'s own biography, read from the file persons.json
+/// records for it. None for people known only from mentions in other
+/// biographies, or when the file is no longer readable.
+fn biography_text(persons: &[PersonSummary], name: &str) -> Option<String> {
+    let p = persons.iter().find(|p| p.display_name == name)?;
+    match scanner::read_text(Path::new(&p.file)) {
+        Ok(text) => Some(text),
+        Err(e) => {
+            eprintln!("Cannot read biography {}: {e}", p.file);
+            None
+        }
+    }
+}
+
+/// Why `Corpus::person_timeline` has no timeline to show.
+enum PersonLookupError<'a> {
+    /// The name is an alias of several biographies
+    Ambiguous(Vec<&'a PersonSummary>),
+    /// No events under the name, or under the biography it is an alias of
+    NoEvents(String),
+}
+
+impl Corpus {
+    /// A person's events as a chronological career arc. `query` may be a
+    /// courtesy or given name, resolved through persons.json aliases when
+    /// no event carries it. With `full`, includes the own biography text.
+    fn person_timeline(
+        &self,
+        query: &str,
+        full: bool,
+    ) -> Result<PersonTimeline<'_>, PersonLookupError<'_>> {
+        // Events are keyed by full name; map a courtesy or given name onto it
+        let mut name = query;
+        if !self.all_events().any(|e| e.person_name() == Some(name)) {
+            match alias_candidates(&self.persons, name).as_slice() {
+                [] => {}
+                [p] => name = &p.display_name,
+                many => return Err(PersonLookupError::Ambiguous(many.to_vec())),
+            }
+        }
+
+        let mut matched: Vec<&event::Event> = self
+            .all_events()
+            .filter(|e| e.person_name() == Some(name))
+            .collect();
+        if matched.is_empty() {
+            return Err(PersonLookupError::NoEvents(name.to_string()));
+        }
+
+        // Timed events first, in AD order; untimed ones in source order at the end
+        matched.sort_by_key(|e| {
+            let ad = e.time.as_ref().map(time_sort_key).unwrap_or(u16::MAX);
+            (ad, e.order_key())
+        });
+
+        let (primary_regime, regimes) = regime_affiliation(&matched);
+        let entries: Vec<Entry> = matched
+            .iter()
+            .map(|e| Entry {
+                ad_year: e
+                    .time
+                    .as_ref()
+                    .and_then(|t| event::exact_ad_year(&t.regime, &t.era, t.year)),
+                iso_date: e.time.as_ref().and_then(|t| t.to_iso_approx()),
+                event: e,
+            })
+            .collect();
+
+        Ok(PersonTimeline {
+            name: name.to_string(),
+            event_count: entries.len(),
+            primary_regime,
+            regimes,
+            entries,
+            biography: full.then(|| biography_text(&self.persons, name)).flatten(),
+        })
+    }
+}
+
+impl PersonTimeline<'_> {
+    /// Keep the entries dated within `since..=until` (either end open).
+    /// With a window, undated entries go too, as nothing places them in
+    /// it; the count and regime shares are redone for what is left.
+    fn retain_years(&mut self, since: Option<u16>, until: Option<u16>) {
+        if since.is_none() && until.is_none() {
+            return;
+        }
+        self.entries.retain(|e| {
+            e.ad_year.is_some_and(|ad| {
+                since.is_none_or(|lo| ad >= lo) && until.is_none_or(|hi| ad <= hi)
+            })
+        });
+        let events: Vec<&event::Event> = self.entries.iter().map(|e| e.event).collect();
+        (self.primary_regime, self.regimes) = regime_affiliation(&events);
+        self.event_count = self.entries.len();
+    }
+}
+
+/// Birth year from a dated death and the age given for it. Ages are
+/// counted in 歲, one at birth and one more each new year, so someone who
+/// died aged 72 in AD 500 was born around AD 429.
+fn birth_ad_year(death_ad: u16, age: u16) -> u16 {
+    (death_ad + 1).saturating_sub(age)
+}
+
+fn run_person(
+    name_args: &[String],
+    full: bool,
+    since: Option<u16>,
+    until: Option<u16>,
+    format: OutputFormat,
+) {
+    let query = name_args.join("");
+    let corpus = Corpus::load(CorpusFiles {
+        persons: true,
+        ..CorpusFiles::default()
+    });
+
+    let mut result = match corpus.person_timeline(&query, full) {
+        Ok(timeline) => timeline,
+        Err(PersonLookupError::Ambiguous(many)) => {
+            eprintln!("{query} is ambiguous:");
+            for p in many {
+                eprintln!("  {} ({} {})", p.display_name, p.book, p.file);
+            }
+            return;
+        }
+        Err(PersonLookupError::NoEvents(name)) => {
+            eprintln!("No events found for person: {name}");
+            return;
+        }
+    };
+    let timed = result
+        .entries
+        .iter()
+        .filter(|e| e.ad_year.is_some())
+        .count();
+    result.retain_years(since, until);
+    let name = &result.name;
+    if timed > 0 && result.entries.is_empty() {
+        eprintln!("No dated events of {name} fall in the requested years ({timed} outside)");
+    }
+
+    if *name != query {
+        let courtesy = corpus
+            .persons
+            .iter()
+            .find(|p| p.display_name == *name)
+            .and_then(|p| p.courtesy_name.as_deref());
+        match courtesy {
+            Some(c) if c == query => eprintln!("显示 {name}（字{c}）"),
+            _ => eprintln!("显示 {name}（{query}）"),
+        }
+    }
+
+    let ad_years: Vec<u16> = result.entries.iter().filter_map(|e| e.ad_year).collect();
+    match (ad_years.iter().min(), ad_years.iter().max()) {
+        (Some(lo), Some(hi)) => eprintln!("{name}: {} events, AD{lo}–AD{hi}", result.event_count),
+        _ => eprintln!("{name}: {} events, no dated events", result.event_count),
+    }
+    let dated_death = result.entries.iter().find_map(|e| match &e.event.kind {
+        event::EventKind::Death {
+            age_at_death: Some(age),
+            ..
+        } => Some((e.ad_year?, *age)),
+        _ => None,
+    });
+    if let Some((died, age)) = dated_death {
+        eprintln!(
+            "  born c. AD{} (died AD{died} aged {age})",
+            birth_ad_year(died, age)
+        );
+    }
+    for r in &result.regimes {
+        eprintln!(
+            "  {}: {} events ({:.0}%)",
+            r.regime,
+            r.event_count,
+            r.share * 100.0
+        );
+    }
+    if full && result.biography.is_none() {
+        eprintln!("{name} has no biography of their own (mentioned in other texts only)");
+    }
+
+    print_output(&result, &result.entries, format);
+}
+
+// ═══════════════════════════════════════════════════════════════════════
+//  EXPORT MODE: events.json flattened to CSV/TSV
+// ═══════════════════════════════════════════════════════════════════════
+
+const EXPORT_COLUMNS: &[&str] = &[
+    "person",
+    "kind",
+    "verb",
+    "new_title",
+    "target",
+    "place",
+    "is_qiao",
+    "regime",
+    "era",
+    "year",
+    "ad_year",
+    "source_file",
+    "byte_offset",
+];
+
+/// Field separator for an `export --format`.
+fn export_separator(format: &str) -> Option<char> {
+    match format {
+        "csv" => Some(','),
+        "tsv" => Some('\t'),
+        _ => None,
+    }
+}
+
+/// One cell, escaped for `sep`: CSV quotes a field holding the separator,
+/// a quote or a line break (RFC 4180); TSV has no quoting, so tabs and
+/// line breaks become spaces.
+fn export_cell(value: &str, sep: char) -> String {
+    if sep == '\t' {
+        value.replace(['\t', '\n', '\r'], " ")
+    } else if value.contains([sep, '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// The verb, new title and target columns of an event. Each kind fills
+/// the ones it has a field for: an appointment's office is its new
+/// title, a battle's objective its target, and so on.
+fn export_fields(kind: &event::EventKind) -> (Option<&str>, Option<&str>, Option<&str>) {
+    use event::EventKind as K;
+    match kind {
+        K::Appointment { new_title, .. } => (None, Some(new_title), None),
+        K::Battle { verb, target, .. } => (Some(verb), None, Some(target)),
+        K::Death { verb, killer, .. } => (Some(verb), None, killer.as_deref()),
+        K::Enfeoffment { verb, rank, .. } => (Some(verb), Some(rank), None),
+        K::Defeat { verb, victor, .. } => (Some(verb), None, Some(victor)),
+        K::Dispatch { purpose, .. } => (None, None, Some(purpose)),
+        K::TenureEnd {
+            verb,
+            office_or_place,
+            ..
+        } => (Some(verb), None, Some(office_or_place)),
+        K::Decline { verb, office, .. } => (Some(verb), office.as_deref(), None),
+        K::Investiture { role, .. } => (None, Some(role), None),
+        K::Pacification { verb, place, .. } => (Some(verb), None, Some(&place.name)),
+        K::PosthumousGrant { verb, title, .. } => (Some(verb), Some(title), None),
+        K::Abdication { verb, to, .. } => (Some(verb), None, to.as_deref()),
+        K::UsurpationEra { .. } => (None, None, None),
+        K::PopulationMove { verb, to, .. } => (Some(verb), None, to.as_deref()),
+        K::Diplomacy { kind, target, .. } => (Some(kind), None, target.as_deref()),
+        K::Omen { phenomenon, .. } => (None, None, Some(phenomenon)),
+        K::Rebellion { verb, .. } => (Some(verb), None, None),
+        K::Surrender { verb, to, .. } => (Some(verb), None, to.as_deref()),
+        K::Marriage { verb, spouse, .. } => (Some(verb), None, spouse.as_deref()),
+    }
+}
+
+/// The place the event itself records, as opposed to the context
+/// `locations`.
+fn export_place(kind: &event::EventKind) -> Option<&event::PlaceRef> {
+    use event::EventKind as K;
+    match kind {
+        K::Appointment { place, .. }
+        | K::Omen { place, .. }
+        | K::Rebellion { place, .. }
+        | K::Battle {
+            target_place: place,
+            ..
+        } => place.as_ref(),
+        K::Pacification { place, .. } => Some(place),
+        _ => None,
+    }
+}
+
+/// Write `events` as a table with a header row, one event per line.
+fn write_export<'a, W: std::io::Write>(
+    out: &mut W,
+    events: impl IntoIterator<Item = &'a event::Event>,
+    sep: char,
+) -> std::io::Result<()> {
+    let sep_str = sep.to_string();
+    writeln!(out, "{}", EXPORT_COLUMNS.join(&sep_str))?;
+    for e in events {
+        let (verb, new_title, target) = export_fields(&e.kind);
+        let place = export_place(&e.kind);
+        let time = e.time.as_ref();
+        let ad_year = time.and_then(|t| event::exact_ad_year(&t.regime, &t.era, t.year));
+        let row = [
+            e.person_name().unwrap_or_default().to_string(),
+            e.kind.name().to_string(),
+            verb.unwrap_or_default().to_string(),
+            new_title.unwrap_or_default().to_string(),
+            target.unwrap_or_default().to_string(),
+            place.map(|p| p.name.clone()).unwrap_or_default(),
+            place.map(|p| p.is_qiao.to_string()).unwrap_or_default(),
+            time.map(|t| t.regime.clone()).unwrap_or_default(),
+            time.map(|t| t.era.clone()).unwrap_or_default(),
+            time.map(|t| t.year.to_string()).unwrap_or_default(),
+            ad_year.map(|y| y.to_string()).unwrap_or_default(),
+            e.source_file.clone(),
+            e.byte_offset.to_string(),
+        ];
+        let cells: Vec<String> = row.iter().map(|v| export_cell(v, sep)).collect();
+        writeln!(out, "{}", cells.join(&sep_str))?;
+    }
+    Ok(())
+}
+
+fn run_export(format: &str, out: &Path) {
+    let Some(sep) = export_separator(format) else {
+        eprintln!("Unknown export format: {format} (expected csv or tsv)");
+        std::process::exit(1);
+    };
+    let events_file: EventsFile = read_json("events.json");
+    let events = events_file
+        .events
+        .iter()
+        .chain(&events_file.unstructured_events);
+
+    let file = std::fs::File::create(out).unwrap_or_else(|e| {
+        eprintln!("Cannot create {}: {e}", out.display());
+        std::process::exit(1);
+    });
+    let mut writer = std::io::BufWriter::new(file);
+    if let Err(e) =
+        write_export(&mut writer, events, sep).and_then(|()| std::io::Write::flush(&mut writer))
+    {
+        eprintln!("Cannot write {}: {e}", out.display());
+        std::process::exit(1);
+    }
+    eprintln!(
+        "{} events → {}",
+        events_file.events.len() + events_file.unstructured_events.len(),
+        out.display()
+    );
+}
+
+// ═══════════════════════════════════════════════════════════════════════
+//  EXTRACT MODE: full corpus processing → output/*.json
+// ═══════════════════════════════════════════════════════════════════════
+
+/// How much `extract` reports on stderr besides output files and errors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Verbosity {
+    Quiet,
+    Normal,
+    Verbose,
+}
+
+/// The extraction report: statistics and samples, filtered by verbosity.
+/// Errors and the output-file summary bypass it and always print.
+struct Log<W: std::io::Write> {
+    level: Verbosity,
+    out: W,
+}
+
+impl Log<std::io::Stderr> {
+    fn stderr(level: Verbosity) -> Self {
+        Log {
+            level,
+            out: std::io::stderr(),
+        }
+    }
+}
+
+impl<W: std::io::Write> Log<W> {
+    fn line(&mut self, level: Verbosity, args: std::fmt::Arguments) {
+        if self.level >= level {
+            let _ = writeln!(self.out, "{args}");
+        }
+    }
+
+    /// How many items a sample list shows: `normal`, or more under --verbose.
+    fn sample_size(&self, normal: usize) -> usize {
+        if self.level == Verbosity::Verbose {
+            normal * 5
+        } else {
+            normal
+        }
+    }
+}
+
+/// A statistics line, dropped under --quiet.
+macro_rules! stat {
+    ($log:expr, $($arg:tt)*) => {
+        $log.line(Verbosity::Normal, format_args!($($arg)*))
+    };
+}
+
+fn report_corpus_statistics<W: std::io::Write>(log: &mut Log<W>, persons: &[types::Person]) {
+    stat!(log, "\n══════════════════════════════════════════");
+    stat!(log, "  CORPUS STATISTICS");
+    stat!(log, "══════════════════════════════════════════");
 
     let mut by_book = std::collections::HashMap::new();
-    for p in &persons {
+    for p in persons {
         *by_book.entry(p.source.book.as_chinese()).or_insert(0usize) += 1;
     }
-    eprintln!("\nBy book:");
+    stat!(log, "\nBy book:");
     let mut book_counts: Vec<_> = by_book.iter().collect();
     book_counts.sort_by_key(|(_, c)| std::cmp::Reverse(**c));
     for (book, count) in &book_counts {
-        eprintln!("  {book}: {count} persons");
+        stat!(log, "  {book}: {count} persons");
     }
 
     let mut by_section = std::collections::HashMap::new();
-    for p in &persons {
+    for p in persons {
         let sec = match p.source.section {
             Section::BenJi => "本紀",
             Section::LieZhuan => "列傳",
@@ -814,13 +2634,13 @@ fn run_extract(root: &Path) {
         };
         *by_section.entry(sec).or_insert(0usize) += 1;
     }
-    eprintln!("\nBy section:");
+    stat!(log, "\nBy section:");
     for (sec, count) in &by_section {
-        eprintln!("  {sec}: {count} persons");
+        stat!(log, "  {sec}: {count} persons");
     }
 
     let (mut emperors, mut officials, mut deposed, mut rulers) = (0usize, 0, 0, 0);
-    for p in &persons {
+    for p in persons {
         match &p.kind {
             types::PersonKind::Emperor { .. } => emperors += 1,
             types::PersonKind::Official { .. } => officials += 1,
@@ -828,31 +2648,111 @@ fn run_extract(root: &Path) {
             types::PersonKind::Ruler { .. } => rulers += 1,
         }
     }
-    eprintln!("\nBy kind:");
-    eprintln!("  Emperor:  {emperors}");
-    eprintln!("  Official: {officials}");
-    eprintln!("  Ruler:    {rulers}");
-    eprintln!("  Deposed:  {deposed}");
+    stat!(log, "\nBy kind:");
+    stat!(log, "  Emperor:  {emperors}");
+    stat!(log, "  Official: {officials}");
+    stat!(log, "  Ruler:    {rulers}");
+    stat!(log, "  Deposed:  {deposed}");
+}
+
+fn run_extract(roots: &[PathBuf], options: &ExtractOptions) {
+    extract(roots, options, &mut Log::stderr(options.verbosity()));
+}
+
+/// The `extract` pipeline, statistics going to `log`. With `--dry-run`
+/// the output directory is neither locked nor written.
+fn extract<W: std::io::Write>(roots: &[PathBuf], options: &ExtractOptions, log: &mut Log<W>) {
+    // Load user inputs before taking the lock: exit() skips the lock's Drop
+    let extra_blacklist = match &options.blacklist {
+        Some(path) => intext::load_blacklist(path).unwrap_or_else(|e| {
+            eprintln!("Error: {e}");
+            std::process::exit(1);
+        }),
+        None => Vec::new(),
+    };
+
+    let _lock = (!options.dry_run).then(|| {
+        std::fs::create_dir_all(&options.out)
+            .unwrap_or_else(|e| panic!("cannot create {}: {e}", options.out.display()));
+        OutputLock::acquire(&options.out).unwrap_or_else(|e| {
+            eprintln!("Error: {e}");
+            std::process::exit(1);
+        })
+    });
+
+    for root in roots {
+        stat!(log, "Scanning corpus at: {}", root.display());
+    }
+
+    // Phase 1: discover all biography files
+    let bio_files = scanner::scan_corpora(roots);
+    stat!(log, "Found {} biography/annals files", bio_files.len());
+
+    // Phase 2: parse person info from each file
+    let mut persons = Vec::new();
+    let mut failed = Vec::new();
+    let mut unreadable = Vec::new();
+
+    for bio in &bio_files {
+        match parser::parse_biography(bio) {
+            Ok(Some(person)) => persons.push(person),
+            Ok(None) => failed.push(bio.path.display().to_string()),
+            Err(e) => unreadable.push((&bio.path, e)),
+        }
+    }
+
+    // Files that cannot be decoded are skipped by every later phase; say so.
+    if !unreadable.is_empty() {
+        eprintln!("\n══════════════════════════════════════════");
+        eprintln!("  UNREADABLE FILES ({} total, skipped)", unreadable.len());
+        eprintln!("══════════════════════════════════════════");
+        for (path, err) in &unreadable {
+            eprintln!("  {}: {err}", path.display());
+        }
+    }
+
+    stat!(
+        log,
+        "Parsed {} persons ({} files could not be parsed)",
+        persons.len(),
+        failed.len()
+    );
+
+    // Phase 3: compute reference stats and build summaries
+    let summaries: Vec<PersonSummary> = persons.iter().map(PersonSummary::from_person).collect();
+
+    report_corpus_statistics(log, &persons);
 
     // ── Print failures ─────────────────────────────────────────────
     if !failed.is_empty() {
-        eprintln!("\n══════════════════════════════════════════");
-        eprintln!("  UNPARSED FILES ({} total)", failed.len());
-        eprintln!("══════════════════════════════════════════");
-        for f in failed.iter().take(30) {
-            eprintln!("  {f}");
+        stat!(log, "\n══════════════════════════════════════════");
+        stat!(log, "  UNPARSED FILES ({} total)", failed.len());
+        stat!(log, "══════════════════════════════════════════");
+        let shown = log.sample_size(30);
+        for f in failed.iter().take(shown) {
+            stat!(log, "  {f}");
         }
-        if failed.len() > 30 {
-            eprintln!("  ... and {} more", failed.len() - 30);
+        if failed.len() > shown {
+            stat!(log, "  ... and {} more", failed.len() - shown);
         }
     }
 
     // ── Phase 4: In-text person name extraction ────────────────────
-    eprintln!("\n══════════════════════════════════════════");
-    eprintln!("  IN-TEXT PERSON NAME RECOGNITION");
-    eprintln!("══════════════════════════════════════════");
-
-    let name_scanner = intext::InTextScanner::new(&persons);
+    stat!(log, "\n══════════════════════════════════════════");
+    stat!(log, "  IN-TEXT PERSON NAME RECOGNITION");
+    stat!(log, "══════════════════════════════════════════");
+
+    if !extra_blacklist.is_empty() {
+        stat!(
+            log,
+            "Loaded {} extra blacklist entries",
+            extra_blacklist.len()
+        );
+    }
+    let mut name_scanner = intext::InTextScanner::new(&persons, &extra_blacklist);
+    if options.match_known_names {
+        name_scanner = name_scanner.with_known_names(&persons);
+    }
     let in_text_persons = name_scanner.scan_corpus(&bio_files);
 
     let total_mentions: usize = in_text_persons.iter().map(|p| p.mention_count).sum();
@@ -861,19 +2761,25 @@ fn run_extract(root: &Path) {
         .filter(|p| !p.has_own_biography)
         .collect();
 
-    eprintln!(
+    stat!(
+        log,
         "\nFound {} unique names with {} total mentions",
         in_text_persons.len(),
         total_mentions
     );
-    eprintln!(
+    stat!(
+        log,
         "  Known (have own biography): {}",
         in_text_persons.len() - unknown_persons.len()
     );
-    eprintln!("  Unknown (in-text only):     {}", unknown_persons.len());
+    stat!(
+        log,
+        "  Unknown (in-text only):     {}",
+        unknown_persons.len()
+    );
 
-    eprintln!("\nTop unknown persons (no own biography):");
-    for p in unknown_persons.iter().take(20) {
+    stat!(log, "\nTop unknown persons (no own biography):");
+    for p in unknown_persons.iter().take(log.sample_size(20)) {
         let files_short: Vec<&str> = p
             .mentioned_in
             .iter()
@@ -885,7 +2791,8 @@ fn run_extract(root: &Path) {
             .iter()
             .map(|(k, v)| format!("{k}×{v}"))
             .collect();
-        eprintln!(
+        stat!(
+            log,
             "  {} ({}次, {}) — {}",
             p.name,
             p.mention_count,
@@ -895,48 +2802,115 @@ fn run_extract(root: &Path) {
     }
 
     // ── Phase 5: Event extraction (time + place + person) ───────────
-    eprintln!("\n══════════════════════════════════════════");
-    eprintln!("  EVENT EXTRACTION");
-    eprintln!("══════════════════════════════════════════");
-
-    let event_scanner = event::EventScanner::new(&persons);
+    stat!(log, "\n══════════════════════════════════════════");
+    stat!(log, "  EVENT EXTRACTION");
+    stat!(log, "══════════════════════════════════════════");
+
+    let event_scanner = options.kind_context_radius.iter().fold(
+        event::EventScanner::new(&persons)
+            .with_provenance(options.debug_provenance)
+            .with_context_radius(options.context_radius)
+            .with_appointment_gap(options.appt_gap)
+            .with_promotion_gap(options.promo_gap)
+            .with_merge_window(options.merge_window),
+        |scanner, (kind, radius)| scanner.with_kind_context_radius(kind.as_str(), *radius),
+    );
     let (events, time_index, event_stats) = event_scanner.scan_corpus(&bio_files);
 
     // ── Phase 6: Build timeline ─────────────────────────────────────
     let timeline = event::Timeline::from_scopes(&time_index.scopes);
 
-    eprintln!(
+    stat!(
+        log,
         "\nExtracted {} events, {} time scopes, {} time points",
         event_stats.total_events,
         time_index.scopes.len(),
         timeline.total_time_points
     );
-    eprintln!("  Appointments: {}", event_stats.appointments);
-    eprintln!("  Battles:      {}", event_stats.battles);
-    eprintln!("  Deaths:       {}", event_stats.deaths);
+    stat!(log, "  Appointments: {}", event_stats.appointments);
+    stat!(log, "  Battles:      {}", event_stats.battles);
+    stat!(log, "  Deaths:       {}", event_stats.deaths);
+    stat!(log, "  Enfeoffments: {}", event_stats.enfeoffments);
+    stat!(log, "  Defeats:      {}", event_stats.defeats);
+    stat!(log, "  Dispatches:   {}", event_stats.dispatches);
+    stat!(log, "  Tenure ends:  {}", event_stats.tenure_ends);
+    stat!(log, "  Declines:     {}", event_stats.declines);
+    stat!(log, "  Investitures: {}", event_stats.investitures);
+    stat!(log, "  Pacified:     {}", event_stats.pacifications);
+    stat!(log, "  Posthumous:   {}", event_stats.posthumous_grants);
+    stat!(log, "  Abdications:  {}", event_stats.abdications);
+    stat!(log, "  Usurper eras: {}", event_stats.usurpation_eras);
+    stat!(log, "  Resettled:    {}", event_stats.population_moves);
+    stat!(log, "  Missions:     {}", event_stats.diplomatic_missions);
+    stat!(log, "  Omens:        {}", event_stats.omens);
+    stat!(log, "  Rebellions:   {}", event_stats.rebellions);
+    stat!(log, "  Surrenders:   {}", event_stats.surrenders);
+    stat!(log, "  Marriages:    {}", event_stats.marriages);
+    stat!(
+        log,
+        "  Era-only time refs: {}",
+        time_index.era_refs.len() - event_stats.unresolved_ganzhi_years
+    );
+    if event_stats.unresolved_ganzhi_years > 0 {
+        stat!(
+            log,
+            "  Unresolved 歲在 years (no era-year to read them against): {}",
+            event_stats.unresolved_ganzhi_years
+        );
+    }
+    if event_stats.suspect_time_refs > 0 {
+        stat!(
+            log,
+            "  Suspect time refs (year past the era's end): {}",
+            event_stats.suspect_time_refs
+        );
+    }
+
+    // Usurper eras missing from the era table are candidates for adding
+    let mut unknown_eras: Vec<&str> = events
+        .iter()
+        .filter_map(|e| match &e.kind {
+            event::EventKind::UsurpationEra {
+                era,
+                known_era: false,
+                ..
+            } => Some(era.as_str()),
+            _ => None,
+        })
+        .collect();
+    unknown_eras.sort_unstable();
+    unknown_eras.dedup();
+    if !unknown_eras.is_empty() {
+        stat!(log, "  Eras not in table: {}", unknown_eras.join(", "));
+    }
 
     // Era distribution
     let mut era_counts: Vec<_> = event_stats.era_distribution.iter().collect();
     era_counts.sort_by_key(|(_, c)| std::cmp::Reverse(**c));
-    eprintln!("\nEra distribution (top 15):");
-    for (era, count) in era_counts.iter().take(15) {
-        eprintln!("  {era}: {count} events");
+    let shown = log.sample_size(15);
+    stat!(log, "\nEra distribution (top {shown}):");
+    for (era, count) in era_counts.iter().take(shown) {
+        stat!(log, "  {era}: {count} events");
     }
 
     // Timeline summary
-    eprintln!("\nTimeline by regime:");
+    stat!(log, "\nTimeline by regime:");
     for regime in &timeline.regimes {
         let era_count = regime.eras.len();
         let year_count: usize = regime.eras.iter().map(|e| e.years.len()).sum();
-        eprintln!(
+        stat!(
+            log,
             "  {}: {} eras, {} distinct years",
-            regime.regime, era_count, year_count
+            regime.regime,
+            era_count,
+            year_count
         );
     }
 
     // Sample events
-    eprintln!("\nSample events (first 10):");
-    for e in events.iter().take(10) {
+    let shown = log.sample_size(10);
+    stat!(log, "\nSample events (first {shown}):");
+    for e in events.iter().take(shown) {
         let time_str = e
             .time
             .as_ref()
@@ -959,25 +2933,172 @@ fn run_extract(root: &Path) {
                         }
                     })
                     .unwrap_or_default();
-                format!("任命 {}→{}{}", person, new_title, place_str)
+                format!("{person}→{new_title}{place_str}")
             }
             event::EventKind::Battle {
                 person,
                 verb,
                 target,
                 target_place,
+                troops,
+                units,
+                ..
             } => {
                 let place_str = target_place
                     .as_ref()
                     .map(|p| format!(" @{}", p.name))
                     .unwrap_or_default();
-                format!("戰事 {}{}{}{}", person, verb, target, place_str)
+                let troops_str = match troops {
+                    Some(n) => format!(" ({}{n}人)", units.join("、")),
+                    None if !units.is_empty() => format!(" ({})", units.join("、")),
+                    None => String::new(),
+                };
+                format!("{person}{verb}{target}{place_str}{troops_str}")
+            }
+            event::EventKind::Death {
+                person,
+                verb,
+                posthumous_title,
+                posthumous_name,
+                ..
+            } => {
+                let title_str = posthumous_title
+                    .as_ref()
+                    .map(|t| format!(" 贈{t}"))
+                    .unwrap_or_default();
+                let name_str = posthumous_name
+                    .as_ref()
+                    .map(|n| format!(" 謚{n}"))
+                    .unwrap_or_default();
+                format!("{person}{verb}{title_str}{name_str}")
+            }
+            event::EventKind::Enfeoffment {
+                person,
+                verb,
+                fief,
+                rank,
+                ..
+            } => {
+                format!(
+                    "{}{}{}{}",
+                    person.as_deref().unwrap_or(""),
+                    verb,
+                    fief,
+                    rank
+                )
+            }
+            event::EventKind::Defeat {
+                person,
+                verb,
+                victor,
+            } => {
+                format!("{person}為{victor}所{verb}")
+            }
+            event::EventKind::Dispatch {
+                dispatcher,
+                agent,
+                purpose,
+                ..
+            } => {
+                format!(
+                    "{}遣{}{}",
+                    dispatcher.as_deref().unwrap_or(""),
+                    agent,
+                    purpose
+                )
+            }
+            event::EventKind::TenureEnd {
+                person,
+                verb,
+                office_or_place,
+                successor,
+            } => match successor {
+                Some(s) => format!(
+                    "{s}代{}為{office_or_place}",
+                    person.as_deref().unwrap_or("")
+                ),
+                None => format!("{}{verb}{office_or_place}", person.as_deref().unwrap_or("")),
+            },
+            event::EventKind::Decline {
+                person,
+                verb,
+                office,
+            } => format!(
+                "{}{}{}",
+                person.as_deref().unwrap_or(""),
+                verb,
+                office.as_deref().unwrap_or("")
+            ),
+            event::EventKind::Investiture { person, role, by } => {
+                format!("{}立{person}為{role}", by.as_deref().unwrap_or(""))
             }
-            event::EventKind::Death { person, verb } => {
-                format!("死亡 {}{}", person, verb)
+            event::EventKind::Pacification {
+                person,
+                verb,
+                place,
+            } => format!("{}{}{}", person.as_deref().unwrap_or(""), verb, place.name),
+            event::EventKind::PosthumousGrant {
+                person,
+                verb,
+                title,
+                ..
+            } => format!("{}{}{}", person.as_deref().unwrap_or(""), verb, title),
+            event::EventKind::Abdication { person, verb, to } => format!(
+                "{}{}{}",
+                person.as_deref().unwrap_or(""),
+                verb,
+                to.as_deref().map(|t| format!("於{t}")).unwrap_or_default()
+            ),
+            event::EventKind::UsurpationEra { person, era, .. } => {
+                format!("{}改元{}", person.as_deref().unwrap_or(""), era)
             }
+            event::EventKind::PopulationMove {
+                person,
+                verb,
+                group,
+                count,
+                unit,
+                to,
+            } => format!(
+                "{}{}{}{}{}",
+                person.as_deref().unwrap_or(""),
+                verb,
+                group.as_deref().unwrap_or(""),
+                count
+                    .zip(unit.as_deref())
+                    .map(|(n, u)| format!("{n}{u}"))
+                    .unwrap_or_default(),
+                to.as_deref().map(|t| format!("於{t}")).unwrap_or_default()
+            ),
+            event::EventKind::Diplomacy {
+                sender,
+                target,
+                kind,
+            } => format!(
+                "{}{}{}",
+                sender.as_deref().unwrap_or(""),
+                kind,
+                target
+                    .as_deref()
+                    .map(|t| format!("於{t}"))
+                    .unwrap_or_default()
+            ),
+            event::EventKind::Omen { phenomenon, place } => format!(
+                "{}{phenomenon}",
+                place.as_ref().map(|p| p.name.as_str()).unwrap_or("")
+            ),
+            event::EventKind::Rebellion { person, verb, .. } => format!("{person}{verb}"),
+            event::EventKind::Surrender { person, verb, to } => format!(
+                "{person}{verb}{}",
+                to.as_deref().map(|t| format!("於{t}")).unwrap_or_default()
+            ),
+            event::EventKind::Marriage {
+                person,
+                verb,
+                spouse,
+            } => format!("{person}{verb}{}", spouse.as_deref().unwrap_or("")),
         };
-        eprintln!("  {} {}", time_str, event_str);
+        stat!(log, "  {} {} {}", time_str, e.kind.kind_zh(), event_str);
     }
 
     // ── Build frequency maps for high-confidence filtering ─────────
@@ -987,7 +3108,9 @@ fn run_extract(root: &Path) {
         std::collections::HashMap::new();
 
     for e in &events {
-        *person_freq.entry(e.person_name().to_string()).or_insert(0) += 1;
+        if let Some(p) = e.person_name() {
+            *person_freq.entry(p.to_string()).or_insert(0) += 1;
+        }
         for loc_name in e.all_location_names() {
             *location_freq.entry(loc_name.to_string()).or_insert(0) += 1;
         }
@@ -995,26 +3118,22 @@ fn run_extract(root: &Path) {
 
     let high_conf_persons: usize = person_freq.values().filter(|&&c| c >= 2).count();
     let high_conf_locations: usize = location_freq.values().filter(|&&c| c >= 2).count();
-    eprintln!(
+    stat!(
+        log,
         "\nHigh-confidence (freq >= 2): {} persons, {} locations",
-        high_conf_persons, high_conf_locations,
+        high_conf_persons,
+        high_conf_locations,
     );
 
     // ── Write split JSON files ──────────────────────────────────────
-    eprintln!("\n══════════════════════════════════════════");
-    eprintln!("  WRITING OUTPUT FILES");
-    eprintln!("══════════════════════════════════════════\n");
-
-    std::fs::create_dir_all(OUTPUT_DIR).expect("cannot create output/");
+    stat!(log, "\n══════════════════════════════════════════");
+    stat!(log, "  WRITING OUTPUT FILES");
+    stat!(log, "══════════════════════════════════════════\n");
 
     // 1. persons.json — biography summaries + in-text mentions + event person frequencies
     #[derive(serde::Serialize)]
-    struct EventPersonEntry {
-        name: String,
-        event_count: usize,
-    }
-    #[derive(serde::Serialize)]
     struct PersonsOutput {
+        schema_version: u32,
         persons: Vec<extract::PersonSummary>,
         in_text_mentions: Vec<intext::InTextPerson>,
         event_persons: Vec<EventPersonEntry>,
@@ -1026,15 +3145,21 @@ fn run_extract(root: &Path) {
             event_count: count,
         })
         .collect();
-    event_persons.sort_by(|a, b| b.event_count.cmp(&a.event_count));
-    write_json(
-        "persons.json",
-        &PersonsOutput {
-            persons: summaries,
-            in_text_mentions: in_text_persons,
-            event_persons,
-        },
-    );
+    event_persons.sort_by_key(|p| std::cmp::Reverse(p.event_count));
+    // Every file written, for --gzip
+    let mut written = Vec::new();
+    if !options.dry_run {
+        written.push(write_json(
+            &options.out,
+            "persons.json",
+            &PersonsOutput {
+                schema_version: SCHEMA_VERSION,
+                persons: summaries,
+                in_text_mentions: in_text_persons,
+                event_persons,
+            },
+        ));
+    }
 
     // 2. locations.json — all raw location extractions, grouped by name
     #[derive(serde::Serialize)]
@@ -1093,20 +3218,49 @@ fn run_extract(root: &Path) {
         }
     }
     let mut locations: Vec<LocationEntry> = loc_map.into_values().collect();
-    locations.sort_by(|a, b| b.event_count.cmp(&a.event_count));
-    write_json("locations.json", &locations);
+    locations.sort_by_key(|l| std::cmp::Reverse(l.event_count));
+    if !options.dry_run {
+        written.push(write_json(&options.out, "locations.json", &locations));
+    }
 
-    // 3. events.json — split into high-confidence and unstructured
-    #[derive(serde::Serialize)]
-    struct EventsOutput {
-        events: Vec<event::Event>,
-        unstructured_events: Vec<event::Event>,
+    // 3. anomalies.json — implausible ages and orderings, for triage
+    let anomalies = anomaly::find_anomalies(&events);
+    stat!(log, "  anomalies: {}", anomalies.len());
+    if !options.dry_run {
+        written.push(write_json(&options.out, "anomalies.json", &anomalies));
+    }
+
+    // Years an era never reached: probably another regime's era of the same name
+    let cross_refs = anomaly::find_cross_regime_refs(&time_index);
+    if !cross_refs.is_empty() {
+        stat!(
+            log,
+            "  possible cross-regime references: {}",
+            cross_refs.len()
+        );
+        for r in cross_refs.iter().take(log.sample_size(10)) {
+            stat!(
+                log,
+                "    {}:{} {} ({}/{} lasted {} year(s))",
+                r.source_file,
+                r.byte_offset,
+                r.raw,
+                r.regime,
+                r.era,
+                r.era_years
+            );
+        }
     }
 
+    // 4. events.json — split into high-confidence and unstructured
     let mut high_confidence = Vec::new();
     let mut unstructured = Vec::new();
     for e in events {
-        let person_count = person_freq.get(e.person_name()).copied().unwrap_or(0);
+        let person_count = e
+            .person_name()
+            .and_then(|p| person_freq.get(p))
+            .copied()
+            .unwrap_or(0);
         if person_count >= 2 {
             // Filter locations to only high-confidence
             let mut filtered = e;
@@ -1129,7 +3283,23 @@ fn run_extract(root: &Path) {
                         *target_place = None;
                     }
                 }
-                event::EventKind::Death { .. } => {}
+                event::EventKind::Death { .. }
+                | event::EventKind::Enfeoffment { .. }
+                | event::EventKind::Defeat { .. }
+                | event::EventKind::Dispatch { .. }
+                | event::EventKind::TenureEnd { .. }
+                | event::EventKind::Decline { .. }
+                | event::EventKind::Investiture { .. }
+                | event::EventKind::Pacification { .. }
+                | event::EventKind::PosthumousGrant { .. }
+                | event::EventKind::Abdication { .. }
+                | event::EventKind::UsurpationEra { .. }
+                | event::EventKind::PopulationMove { .. }
+                | event::EventKind::Diplomacy { .. }
+                | event::EventKind::Omen { .. }
+                | event::EventKind::Rebellion { .. }
+                | event::EventKind::Surrender { .. }
+                | event::EventKind::Marriage { .. } => {}
             }
             high_confidence.push(filtered);
         } else {
@@ -1137,38 +3307,855 @@ fn run_extract(root: &Path) {
         }
     }
 
-    eprintln!(
+    stat!(
+        log,
         "  events: {} high-confidence, {} unstructured",
         high_confidence.len(),
         unstructured.len(),
     );
-    write_json(
-        "events.json",
-        &EventsOutput {
-            events: high_confidence,
-            unstructured_events: unstructured,
-        },
-    );
+    // The largest output: streamed one event at a time
+    if !options.dry_run {
+        written.push(write_json_streamed(&options.out, "events.json", |w| {
+            w.field("schema_version", &SCHEMA_VERSION)?;
+            w.array("events", &high_confidence)?;
+            w.array("unstructured_events", &unstructured)
+        }));
+        if options.jsonl {
+            written.push(write_jsonl(
+                &options.out,
+                "events.jsonl",
+                high_confidence.iter().chain(&unstructured),
+            ));
+        }
+    }
 
-    // 4. timeline.json — timeline + time_index + stats
+    let file_eras = time_index.file_eras();
+
+    // 5. timeline.json — timeline + time_index + stats
     #[derive(serde::Serialize)]
     struct TimelineOutput {
+        schema_version: u32,
         timeline: event::Timeline,
         time_index: event::TimeIndex,
         stats: event::EventStats,
     }
-    write_json(
+    if options.dry_run {
+        stat!(
+            log,
+            "\nDry run: nothing written to {}/",
+            options.out.display()
+        );
+        return;
+    }
+    written.push(write_json(
+        &options.out,
         "timeline.json",
         &TimelineOutput {
+            schema_version: SCHEMA_VERSION,
             timeline,
             time_index,
             stats: event_stats,
         },
-    );
+    ));
+
+    // 6. file_eras.json — which eras each source file covers
+    written.push(write_json(&options.out, "file_eras.json", &file_eras));
+
+    if options.gzip {
+        for path in &written {
+            match gzip_file(path) {
+                Ok(gz) => eprintln!("  {}", gz.display()),
+                Err(e) => eprintln!("Warning: cannot gzip {}: {e}", path.display()),
+            }
+        }
+    }
+
+    stat!(log, "\nDone. Query with:");
+    stat!(log, "  cargo run -- query \"太和三年\"");
+    stat!(log, "  cargo run -- query \"太和元年-太和六年\"");
+    stat!(log, "  cargo run -- query \"@東晉\"");
+    stat!(log, "  cargo run -- timeline");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time_ref(regime: &str, era: &str, year: u8) -> event::TimeRef {
+        event::TimeRef {
+            era: era.into(),
+            regime: regime.into(),
+            year,
+            month: None,
+            is_leap: false,
+            day_ganzhi: None,
+            day_num: None,
+            day: None,
+            year_ganzhi: None,
+            suspect: false,
+            precision: event::TimePrecision::Year,
+            origin: event::TimeOrigin::Era,
+            raw: format!("{era}{year}年"),
+            byte_offset: 0,
+        }
+    }
+
+    fn death_event(person: &str, time: event::TimeRef) -> event::Event {
+        event::Event::new(
+            event::EventKind::Death {
+                person: person.into(),
+                verb: "卒".into(),
+                manner: event::DeathManner::Natural,
+                killer: None,
+                posthumous_title: None,
+                posthumous_name: None,
+                age_at_death: None,
+            },
+            Some(time),
+            "t.txt",
+            0,
+            String::new(),
+            Vec::new(),
+        )
+    }
+
+    #[test]
+    fn test_multi_era_query_groups_events() {
+        let events = vec![
+            death_event("王進", time_ref("劉宋", "元嘉", 3)),
+            death_event("張華", time_ref("南齊", "永明", 2)),
+            death_event("褚淵", time_ref("北魏", "太和", 1)),
+        ];
+        let index = event::TimeIndex {
+            scopes: Vec::new(),
+            era_refs: Vec::new(),
+        };
+
+        let queries = parse_multi_era_query("元嘉 永明").expect("multi-era query");
+        let groups = query_era_groups(&index, &events, &queries);
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].era, "元嘉");
+        assert_eq!(groups[0].events[0].person_name(), Some("王進"));
+        assert_eq!(groups[1].era, "永明");
+        assert_eq!(groups[1].events[0].person_name(), Some("張華"));
+    }
+
+    #[test]
+    fn test_regime_affiliation_across_transition() {
+        let events = [
+            death_event("褚淵", time_ref("劉宋", "元徽", 2)),
+            death_event("褚淵", time_ref("劉宋", "昇明", 1)),
+            death_event("褚淵", time_ref("劉宋", "昇明", 2)),
+            death_event("褚淵", time_ref("南齊", "建元", 1)),
+        ];
+        let refs: Vec<&event::Event> = events.iter().collect();
+        let (primary, shares) = regime_affiliation(&refs);
+        assert_eq!(primary.as_deref(), Some("劉宋"));
+        assert_eq!(shares.len(), 2);
+        assert_eq!(
+            (shares[0].regime.as_str(), shares[0].event_count),
+            ("劉宋", 3)
+        );
+        assert_eq!(
+            (shares[1].regime.as_str(), shares[1].event_count),
+            ("南齊", 1)
+        );
+        assert!((shares[0].share - 0.75).abs() < 1e-6);
+        assert!((shares[1].share - 0.25).abs() < 1e-6);
+    }
+
+    fn appointment_event(person: &str, title: &str, time: event::TimeRef) -> event::Event {
+        event::Event::new(
+            event::EventKind::Appointment {
+                person: person.into(),
+                new_title: title.into(),
+                place: None,
+            },
+            Some(time),
+            "t.txt",
+            0,
+            String::new(),
+            Vec::new(),
+        )
+    }
+
+    #[test]
+    fn test_degree_centrality_ranks_hub_first() {
+        let in_scope = |person: &str, scope: usize| event::Event {
+            scope_index: Some(scope),
+            ..death_event(person, time_ref("劉宋", "元嘉", 3))
+        };
+        // 王進 shares a passage with three others; 陳顯 only with 褚淵
+        let events = [
+            in_scope("王進", 0),
+            in_scope("張華", 0),
+            in_scope("王進", 1),
+            in_scope("褚淵", 1),
+            in_scope("王進", 2),
+            in_scope("沈約", 2),
+            in_scope("王進", 3),
+            in_scope("張華", 3),
+            in_scope("褚淵", 4),
+            in_scope("陳顯", 4),
+        ];
+        let ranked = degree_centrality(&events);
+        assert_eq!(ranked[0].name, "王進");
+        assert_eq!((ranked[0].degree, ranked[0].weighted_degree), (3, 4));
+        let chen = ranked.iter().position(|c| c.name == "陳顯").unwrap();
+        assert_eq!(ranked[chen].degree, 1);
+        assert!(chen > 0);
+    }
+
+    #[test]
+    fn test_title_frequencies_group_by_suffix() {
+        let events = [
+            appointment_event("王進", "荊州刺史", time_ref("劉宋", "元嘉", 3)),
+            appointment_event("張華", "龍驤將軍、揚州刺史", time_ref("劉宋", "元嘉", 4)),
+            appointment_event("王進", "尚書令", time_ref("劉宋", "元嘉", 5)),
+            death_event("王進", time_ref("劉宋", "元嘉", 6)),
+        ];
+        let ranked = title_frequencies(&events);
+        let counts: Vec<(&str, usize, usize)> = ranked
+            .iter()
+            .map(|t| (t.title, t.appointments, t.holders))
+            .collect();
+        assert_eq!(counts, [("刺史", 2, 2), ("將軍", 1, 1), ("尚書", 1, 1)]);
+    }
+
+    #[test]
+    fn test_calendar_keeps_only_month_or_day_refs() {
+        let scope = |time: event::TimeRef| event::TimeScope {
+            span: event::TextSpan {
+                file: "t.txt".into(),
+                byte_start: time.byte_offset,
+                byte_end: time.byte_offset + 1,
+            },
+            time,
+        };
+        let dated = event::TimeRef {
+            month: Some(5),
+            day_ganzhi: Some("甲子".into()),
+            precision: event::TimePrecision::Exact,
+            ..time_ref("劉宋", "元嘉", 3)
+        };
+        let index = event::TimeIndex {
+            scopes: vec![scope(time_ref("劉宋", "元嘉", 2)), scope(dated)],
+            era_refs: Vec::new(),
+        };
+        let entries = calendar_entries(&index);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].ad_year, Some(426));
+        assert_eq!(entries[0].time.day_ganzhi.as_deref(), Some("甲子"));
+    }
+
+    #[test]
+    fn test_era_summary_counts() {
+        let events = [
+            appointment_event("王進", "侍中", time_ref("劉宋", "元嘉", 3)),
+            appointment_event("張華", "荊州刺史", time_ref("劉宋", "元嘉", 4)),
+            death_event("王進", time_ref("劉宋", "元嘉", 5)),
+        ];
+        let refs: Vec<&event::Event> = events.iter().collect();
+        let summary = summarize_era("元嘉", &refs);
+        assert_eq!(summary.kind_counts.get("Appointment"), Some(&2));
+        assert_eq!(summary.kind_counts.get("Death"), Some(&1));
+        assert_eq!(summary.appointments.len(), 2);
+        assert_eq!(summary.deaths.len(), 1);
+        assert_eq!(summary.top_persons[0], ("王進".to_string(), 2));
+        assert_eq!(summary.eras, vec!["劉宋/元嘉".to_string()]);
+    }
+
+    fn in_text_person(
+        name: &str,
+        mention_count: usize,
+        has_own_biography: bool,
+    ) -> intext::InTextPerson {
+        intext::InTextPerson {
+            name: name.into(),
+            surname: name.chars().take(1).collect(),
+            given: name.chars().skip(1).collect(),
+            mention_count,
+            mentioned_in: vec!["a.txt".into()],
+            pattern_counts: std::collections::HashMap::new(),
+            has_own_biography,
+            sample_contexts: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_unknown_excludes_biographied_persons() {
+        let mentions = [
+            in_text_person("褚淵", 40, true),
+            in_text_person("王進", 3, false),
+            in_text_person("張華", 7, false),
+        ];
+        let names: Vec<&str> = unknown_persons(&mentions)
+            .iter()
+            .map(|p| p.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["張華", "王進"]);
+    }
+
+    #[test]
+    fn test_reconcile_flags_passive_and_unmentioned() {
+        let mentions = [
+            in_text_person("褚淵", 40, true),
+            in_text_person("張華", 12, false),
+        ];
+        let event_persons = [
+            EventPersonEntry {
+                name: "褚淵".into(),
+                event_count: 9,
+            },
+            EventPersonEntry {
+                name: "王進".into(),
+                event_count: 2,
+            },
+        ];
+        let report = reconcile(&mentions, &event_persons, 10, 1);
+        let passive: Vec<_> = report
+            .passive
+            .iter()
+            .map(|p| (p.name, p.event_count))
+            .collect();
+        assert_eq!(passive, [("張華", 0)]);
+        let unmentioned: Vec<_> = report.unmentioned.iter().map(|p| p.name).collect();
+        assert_eq!(unmentioned, ["王進"]);
+    }
+
+    #[test]
+    fn test_parse_kind_radius_checks_kind() {
+        assert_eq!(parse_kind_radius("Battle=80"), Ok(("Battle".into(), 80)));
+        let err = parse_kind_radius("battle=80").unwrap_err();
+        assert!(err.contains("Appointment, Battle"), "{err}");
+        assert!(parse_kind_radius("Battle=wide").is_err());
+    }
+
+    #[test]
+    fn test_schema_mismatch_direction() {
+        assert_eq!(schema_mismatch("events.json", SCHEMA_VERSION), None);
+        let older = schema_mismatch("events.json", SCHEMA_VERSION - 1).unwrap();
+        assert!(older.contains("older") && older.contains("Re-run extraction"));
+        let newer = schema_mismatch("events.json", SCHEMA_VERSION + 1).unwrap();
+        assert!(newer.contains("newer") && newer.contains("Upgrade"));
+    }
+
+    #[test]
+    fn test_schema_probe_defaults_and_ignores_fields() {
+        let probe: SchemaProbe = serde_json::from_str(r#"{"events": []}"#).unwrap();
+        assert_eq!(probe.schema_version, 1);
+        let probe: SchemaProbe =
+            serde_json::from_str(r#"{"schema_version": 7, "extra": 1}"#).unwrap();
+        assert_eq!(probe.schema_version, 7);
+    }
+
+    #[test]
+    fn test_parse_era_year_prefers_longest_era() {
+        assert_eq!(parse_era_year("中大通三年"), ("中大通".into(), Some(3)));
+        assert_eq!(parse_era_year("大通三年"), ("大通".into(), Some(3)));
+        assert_eq!(parse_era_year("太平真君五年"), ("太平真君".into(), Some(5)));
+        assert_eq!(parse_era_year("太平真君"), ("太平真君".into(), None));
+        assert_eq!(parse_era_year("建元"), ("建元".into(), None));
+        assert_eq!(parse_era_year("建元元年"), ("建元".into(), Some(1)));
+        assert_eq!(parse_era_year("太和十二年"), ("太和".into(), Some(12)));
+    }
+
+    fn person_summary(display_name: &str, aliases: &[&str]) -> extract::PersonSummary {
+        extract::PersonSummary {
+            display_name: display_name.into(),
+            book: "南齊書".into(),
+            section: "列傳".into(),
+            kind: "Official".into(),
+            aliases: aliases.iter().map(|a| a.to_string()).collect(),
+            ref_stats: Default::default(),
+            courtesy_name: aliases.last().map(|a| a.to_string()),
+            origin: None,
+            origin_coords: None,
+            ancestral_origin: None,
+            current_residence: None,
+            file: format!("{display_name}.txt"),
+        }
+    }
+
+    #[test]
+    fn test_biography_text_reads_own_file() {
+        let root = fixture::write_corpus(fixture::SONG_CORPUS);
+        let path = root.path().join(fixture::SONG_CORPUS[1].path);
+        let mut fu_liang = person_summary("傅亮", &["傅亮", "亮", "季友"]);
+        fu_liang.file = path.display().to_string();
+        let persons = [fu_liang, person_summary("王進", &["王進"])];
+
+        let text = biography_text(&persons, "傅亮").expect("傅亮 has a biography");
+        assert_eq!(text, fixture::SONG_CORPUS[1].text);
+        // in-text only: no entry in persons.json
+        assert_eq!(biography_text(&persons, "褚淵"), None);
+    }
+
+    #[test]
+    fn test_alias_candidates_resolve_courtesy_name() {
+        let persons = [
+            person_summary("褚淵", &["褚淵", "淵", "彥回"]),
+            person_summary("王進", &["王進", "進", "彥回"]),
+            person_summary("張華", &["張華", "華", "茂先"]),
+        ];
+        let names = |q: &str| -> Vec<String> {
+            alias_candidates(&persons, q)
+                .iter()
+                .map(|p| p.display_name.clone())
+                .collect()
+        };
+        assert_eq!(names("茂先"), vec!["張華"]);
+        assert_eq!(names("彥回"), vec!["褚淵", "王進"]);
+        assert!(names("張華").is_empty());
+    }
+
+    fn in_memory_corpus() -> Corpus {
+        let jingzhou = event::Event {
+            kind: event::EventKind::Appointment {
+                person: "王進".into(),
+                new_title: "荊州刺史".into(),
+                place: Some(event::PlaceRef {
+                    role_suffix: Some("刺史".into()),
+                    ..event::PlaceRef::new("荊州")
+                }),
+            },
+            context: "元嘉五年，以王進為使持節、都督荊湘雍梁四州豫州之義陽諸軍事、荊州刺史。"
+                .into(),
+            ..appointment_event("王進", "荊州刺史", time_ref("劉宋", "元嘉", 5))
+        };
+        Corpus {
+            time_index: event::TimeIndex {
+                scopes: Vec::new(),
+                era_refs: Vec::new(),
+            },
+            events: vec![
+                appointment_event("王進", "侍中", time_ref("劉宋", "元嘉", 3)),
+                jingzhou,
+                death_event("褚淵", time_ref("南齊", "建元", 4)),
+            ],
+            unstructured_events: vec![appointment_event(
+                "褚淵",
+                "司徒",
+                time_ref("劉宋", "昇明", 1),
+            )],
+            persons: vec![
+                person_summary("褚淵", &["褚淵", "淵", "彥回"]),
+                person_summary("王進", &["王進", "進"]),
+                person_summary("張華", &["張華", "華", "茂先"]),
+                person_summary("張茂", &["張茂", "茂先"]),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_corpus_who_at_place() {
+        let mut corpus = in_memory_corpus();
+        let jingzhou = event::PlaceRef::new("荊州");
+        corpus.events.push(event::Event {
+            kind: event::EventKind::Appointment {
+                person: "王進".into(),
+                new_title: "南蠻校尉".into(),
+                place: Some(jingzhou.clone()),
+            },
+            ..appointment_event("王進", "南蠻校尉", time_ref("劉宋", "元嘉", 8))
+        });
+        corpus.events.push(event::Event {
+            locations: vec![jingzhou],
+            ..death_event("褚淵", time_ref("劉宋", "昇明", 2))
+        });
+
+        let places = ["荊州".to_string()];
+        let all = corpus.who(&places, None);
+        let summary: Vec<_> = all
+            .iter()
+            .map(|p| (p.person, p.role, p.as_of_ad, p.ad_years.clone()))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("王進", Some("南蠻校尉"), Some(431), vec![428, 431]),
+                ("褚淵", None, Some(478), vec![478]),
+            ]
+        );
+
+        let in_window = corpus.who(&places, Some("元嘉五年"));
+        assert_eq!(in_window.len(), 1);
+        assert_eq!(in_window[0].role, Some("荊州刺史"));
+        assert_eq!(in_window[0].event_count, 1);
+        assert!(corpus.who(&["揚州".to_string()], None).is_empty());
+    }
+
+    #[test]
+    fn test_corpus_query_returns_typed_result() {
+        let corpus = in_memory_corpus();
+        match corpus.query("元嘉五年") {
+            QueryResult::Single {
+                event_count,
+                events,
+                ..
+            } => {
+                assert_eq!(event_count, 1);
+                assert_eq!(events[0].person_name(), Some("王進"));
+            }
+            QueryResult::MultiEra { .. } => panic!("single era query"),
+        }
+        assert!(matches!(
+            corpus.query("元嘉 建元"),
+            QueryResult::MultiEra { groups, .. } if groups.len() == 2
+        ));
+    }
+
+    #[test]
+    fn test_render_json_compact_and_ndjson() {
+        let corpus = in_memory_corpus();
+        let result = corpus.query("元嘉 建元");
+
+        let compact = render_json(&result, OutputFormat::Compact);
+        assert!(!compact.contains('\n') && !compact.contains(": "));
+        assert!(render_json(&result, OutputFormat::Pretty).contains("\n  "));
+
+        let events = result.events();
+        let ndjson = render_ndjson(&events);
+        let lines: Vec<&str> = ndjson.lines().collect();
+        assert_eq!(lines.len(), events.len());
+        assert!(lines.len() > 1);
+        for line in lines {
+            let record: serde_json::Value = serde_json::from_str(line).unwrap();
+            assert!(record.get("source_file").is_some());
+        }
+    }
+
+    #[test]
+    fn test_corpus_locate_latest_place() {
+        let corpus = in_memory_corpus();
+        let located = corpus.locate("元嘉五年").expect("dated events in 元嘉五年");
+        let wang = &located["王進"];
+        assert_eq!(
+            (wang.location.as_str(), wang.status.as_str()),
+            ("荊州", "current")
+        );
+        assert_eq!(wang.command_over, ["荊州", "湘州", "雍州", "梁州", "豫州"]);
+        assert!(corpus.locate("太和三年").is_none());
+    }
+
+    #[test]
+    fn test_corpus_person_timeline_resolves_alias() {
+        let corpus = in_memory_corpus();
+        let Ok(timeline) = corpus.person_timeline("彥回", false) else {
+            panic!("彥回 resolves to 褚淵");
+        };
+        assert_eq!(timeline.name, "褚淵");
+        // unstructured events count too, in AD order
+        let years: Vec<Option<u16>> = timeline.entries.iter().map(|e| e.ad_year).collect();
+        assert_eq!(years, [Some(477), Some(482)]);
+        assert_eq!(timeline.primary_regime.as_deref(), Some("劉宋"));
+
+        assert!(matches!(
+            corpus.person_timeline("茂先", false),
+            Err(PersonLookupError::Ambiguous(many)) if many.len() == 2
+        ));
+        assert!(matches!(
+            corpus.person_timeline("張華", false),
+            Err(PersonLookupError::NoEvents(name)) if name == "張華"
+        ));
+    }
+
+    #[test]
+    fn test_person_timeline_year_window() {
+        let corpus = in_memory_corpus();
+        let timeline = |since, until| {
+            let Ok(mut t) = corpus.person_timeline("褚淵", false) else {
+                panic!("褚淵 has events");
+            };
+            t.retain_years(since, until);
+            t
+        };
+        assert_eq!(timeline(None, None).event_count, 2);
+
+        let late = timeline(Some(480), None);
+        let years: Vec<Option<u16>> = late.entries.iter().map(|e| e.ad_year).collect();
+        assert_eq!(years, [Some(482)]);
+        assert_eq!(late.event_count, 1);
+        assert_eq!(late.primary_regime.as_deref(), Some("南齊"));
+        assert!(!render_json(&late, OutputFormat::Compact).contains("\"ad_year\":477"));
+
+        assert!(timeline(Some(400), Some(450)).entries.is_empty());
+    }
+
+    fn regime_timeline(regime: &str, era: &str, years: &[u8]) -> event::RegimeTimeline {
+        event::RegimeTimeline {
+            regime: regime.into(),
+            eras: vec![event::EraTimeline {
+                era: era.into(),
+                years: years
+                    .iter()
+                    .map(|&year| event::TimePoint {
+                        era: era.into(),
+                        year,
+                        occurrence_count: 1,
+                        files: vec!["t.txt".into()],
+                    })
+                    .collect(),
+                coverage: None,
+            }],
+        }
+    }
+
+    #[test]
+    fn test_song_to_qi_transition() {
+        // 劉宋 昇明 477–479, 南齊 建元 479–482
+        let timeline = event::Timeline {
+            regimes: vec![
+                regime_timeline("劉宋", "昇明", &[1, 2, 3]),
+                regime_timeline("南齊", "建元", &[1, 2]),
+            ],
+            total_time_points: 5,
+        };
+        let transitions = dynastic_transitions(&timeline);
+        assert_eq!(transitions.len(), 1);
+        let t = &transitions[0];
+        assert_eq!((t.from, t.to), ("劉宋", "南齊"));
+        assert_eq!((t.from_last_ad, t.to_first_ad), (479, 479));
+        assert_eq!(t.overlap, vec![479]);
+    }
+
+    #[test]
+    fn test_single_era_query_not_split() {
+        assert!(parse_multi_era_query("元嘉三年").is_none());
+        assert!(parse_multi_era_query("太和元年 - 太和六年").is_none());
+    }
+
+    #[test]
+    fn test_output_lock_rejects_second_run() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = OutputLock::acquire(dir.path()).expect("first lock");
+        let err = OutputLock::acquire(dir.path())
+            .err()
+            .expect("second lock must fail");
+        assert!(err.contains("locked"), "{err}");
+        drop(first);
+        assert!(OutputLock::acquire(dir.path()).is_ok());
+    }
+
+    #[test]
+    fn test_quiet_omits_statistics() {
+        let report = |level| {
+            let mut log = Log {
+                level,
+                out: Vec::new(),
+            };
+            report_corpus_statistics(&mut log, &[]);
+            String::from_utf8(log.out).unwrap()
+        };
+        assert!(report(Verbosity::Normal).contains("CORPUS STATISTICS"));
+        assert!(!report(Verbosity::Quiet).contains("CORPUS STATISTICS"));
+        assert!(report(Verbosity::Quiet).is_empty());
+    }
+
+    #[test]
+    fn test_dry_run_leaves_output_untouched() {
+        // Name, size and mtime of everything in output/, or None if absent
+        let snapshot = || {
+            std::fs::read_dir(OUTPUT_DIR).ok().map(|dir| {
+                let mut entries: Vec<_> = dir
+                    .filter_map(Result::ok)
+                    .map(|e| {
+                        let meta = e.metadata().unwrap();
+                        (e.file_name(), meta.len(), meta.modified().ok())
+                    })
+                    .collect();
+                entries.sort();
+                entries
+            })
+        };
+        let before = snapshot();
+
+        let root = fixture::write_corpus(fixture::SONG_CORPUS);
+        let options = ExtractOptions {
+            dry_run: true,
+            ..ExtractOptions::default()
+        };
+        let mut log = Log {
+            level: Verbosity::Normal,
+            out: Vec::new(),
+        };
+        extract(&[root.path().to_path_buf()], &options, &mut log);
+
+        assert_eq!(snapshot(), before);
+        let printed = String::from_utf8(log.out).unwrap();
+        assert!(printed.contains("EVENT EXTRACTION"), "{printed}");
+        assert!(printed.contains("Dry run"), "{printed}");
+    }
+
+    #[test]
+    fn test_extract_then_query_round_trip() {
+        let root = fixture::write_corpus(fixture::SONG_CORPUS);
+        let out = tempfile::tempdir().unwrap();
+        let options = ExtractOptions {
+            out: out.path().to_path_buf(),
+            quiet: true,
+            ..ExtractOptions::default()
+        };
+        let mut log = Log {
+            level: Verbosity::Quiet,
+            out: Vec::new(),
+        };
+        extract(&[root.path().to_path_buf()], &options, &mut log);
+        assert!(!out.path().join(OutputLock::FILE_NAME).exists());
+
+        // Each command reads only the files it uses
+        std::fs::remove_file(out.path().join("persons.json")).unwrap();
+        let corpus = Corpus::load_from(
+            out.path(),
+            CorpusFiles {
+                timeline: true,
+                ..CorpusFiles::default()
+            },
+        );
+        let result = corpus.query("永初元年");
+        let appointed: Vec<_> = result
+            .events()
+            .into_iter()
+            .filter_map(|e| match &e.kind {
+                event::EventKind::Appointment {
+                    person, new_title, ..
+                } => Some((person.as_str(), new_title.as_str())),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(appointed, [("傅亮", "尚書僕射")]);
+        assert!(corpus.available_eras().contains(&"元嘉"));
+
+        std::fs::remove_file(out.path().join("timeline.json")).unwrap();
+        let corpus = Corpus::load_from(out.path(), CorpusFiles::default());
+        assert!(corpus.locate("永初元年").is_some());
+    }
+
+    #[test]
+    fn test_streamed_json_matches_in_memory() {
+        #[derive(serde::Serialize)]
+        struct EventsOutput<'a> {
+            schema_version: u32,
+            events: &'a [event::Event],
+            unstructured_events: &'a [event::Event],
+        }
+        let events = [
+            appointment_event("王進", "侍中", time_ref("劉宋", "元嘉", 3)),
+            death_event("王進", time_ref("劉宋", "元嘉", 5)),
+        ];
+        let in_memory = EventsOutput {
+            schema_version: SCHEMA_VERSION,
+            events: &events,
+            unstructured_events: &[],
+        };
+
+        for pretty in [true, false] {
+            let mut w = JsonObjectWriter::new(Vec::new(), pretty).unwrap();
+            w.field("schema_version", &SCHEMA_VERSION).unwrap();
+            w.array("events", &events).unwrap();
+            w.array("unstructured_events", &[] as &[event::Event])
+                .unwrap();
+            let streamed = String::from_utf8(w.finish().unwrap()).unwrap();
 
-    eprintln!("\nDone. Query with:");
-    eprintln!("  cargo run -- query \"太和三年\"");
-    eprintln!("  cargo run -- query \"太和元年-太和六年\"");
-    eprintln!("  cargo run -- query \"@東晉\"");
-    eprintln!("  cargo run -- timeline");
+            let expected = if pretty {
+                serde_json::to_string_pretty(&in_memory).unwrap()
+            } else {
+                serde_json::to_string(&in_memory).unwrap()
+            };
+            assert_eq!(streamed, expected);
+            let parsed: EventsFile = serde_json::from_str(&streamed).unwrap();
+            assert_eq!(parsed.events.len(), 2);
+            assert_eq!(parsed.events[1].person_name(), Some("王進"));
+        }
+    }
+
+    #[test]
+    fn test_gzip_file_round_trips() {
+        use std::io::Read;
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("events.json");
+        let json = serde_json::to_string_pretty(&serde_json::json!({
+            "schema_version": SCHEMA_VERSION,
+            "events": ["王進卒", "元嘉三年"],
+        }))
+        .unwrap();
+        std::fs::write(&path, &json).unwrap();
+
+        let gz = gzip_file(&path).expect("gzip");
+        assert_eq!(gz, dir.path().join("events.json.gz"));
+        let mut decoded = String::new();
+        flate2::read::GzDecoder::new(std::fs::File::open(&gz).unwrap())
+            .read_to_string(&mut decoded)
+            .unwrap();
+        assert_eq!(decoded, json);
+    }
+
+    #[test]
+    fn test_export_rows() {
+        let appointed = appointment_event("王進", "吳郡太守", time_ref("劉宋", "元嘉", 3));
+        let undated = event::Event {
+            time: None,
+            source_file: "a,b.txt".into(),
+            ..death_event("張華", time_ref("劉宋", "元嘉", 1))
+        };
+        let unresolved = death_event("褚淵", time_ref("劉宋", "無此", 1));
+
+        let mut csv = Vec::new();
+        write_export(&mut csv, [&appointed, &undated, &unresolved], ',').unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], EXPORT_COLUMNS.join(","));
+        assert_eq!(
+            lines[1],
+            "王進,Appointment,,吳郡太守,,,,劉宋,元嘉,3,426,t.txt,0"
+        );
+        // blank time cells; the comma in the file name is quoted
+        assert_eq!(lines[2], "張華,Death,卒,,,,,,,,,\"a,b.txt\",0");
+        // an era missing from the table leaves ad_year blank
+        assert_eq!(lines[3], "褚淵,Death,卒,,,,,劉宋,無此,1,,t.txt,0");
+
+        let mut tsv = Vec::new();
+        write_export(&mut tsv, [&undated], '\t').unwrap();
+        let tsv = String::from_utf8(tsv).unwrap();
+        assert_eq!(tsv.lines().nth(1).unwrap().split('\t').count(), 13);
+
+        assert_eq!(export_separator("tsv"), Some('\t'));
+        assert_eq!(export_separator("xlsx"), None);
+    }
+
+    #[test]
+    fn test_events_jsonl_round_trips() {
+        let dir = tempfile::tempdir().unwrap();
+        let events = [
+            appointment_event("王進", "吳郡太守", time_ref("劉宋", "元嘉", 3)),
+            death_event("張華", time_ref("南齊", "永明", 2)),
+        ];
+        write_jsonl(dir.path(), "events.jsonl", &events);
+
+        let text = std::fs::read_to_string(dir.path().join("events.jsonl")).unwrap();
+        let parsed: Vec<event::Event> = text
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed[0].person_name(), Some("王進"));
+        assert_eq!(parsed[1].kind.name(), "Death");
+        assert_eq!(parsed[1].time.as_ref().unwrap().era, "永明");
+    }
+
+    #[test]
+    fn test_gzip_covers_every_file_written() {
+        let corpus = fixture::write_corpus(fixture::SONG_CORPUS);
+        let out = tempfile::tempdir().unwrap();
+        let options = ExtractOptions {
+            out: out.path().to_path_buf(),
+            jsonl: true,
+            gzip: true,
+            ..ExtractOptions::default()
+        };
+        run_extract(&[corpus.path().to_path_buf()], &options);
+        for name in ["events.json", "events.jsonl", "timeline.json"] {
+            assert!(out.path().join(format!("{name}.gz")).exists(), "{name}.gz");
+        }
+    }
 }
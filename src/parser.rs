@@ -1,8 +1,7 @@
 use regex::Regex;
-use std::fs;
 use std::sync::LazyLock;
 
-use crate::scanner::BiographyFile;
+use crate::scanner::{BiographyFile, read_text};
 use crate::surname::split_name;
 use crate::types::*;
 
@@ -23,10 +22,12 @@ use crate::types::*;
 //     世祖武皇帝諱賾，字宣遠，太祖長子也。
 
 // Pattern 1: Official biography opening
-// {FullName}[，]字{Courtesy}[，]{Origin}人[也]。
+// {FullName}[，][小字{Childhood}，]字{Courtesy}[，]{Origin}人[也]。
+// The name is lazy so that 小 of an interposed 小字 is not read as a third
+// name character; courtesy names run to four characters.
 static RE_OFFICIAL: LazyLock<Regex> = LazyLock::new(|| {
     Regex::new(
-        r"^(?P<name>[^\s，。、字]{2,4})[，,]?字(?P<courtesy>[^\s，。]{1,3})[，,](?P<origin>[^\s，。人]+)人"
+        r"^(?P<name>[^\s，。、字]{2,4}?)(?:[，,]?小字[^\s，。、字]{1,4})?[，,]?字(?P<courtesy>[^\s，。]{1,4})[，,](?P<origin>[^\s，。人]+)人"
     ).unwrap()
 });
 
@@ -36,6 +37,17 @@ static RE_OFFICIAL_NO_COURTESY: LazyLock<Regex> = LazyLock::new(|| {
     Regex::new(r"^(?P<name>[^\s，。、字]{2,4})[，,](?P<origin>[^\s，。人字]+)人也").unwrap()
 });
 
+// Residence of an émigré family, in the opening sentence or the one
+// right after it:
+//   本高平人，永嘉之亂，徙居江左
+//   高平金鄉人也。世居京口
+static RE_RESIDENCE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"^[^。]{0,30}?(?:。[^。]{0,30}?)?(?:僑居|寓居|徙居|移居|世居|徙家|家於)(?P<place>[^\s，。；焉之居]{2,4})[，。；焉]",
+    )
+    .unwrap()
+});
+
 // Pattern 2a: Emperor with temple name
 // {TempleName}{Posthumous}皇帝[，]諱{Given}，字{Courtesy}[，小字{Childhood}]
 // Temple names are always exactly 2 chars: 高祖, 太宗, 世祖, etc.
@@ -66,6 +78,16 @@ static RE_DEPOSED: LazyLock<Regex> = LazyLock::new(|| {
 static RE_SURNAME: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r"姓(?P<surname>[^\s，。氏]+)氏").unwrap());
 
+// Name change: 改名X / 更名為X, clause-initial or after the subject's own
+// name (顯後改名). 賜姓 grants a new surname, not a given name, and is
+// not matched.
+static RE_RENAME: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?:^|[，。；\n])(?P<who>[^\s，。；]{0,4}?)(?:改名|更名)(?:為|爲|曰)?(?P<new>[^\s，。；、：字以為爲]{1,2})",
+    )
+    .unwrap()
+});
+
 // ── 載記 ruler patterns ────────────────────────────────────────────
 //
 // These are more relaxed than the official pattern because 載記 figures
@@ -98,11 +120,19 @@ static RE_RULER_NO_COURTESY: LazyLock<Regex> = LazyLock::new(|| {
     Regex::new(r"^(?P<name>[^\s，。字]{2,5})[，,](?P<lineage>[^\s。]+也)").unwrap()
 });
 
-/// Try to parse a person from a biography file.
-pub fn parse_biography(bio: &BiographyFile) -> Option<Person> {
-    let content = fs::read_to_string(&bio.path).ok()?;
-    let source = bio.source.clone();
+/// Try to parse a person from a biography file. Err when the file
+/// cannot be read or decoded (see `read_text`), Ok(None) when no opening
+/// line matches.
+pub fn parse_biography(bio: &BiographyFile) -> std::io::Result<Option<Person>> {
+    let content = read_text(&bio.path)?;
+    Ok(parse_opening(&content, &bio.source).map(|mut person| {
+        add_rename_aliases(&mut person, &content);
+        person
+    }))
+}
 
+/// The person introduced by the first lines of a biography.
+fn parse_opening(content: &str, source: &Source) -> Option<Person> {
     // For 本紀/載記, the person intro may not be on line 1
     // (some files have headers like "武帝上\n梁書卷第一\n..." first).
     // Try each of the first 10 lines.
@@ -120,19 +150,19 @@ pub fn parse_biography(bio: &BiographyFile) -> Option<Person> {
     for line in &lines_to_try {
         // Try emperor patterns first (for 本紀 and 載記)
         if (source.section == Section::BenJi || source.section == Section::ZaiJi)
-            && let Some(p) = try_parse_emperor(line, &content, &source)
+            && let Some(p) = try_parse_emperor(line, content, source)
         {
             return Some(p);
         }
 
         // Try official pattern (most common)
-        if let Some(p) = try_parse_official(line, &source) {
+        if let Some(p) = try_parse_official(line, source) {
             return Some(p);
         }
 
         // Try emperor patterns even in 列傳
         if source.section != Section::BenJi
-            && let Some(p) = try_parse_emperor(line, &content, &source)
+            && let Some(p) = try_parse_emperor(line, content, source)
         {
             return Some(p);
         }
@@ -142,7 +172,7 @@ pub fn parse_biography(bio: &BiographyFile) -> Option<Person> {
     // (only after all strict patterns have failed on all lines)
     if source.section == Section::ZaiJi {
         for line in &lines_to_try {
-            if let Some(p) = try_parse_ruler(line, &source) {
+            if let Some(p) = try_parse_ruler(line, source) {
                 return Some(p);
             }
         }
@@ -151,11 +181,82 @@ pub fn parse_biography(bio: &BiographyFile) -> Option<Person> {
     None
 }
 
+/// Add the given name a biography says its subject took later (改名顯),
+/// with the surname when known, to the subject's aliases.
+fn add_rename_aliases(person: &mut Person, content: &str) {
+    let (surname, given) = match &person.kind {
+        PersonKind::Official {
+            surname,
+            given_name,
+            ..
+        }
+        | PersonKind::Ruler {
+            surname,
+            given_name,
+            ..
+        } => (Some(surname.clone()), given_name.clone()),
+        PersonKind::Emperor {
+            surname,
+            given_name,
+            ..
+        } => (surname.clone(), given_name.clone()),
+        PersonKind::Deposed { given_name, .. } => (None, given_name.clone()),
+    };
+    let full = person.display_name();
+    for caps in RE_RENAME.captures_iter(content) {
+        let who = caps["who"].trim_matches(['後', '乃', '因', '遂', '又', '尋']);
+        let is_subject = if who.is_empty() {
+            // A bare 改名 may rename a place or office (。改名為建康): take
+            // it only in a sentence that names the subject
+            let at = caps.name("who").unwrap().start();
+            let sentence = content[..at].rsplit(['。', '\n']).next().unwrap_or("");
+            sentence.contains(given.as_str()) || sentence.contains(full.as_str())
+        } else {
+            who == given || who == full
+        };
+        if !is_subject {
+            continue;
+        }
+        let new = &caps["new"];
+        if new == given {
+            continue;
+        }
+        let names = surname
+            .as_ref()
+            .map(|s| format!("{s}{new}"))
+            .into_iter()
+            .chain([new.to_string()]);
+        for name in names {
+            if !person.aliases.contains(&name) {
+                person.aliases.push(name);
+            }
+        }
+    }
+}
+
+/// (origin, ancestral origin, current residence) from the origin clause
+/// of an opening and the text after it. The 本/其先 prefix marks an
+/// ancestral home; the ancestral origin is kept apart only when a
+/// residence is also given.
+fn origins(origin: &str, rest: &str) -> (String, Option<String>, Option<String>) {
+    let origin = ["本", "其先"]
+        .iter()
+        .find_map(|p| origin.strip_prefix(p))
+        .unwrap_or(origin)
+        .to_string();
+    let residence = RE_RESIDENCE
+        .captures(rest)
+        .map(|caps| caps["place"].to_string());
+    let ancestral = residence.as_ref().map(|_| origin.clone());
+    (origin, ancestral, residence)
+}
+
 fn try_parse_official(line: &str, source: &Source) -> Option<Person> {
     if let Some(caps) = RE_OFFICIAL.captures(line) {
         let full_name = caps.name("name")?.as_str();
         let courtesy = caps.name("courtesy")?.as_str();
-        let origin = caps.name("origin").map(|m| m.as_str().to_string());
+        let (origin, ancestral_origin, current_residence) =
+            origins(&caps["origin"], &line[caps.get(0)?.end()..]);
 
         let (surname, given_name) = split_name(full_name)?;
 
@@ -164,7 +265,9 @@ fn try_parse_official(line: &str, source: &Source) -> Option<Person> {
                 surname,
                 given_name,
                 courtesy_name: CourtesyName::Recorded(courtesy.to_string()),
-                origin,
+                origin: Some(origin),
+                ancestral_origin,
+                current_residence,
             },
             source: source.clone(),
             aliases: Vec::new(),
@@ -176,7 +279,8 @@ fn try_parse_official(line: &str, source: &Source) -> Option<Person> {
     // Try without courtesy name
     if let Some(caps) = RE_OFFICIAL_NO_COURTESY.captures(line) {
         let full_name = caps.name("name")?.as_str();
-        let origin = caps.name("origin").map(|m| m.as_str().to_string());
+        let (origin, ancestral_origin, current_residence) =
+            origins(&caps["origin"], &line[caps.get(0)?.end()..]);
 
         let (surname, given_name) = split_name(full_name)?;
 
@@ -185,7 +289,9 @@ fn try_parse_official(line: &str, source: &Source) -> Option<Person> {
                 surname,
                 given_name,
                 courtesy_name: CourtesyName::NotRecorded,
-                origin,
+                origin: Some(origin),
+                ancestral_origin,
+                current_residence,
             },
             source: source.clone(),
             aliases: Vec::new(),
@@ -433,3 +539,102 @@ fn extract_temple_from_juan(juan: &str) -> Option<String> {
     }
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn official(line: &str) -> Option<(&str, &str)> {
+        let caps = RE_OFFICIAL.captures(line)?;
+        Some((caps.name("name")?.as_str(), caps.name("courtesy")?.as_str()))
+    }
+
+    #[test]
+    fn test_official_name_courtesy_boundary() {
+        assert_eq!(
+            official("柳世隆字彥緒，河東解人也。"),
+            Some(("柳世隆", "彥緒"))
+        );
+        assert_eq!(
+            official("王進，字子孝，琅邪臨沂人也。"),
+            Some(("王進", "子孝"))
+        );
+        // 小字 between the name and 字 is neither name nor courtesy
+        assert_eq!(
+            official("王進小字阿奴，字子孝，琅邪臨沂人也。"),
+            Some(("王進", "子孝"))
+        );
+    }
+
+    #[test]
+    fn test_official_ancestral_origin_and_residence() {
+        let source = Source {
+            book: Book::SongShu,
+            section: Section::LieZhuan,
+            juan: "列傳".into(),
+            file_path: "t".into(),
+        };
+        let places = |line: &str| match try_parse_official(line, &source).map(|p| p.kind) {
+            Some(PersonKind::Official {
+                origin,
+                ancestral_origin,
+                current_residence,
+                ..
+            }) => (origin, ancestral_origin, current_residence),
+            other => panic!("not an official: {other:?}"),
+        };
+        assert_eq!(
+            places("王進字子孝，本高平人，永嘉之亂，徙居江左。"),
+            (
+                Some("高平".into()),
+                Some("高平".into()),
+                Some("江左".into())
+            )
+        );
+        assert_eq!(
+            places("王進字子孝，高平金鄉人也。世居京口。"),
+            (
+                Some("高平金鄉".into()),
+                Some("高平金鄉".into()),
+                Some("京口".into())
+            )
+        );
+        // a plain origin is not split
+        assert_eq!(
+            places("王進字子孝，琅邪臨沂人也。"),
+            (Some("琅邪臨沂".into()), None, None)
+        );
+    }
+
+    #[test]
+    fn test_rename_adds_alias() {
+        let source = Source {
+            book: Book::NanQiShu,
+            section: Section::LieZhuan,
+            juan: "列傳".into(),
+            file_path: "t".into(),
+        };
+        let content = "褚淵字彥回，河南陽翟人也。\n淵後改名顯。賜姓蕭氏。其子更名賁。\n\
+                       自是都邑鎮於秣陵。孫權遷都，改名為建康。\n\
+                       淵既登朝，改名曰彥。";
+        let mut person = parse_opening(content, &source).expect("official");
+        add_rename_aliases(&mut person, content);
+        assert!(person.aliases.contains(&"顯".to_string()));
+        assert!(person.aliases.contains(&"褚顯".to_string()));
+        // a bare 改名 in a sentence about the subject
+        assert!(person.aliases.contains(&"褚彥".to_string()));
+        // a renamed city is not an alias
+        assert!(!person.aliases.iter().any(|a| a.contains('建')));
+        // a son's rename and a surname grant are not the subject's
+        assert!(!person.aliases.iter().any(|a| a.contains('賁')));
+        assert!(!person.aliases.iter().any(|a| a.contains('蕭')));
+    }
+
+    #[test]
+    fn test_official_four_char_courtesy() {
+        assert_eq!(
+            official("王進字子孝伯玉，琅邪臨沂人也。"),
+            Some(("王進", "子孝伯玉"))
+        );
+    }
+}
@@ -3,7 +3,10 @@
 //! This module provides the mapping needed to disambiguate era names:
 //! the same era name (e.g. 太和) can belong to different regimes,
 //! and we scope each usage to a specific regime based on which book
-//! (晉書/宋書/etc.) the text comes from.
+//! (晉書/宋書/etc.) the text comes from, and on whether the era lasted
+//! as many years as the text counts.
+
+use std::sync::LazyLock;
 
 use serde::Serialize;
 
@@ -22,6 +25,12 @@ pub enum Regime {
     Liang,       // 梁
     Chen,        // 陳
     NorthernWei, // 北魏
+    // ── After the 北魏 split (534) ──
+    EasternWei,   // 東魏
+    WesternWei,   // 西魏
+    NorthernQi,   // 北齊
+    NorthernZhou, // 北周
+    Sui,          // 隋
     // ── Sixteen Kingdoms (十六國) ──
     HanZhao,       // 漢趙 (前趙)
     LaterZhao,     // 後趙
@@ -51,6 +60,11 @@ impl Regime {
             Self::Liang => "梁",
             Self::Chen => "陳",
             Self::NorthernWei => "北魏",
+            Self::EasternWei => "東魏",
+            Self::WesternWei => "西魏",
+            Self::NorthernQi => "北齊",
+            Self::NorthernZhou => "北周",
+            Self::Sui => "隋",
             Self::HanZhao => "漢趙",
             Self::LaterZhao => "後趙",
             Self::ChengHan => "成漢",
@@ -80,6 +94,11 @@ impl Regime {
             Self::Liang => 502,
             Self::Chen => 557,
             Self::NorthernWei => 386,
+            Self::EasternWei => 534,
+            Self::WesternWei => 535,
+            Self::NorthernQi => 550,
+            Self::NorthernZhou => 557,
+            Self::Sui => 581,
             Self::HanZhao => 304,
             Self::LaterZhao => 319,
             Self::ChengHan => 304,
@@ -98,6 +117,25 @@ impl Regime {
             Self::NorthernYan => 407,
         }
     }
+
+    /// The regime that took over by dynastic succession (禪讓/篡位), for
+    /// the southern line 西晉→東晉→劉宋→南齊→梁→陳 and the two northern
+    /// lines 東魏→北齊 and 西魏→北周→隋. Conquests between concurrent
+    /// states are not successions, and neither is the split of 北魏
+    /// into 東魏 and 西魏.
+    pub fn successor(&self) -> Option<Regime> {
+        match self {
+            Self::WesternJin => Some(Self::EasternJin),
+            Self::EasternJin => Some(Self::LiuSong),
+            Self::LiuSong => Some(Self::SouthernQi),
+            Self::SouthernQi => Some(Self::Liang),
+            Self::Liang => Some(Self::Chen),
+            Self::EasternWei => Some(Self::NorthernQi),
+            Self::WesternWei => Some(Self::NorthernZhou),
+            Self::NorthernZhou => Some(Self::Sui),
+            _ => None,
+        }
+    }
 }
 
 // ── Era name entry ───────────────────────────────────────────────────
@@ -109,10 +147,16 @@ pub struct EraEntry {
     /// First AD year of this era (e.g. 424 for 元嘉).
     pub start_ad: u16,
     /// Last AD year of this era (e.g. 453 for 元嘉).
-    #[allow(dead_code)]
     pub end_ad: u16,
 }
 
+impl EraEntry {
+    /// Whether regnal year `year` (1-based) falls within this era's span.
+    pub fn covers_year(&self, year: u8) -> bool {
+        year >= 1 && self.start_ad + u16::from(year) - 1 <= self.end_ad
+    }
+}
+
 /// Master list of era names for the Six Dynasties period.
 /// Ordered by regime, then chronologically within each regime.
 /// This is the disambiguation table: given an era name + the source Book,
@@ -779,6 +823,173 @@ pub static ERA_NAMES: &[EraEntry] = &[
         start_ad: 532,
         end_ad: 534,
     },
+    // ── 東魏 ──
+    EraEntry {
+        name: "天平",
+        regime: Regime::EasternWei,
+        start_ad: 534,
+        end_ad: 537,
+    },
+    EraEntry {
+        name: "元象",
+        regime: Regime::EasternWei,
+        start_ad: 538,
+        end_ad: 539,
+    },
+    EraEntry {
+        name: "興和",
+        regime: Regime::EasternWei,
+        start_ad: 539,
+        end_ad: 542,
+    },
+    EraEntry {
+        name: "武定",
+        regime: Regime::EasternWei,
+        start_ad: 543,
+        end_ad: 550,
+    },
+    // ── 西魏 ──
+    EraEntry {
+        name: "大統",
+        regime: Regime::WesternWei,
+        start_ad: 535,
+        end_ad: 551,
+    },
+    // ── 北齊 ──
+    EraEntry {
+        name: "天保",
+        regime: Regime::NorthernQi,
+        start_ad: 550,
+        end_ad: 559,
+    },
+    EraEntry {
+        name: "乾明",
+        regime: Regime::NorthernQi,
+        start_ad: 560,
+        end_ad: 560,
+    },
+    EraEntry {
+        name: "皇建",
+        regime: Regime::NorthernQi,
+        start_ad: 560,
+        end_ad: 561,
+    },
+    EraEntry {
+        name: "太寧",
+        regime: Regime::NorthernQi,
+        start_ad: 561,
+        end_ad: 562,
+    },
+    EraEntry {
+        name: "河清",
+        regime: Regime::NorthernQi,
+        start_ad: 562,
+        end_ad: 565,
+    },
+    EraEntry {
+        name: "天統",
+        regime: Regime::NorthernQi,
+        start_ad: 565,
+        end_ad: 569,
+    },
+    EraEntry {
+        name: "武平",
+        regime: Regime::NorthernQi,
+        start_ad: 570,
+        end_ad: 576,
+    },
+    EraEntry {
+        name: "隆化",
+        regime: Regime::NorthernQi,
+        start_ad: 576,
+        end_ad: 576,
+    },
+    EraEntry {
+        name: "德昌",
+        regime: Regime::NorthernQi,
+        start_ad: 576,
+        end_ad: 576,
+    },
+    EraEntry {
+        name: "承光",
+        regime: Regime::NorthernQi,
+        start_ad: 577,
+        end_ad: 577,
+    },
+    // ── 北周 ──
+    EraEntry {
+        name: "武成",
+        regime: Regime::NorthernZhou,
+        start_ad: 559,
+        end_ad: 560,
+    },
+    EraEntry {
+        name: "保定",
+        regime: Regime::NorthernZhou,
+        start_ad: 561,
+        end_ad: 565,
+    },
+    EraEntry {
+        name: "天和",
+        regime: Regime::NorthernZhou,
+        start_ad: 566,
+        end_ad: 572,
+    },
+    EraEntry {
+        name: "建德",
+        regime: Regime::NorthernZhou,
+        start_ad: 572,
+        end_ad: 578,
+    },
+    EraEntry {
+        name: "宣政",
+        regime: Regime::NorthernZhou,
+        start_ad: 578,
+        end_ad: 578,
+    },
+    EraEntry {
+        name: "大成",
+        regime: Regime::NorthernZhou,
+        start_ad: 579,
+        end_ad: 579,
+    },
+    EraEntry {
+        name: "大象",
+        regime: Regime::NorthernZhou,
+        start_ad: 579,
+        end_ad: 580,
+    },
+    EraEntry {
+        name: "大定",
+        regime: Regime::NorthernZhou,
+        start_ad: 581,
+        end_ad: 581,
+    },
+    // ── 隋 ──
+    EraEntry {
+        name: "開皇",
+        regime: Regime::Sui,
+        start_ad: 581,
+        end_ad: 600,
+    },
+    EraEntry {
+        name: "仁壽",
+        regime: Regime::Sui,
+        start_ad: 601,
+        end_ad: 604,
+    },
+    EraEntry {
+        name: "大業",
+        regime: Regime::Sui,
+        start_ad: 605,
+        end_ad: 618,
+    },
+    EraEntry {
+        name: "義寧",
+        regime: Regime::Sui,
+        start_ad: 617,
+        end_ad: 618,
+    },
     // ── 漢趙 (前趙) ──
     EraEntry {
         name: "元熙",
@@ -1183,6 +1394,48 @@ pub fn resolve_era(era_name: &str, book: Book) -> Option<Regime> {
     None
 }
 
+/// Resolve an era name with its regnal year. Regimes whose era of that
+/// name did not last `year` years are ruled out first (太和二十年 cannot
+/// be 東晉's six-year 太和), then the Book's default regime is preferred
+/// among the rest. Falls back to `resolve_era` when no span covers the
+/// year, as texts sometimes keep counting an era after it was changed.
+pub fn resolve_era_year(era_name: &str, year: u8, book: Book) -> Option<Regime> {
+    let default = default_regime(book);
+    let covering: Vec<&EraEntry> = ERA_NAMES
+        .iter()
+        .filter(|e| e.name == era_name && e.covers_year(year))
+        .collect();
+    covering
+        .iter()
+        .find(|e| e.regime == default)
+        .or(covering.first())
+        .map(|e| e.regime)
+        .or_else(|| resolve_era(era_name, book))
+}
+
+/// Whether `year` of `regime`'s era `era_name` lies past the era's end
+/// (元嘉四十三年: 元嘉 lasted 30 years). False when the table does not
+/// know the era, as there is nothing to check against.
+pub fn exceeds_era_span(regime: Regime, era_name: &str, year: u8) -> bool {
+    let mut spans = ERA_NAMES
+        .iter()
+        .filter(|e| e.regime == regime && e.name == era_name)
+        .peekable();
+    spans.peek().is_some() && !spans.any(|e| e.covers_year(year))
+}
+
+/// Whether `name` is an era in `ERA_NAMES`, of any regime.
+pub fn is_known_era(name: &str) -> bool {
+    ERA_NAMES.iter().any(|e| e.name == name)
+}
+
+/// `build_era_regex`, built once per process. The table is static, so
+/// every scanner can share the same alternation.
+pub fn era_regex() -> &'static str {
+    static ERA_RE: LazyLock<String> = LazyLock::new(build_era_regex);
+    &ERA_RE
+}
+
 /// Build a regex alternation matching any known era name.
 /// Sorted by length descending so "太平真君" matches before "太平".
 pub fn build_era_regex() -> String {
@@ -1191,3 +1444,112 @@ pub fn build_era_regex() -> String {
     names.dedup();
     format!("(?:{})", names.join("|"))
 }
+
+// ── Table validation ─────────────────────────────────────────────────
+
+/// A break between two consecutive eras of one regime in `ERA_NAMES`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Gap {
+    pub prev_era: &'static str,
+    pub next_era: &'static str,
+    pub prev_end_ad: u16,
+    pub next_start_ad: u16,
+}
+
+impl Gap {
+    /// True if the next era starts before the previous one ended
+    /// (as opposed to leaving uncovered years in between).
+    #[allow(dead_code)]
+    pub fn is_overlap(&self) -> bool {
+        self.next_start_ad < self.prev_end_ad
+    }
+}
+
+/// Check that consecutive eras of a regime (in table order) join up.
+///
+/// A new era may start in the year the previous one ended (mid-year
+/// change of era name) or the year after; anything else is reported.
+#[allow(dead_code)] // table self-check, exercised by tests
+pub fn regime_era_continuity(regime: Regime) -> Vec<Gap> {
+    let eras: Vec<&EraEntry> = ERA_NAMES.iter().filter(|e| e.regime == regime).collect();
+    eras.windows(2)
+        .filter(|w| {
+            let (prev, next) = (w[0], w[1]);
+            next.start_ad != prev.end_ad && next.start_ad != prev.end_ad + 1
+        })
+        .map(|w| Gap {
+            prev_era: w[0].name,
+            next_era: w[1].name,
+            prev_end_ad: w[0].end_ad,
+            next_start_ad: w[1].start_ad,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_era_year_rules_out_short_eras() {
+        // 東晉 太和 ran 366–371: six years only
+        assert_eq!(resolve_era("太和", Book::JinShu), Some(Regime::EasternJin));
+        assert_eq!(
+            resolve_era_year("太和", 20, Book::JinShu),
+            Some(Regime::NorthernWei)
+        );
+        // within both spans the Book still decides
+        assert_eq!(
+            resolve_era_year("太和", 3, Book::JinShu),
+            Some(Regime::EasternJin)
+        );
+        assert_eq!(
+            resolve_era_year("太和", 3, Book::WeiShu),
+            Some(Regime::NorthernWei)
+        );
+        // no span long enough: fall back to the Book default
+        assert_eq!(
+            resolve_era_year("太和", 40, Book::JinShu),
+            Some(Regime::EasternJin)
+        );
+    }
+
+    #[test]
+    fn test_liu_song_eras_continuous() {
+        assert_eq!(regime_era_continuity(Regime::LiuSong), Vec::new());
+        let song: Vec<&EraEntry> = ERA_NAMES
+            .iter()
+            .filter(|e| e.regime == Regime::LiuSong)
+            .collect();
+        assert_eq!(song.first().unwrap().start_ad, 420);
+        assert_eq!(song.last().unwrap().end_ad, 479);
+    }
+
+    #[test]
+    fn test_northern_wei_yongxing_duplicate_flagged() {
+        // The second 北魏 永興 (AD532) carries the 409–413 span of the first.
+        let gaps = regime_era_continuity(Regime::NorthernWei);
+        assert!(
+            gaps.iter()
+                .any(|g| g.prev_era == "太昌" && g.next_era == "永興" && g.is_overlap()),
+            "{gaps:?}"
+        );
+    }
+
+    #[test]
+    fn test_post_wei_regimes() {
+        assert_eq!(resolve_era("天保", Book::WeiShu), Some(Regime::NorthernQi));
+        assert_eq!(resolve_era("開皇", Book::WeiShu), Some(Regime::Sui));
+        // 北齊 太寧 does not shadow 東晉's in 晉書
+        assert_eq!(resolve_era("太寧", Book::JinShu), Some(Regime::EasternJin));
+        assert_eq!(regime_era_continuity(Regime::NorthernQi), Vec::new());
+        assert_eq!(regime_era_continuity(Regime::NorthernZhou), Vec::new());
+        // 北魏 splits rather than passing on; each half has its own line
+        assert_eq!(Regime::NorthernWei.successor(), None);
+        assert_eq!(Regime::EasternWei.successor(), Some(Regime::NorthernQi));
+        assert_eq!(
+            Regime::WesternWei.successor().and_then(|r| r.successor()),
+            Some(Regime::Sui)
+        );
+    }
+}
@@ -1,3 +1,4 @@
+use std::io;
 use std::path::{Path, PathBuf};
 use walkdir::WalkDir;
 
@@ -124,6 +125,51 @@ pub fn scan_corpus(root: &Path) -> Vec<BiographyFile> {
     results
 }
 
+/// Scan several corpus roots (books kept in separate directories, or
+/// supplementary texts) into one file list. A file reachable from more
+/// than one root, through overlapping roots or symlinks, is kept once.
+pub fn scan_corpora(roots: &[PathBuf]) -> Vec<BiographyFile> {
+    let mut seen = std::collections::HashSet::new();
+    roots
+        .iter()
+        .flat_map(|root| scan_corpus(root))
+        .filter(|bio| seen.insert(std::fs::canonicalize(&bio.path).unwrap_or(bio.path.clone())))
+        .collect()
+}
+
+/// Read a corpus text file.
+///
+/// Honors a byte-order mark: a UTF-8 BOM is stripped (it would otherwise
+/// break the `^`-anchored opening patterns), and UTF-16 LE/BE files are
+/// decoded. Anything else must be valid UTF-8.
+pub fn read_text(path: &Path) -> io::Result<String> {
+    let bytes = std::fs::read(path)?;
+    let invalid = |e: std::string::FromUtf16Error| io::Error::new(io::ErrorKind::InvalidData, e);
+    match bytes.as_slice() {
+        [0xEF, 0xBB, 0xBF, rest @ ..] => String::from_utf8(rest.to_vec())
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)),
+        [0xFF, 0xFE, rest @ ..] => {
+            String::from_utf16(&utf16_units(rest, u16::from_le_bytes)).map_err(invalid)
+        }
+        [0xFE, 0xFF, rest @ ..] => {
+            String::from_utf16(&utf16_units(rest, u16::from_be_bytes)).map_err(invalid)
+        }
+        _ => String::from_utf8(bytes).map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("not UTF-8 (GBK/Big5?): {}", e.utf8_error()),
+            )
+        }),
+    }
+}
+
+fn utf16_units(bytes: &[u8], decode: fn([u8; 2]) -> u16) -> Vec<u16> {
+    bytes
+        .chunks_exact(2)
+        .map(|c| decode([c[0], c[1]]))
+        .collect()
+}
+
 /// Strip leading "NN_" prefix from filenames.
 fn strip_numeric_prefix(s: &str) -> &str {
     if let Some(idx) = s.find('_') {
@@ -139,3 +185,65 @@ fn strip_numeric_prefix(s: &str) -> &str {
 fn is_year_file(name: &str) -> bool {
     name.ends_with("年") || name.ends_with("年餘")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(bytes: &[u8]) -> tempfile::TempPath {
+        let path = tempfile::NamedTempFile::new().unwrap().into_temp_path();
+        std::fs::write(&path, bytes).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_scan_corpora_merges_roots() {
+        use crate::fixture::{FixtureFile, write_corpus};
+
+        let song = write_corpus(&[FixtureFile {
+            path: "宋書/02_列傳/02_列傳第三　傅亮/03_傅亮.txt",
+            text: "傅亮字季友，北地靈州人也。\n",
+        }]);
+        let qi = write_corpus(&[FixtureFile {
+            path: "南齊書/01_列傳/03_列傳第四　褚淵/01_褚淵.txt",
+            text: "褚淵字彥回，河南陽翟人也。\n",
+        }]);
+        let roots = [song.path().to_path_buf(), qi.path().to_path_buf()];
+        let books: Vec<Book> = scan_corpora(&roots).iter().map(|b| b.source.book).collect();
+        assert_eq!(books.len(), 2);
+        assert!(books.contains(&Book::SongShu) && books.contains(&Book::NanQiShu));
+
+        // the same root twice yields each file once
+        let twice = [song.path().to_path_buf(), song.path().to_path_buf()];
+        assert_eq!(scan_corpora(&twice).len(), 1);
+    }
+
+    #[test]
+    fn test_non_utf8_file_reported() {
+        // "褚淵" in GBK
+        let path = fixture(&[0xF1, 0xD2, 0xD4, 0xA8]);
+        let bio = BiographyFile {
+            source: Source {
+                book: Book::NanQiShu,
+                section: Section::LieZhuan,
+                juan: "列傳第四　褚淵".into(),
+                file_path: path.to_path_buf(),
+            },
+            path: path.to_path_buf(),
+        };
+        let err = crate::parser::parse_biography(&bio)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("not UTF-8"), "{err}");
+    }
+
+    #[test]
+    fn test_bom_files_decoded() {
+        let utf8 = fixture("\u{FEFF}褚淵".as_bytes());
+        let mut le = vec![0xFF, 0xFE];
+        le.extend("褚淵".encode_utf16().flat_map(u16::to_le_bytes));
+        let utf16 = fixture(&le);
+        assert_eq!(read_text(&utf8).unwrap(), "褚淵");
+        assert_eq!(read_text(&utf16).unwrap(), "褚淵");
+    }
+}
@@ -42,9 +42,16 @@ pub fn build_name_regex(extra: &[String]) -> String {
         let chars: Vec<char> = s.chars().collect();
         if chars.len() >= 2 {
             // Already in COMPOUND_SURNAMES?
-            if !COMPOUND_SURNAMES.contains(&s.as_str()) {
-                compounds.push(Box::leak(s.clone().into_boxed_str()));
+            if COMPOUND_SURNAMES.contains(&s.as_str()) || compounds.contains(&s.as_str()) {
+                continue;
             }
+            // Unknown compounds usually come from a mis-parse that glued
+            // surname + given name together; letting them in over-matches.
+            if !is_plausible_compound_surname(s) {
+                eprintln!("  rejected implausible extra surname: {s}");
+                continue;
+            }
+            compounds.push(Box::leak(s.clone().into_boxed_str()));
         } else if chars.len() == 1 && !singles.contains(&chars[0]) {
             singles.push(chars[0]);
         }
@@ -74,6 +81,32 @@ pub fn build_name_regex(extra: &[String]) -> String {
     }
 }
 
+/// Check whether an unlisted multi-char string could be a compound surname.
+///
+/// Accepts only two common (BMP CJK) ideographs, and rejects the typical
+/// parse-error shape of a known single surname followed by a non-surname
+/// char (e.g. "王進" = 王 + 進), which is a full name, not a surname.
+fn is_plausible_compound_surname(s: &str) -> bool {
+    let chars: Vec<char> = s.chars().collect();
+    if chars.len() != 2 {
+        return false;
+    }
+    let is_common_ideograph = |c: char| ('\u{4E00}'..='\u{9FFF}').contains(&c);
+    if !chars.iter().all(|&c| is_common_ideograph(c)) {
+        return false;
+    }
+    !SINGLE_SURNAMES.contains(&chars[0]) || SINGLE_SURNAMES.contains(&chars[1])
+}
+
+/// Whether `s` is a surname in the built-in tables.
+pub fn is_known_surname(s: &str) -> bool {
+    let mut chars = s.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => SINGLE_SURNAMES.contains(&c),
+        _ => COMPOUND_SURNAMES.contains(&s),
+    }
+}
+
 /// Given a full name string (e.g. "褚淵", "司馬褧"), split into (surname, given_name).
 /// Returns None if the string is too short.
 pub fn split_name(full_name: &str) -> Option<(String, String)> {
@@ -119,4 +152,21 @@ mod tests {
     fn test_two_char_given() {
         assert_eq!(split_name("柳世隆"), Some(("柳".into(), "世隆".into())));
     }
+
+    #[test]
+    fn test_bogus_extra_surname_excluded() {
+        // "王進" is surname + given glued together by a parse error
+        let re = build_name_regex(&["王進".into(), "劉裕之".into()]);
+        assert!(!re.contains("王進"));
+        assert!(!re.contains("劉裕之"));
+        let compiled = regex::Regex::new(&re).expect("name regex compiles");
+        assert!(compiled.is_match("王猛"));
+    }
+
+    #[test]
+    fn test_plausible_extra_surname_kept() {
+        let re = build_name_regex(&["乞伏".into(), "賀蘭".into()]);
+        assert!(re.contains("乞伏"));
+        assert!(re.contains("賀蘭"));
+    }
 }
@@ -1,3 +1,7 @@
+use std::sync::LazyLock;
+
+use regex::Regex;
+
 /// Title suffixes: the final 2-3 characters of compound official titles.
 /// E.g. "前將軍" ends with "將軍", "青州刺史" ends with "刺史".
 /// These are used as anchors: when a title suffix appears, the next 2-4 chars
@@ -23,6 +27,46 @@ pub const STANDALONE_TITLES: &[&str] = &[
     "國子", "秘書", "著作",
 ];
 
+/// Leading words of office titles whose first char is also a surname, so
+/// "黃門侍郎" or "散騎常侍" can pass for name + title. A name candidate
+/// starting with one of these is an office, not a person.
+pub const OFFICE_HEADS: &[&str] = &[
+    "黃門", "散騎", "尚書", "中書", "秘書", "太子", "司徒", "司空", "太尉", "光祿", "金紫", "車騎",
+    "驃騎", "冠軍", "輔國", "寧朔", "建威", "振威", "龍驤", "征虜", "寧遠", "安遠", "明威", "國子",
+    "給事", "通直", "員外", "奉朝", "太常", "廷尉", "衞尉", "衛尉", "御史", "都官", "吏部", "度支",
+    "殿中", "左衞", "右衞", "左衛", "右衛",
+];
+
+/// Whether `s` names an office: it contains a title suffix or standalone title.
+pub fn is_office(s: &str) -> bool {
+    TITLE_SUFFIXES
+        .iter()
+        .chain(STANDALONE_TITLES)
+        .any(|t| s.contains(t))
+}
+
+/// The title suffix or standalone title an office is classed under: the
+/// longest one `title` ends with (青州刺史 → 刺史), else the last one it
+/// contains (尚書令 → 尚書). None when `title` names no known office.
+pub fn office_class(title: &str) -> Option<&'static str> {
+    let all = || TITLE_SUFFIXES.iter().chain(STANDALONE_TITLES).copied();
+    all()
+        .filter(|t| title.ends_with(t))
+        .max_by_key(|t| t.len())
+        .or_else(|| {
+            all()
+                .filter_map(|t| title.rfind(t).map(|i| (i, t)))
+                .max_by_key(|&(i, t)| (i, t.len()))
+                .map(|(_, t)| t)
+        })
+}
+
+/// `build_title_regex`, built once per process.
+pub fn title_regex() -> &'static str {
+    static TITLE_RE: LazyLock<String> = LazyLock::new(build_title_regex);
+    &TITLE_RE
+}
+
 /// Build a regex fragment matching any title suffix or standalone title.
 /// Sorted by length descending so longer patterns match first.
 pub fn build_title_regex() -> String {
@@ -36,3 +80,40 @@ pub fn build_title_regex() -> String {
 
     format!("(?:{})", all.join("|"))
 }
+
+/// 都督/督/監 {provinces} 諸軍事: a military command over named provinces.
+static RE_COMMAND: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?:都督|督|監)([^，。；：\s]{1,30}?)諸軍事").expect("command regex")
+});
+
+/// Provinces under the 都督X諸軍事 commands in `text`, in the order named,
+/// expanded from the compressed listing: 南北兗青徐冀五州 → 南兗州, 北兗州,
+/// 青州, 徐州, 冀州. A province only partly covered (豫州之梁郡) still
+/// counts; the commanderies named after 之 end the listing. Commands over
+/// no named province (中外, 京畿, 十八州) give nothing.
+pub fn command_provinces(text: &str) -> Vec<String> {
+    let mut provinces: Vec<String> = Vec::new();
+    for caps in RE_COMMAND.captures_iter(text) {
+        let listing = caps[1].split('之').next().unwrap_or_default();
+        for part in listing.split_inclusive('州') {
+            let Some(names) = part.strip_suffix('州') else {
+                break;
+            };
+            let mut prefixes = Vec::new();
+            for c in names
+                .trim_end_matches(|c| "一二三四五六七八九十".contains(c))
+                .chars()
+            {
+                match c {
+                    '南' | '北' | '東' => prefixes.push(c),
+                    '、' => {}
+                    _ if prefixes.is_empty() => provinces.push(format!("{c}州")),
+                    _ => provinces.extend(prefixes.drain(..).map(|p| format!("{p}{c}州"))),
+                }
+            }
+        }
+    }
+    let mut seen = std::collections::HashSet::new();
+    provinces.retain(|p| seen.insert(p.clone()));
+    provinces
+}
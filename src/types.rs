@@ -130,6 +130,11 @@ pub enum PersonKind {
         given_name: String,
         courtesy_name: CourtesyName,
         origin: Option<String>,
+        /// 本籍 of an émigré family ("本高平人" / "其先高平人"), set only
+        /// when the biography also says where it moved to
+        ancestral_origin: Option<String>,
+        /// Where the family settled: 僑居/徙居/世居 Y
+        current_residence: Option<String>,
     },
     /// Deposed emperor / prince with 諱 but no full temple name
     Deposed {